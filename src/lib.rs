@@ -5,4 +5,6 @@ pub mod midi;
 pub mod noise;
 pub mod jar;
 pub mod pipe;
-pub mod symbol_table;
\ No newline at end of file
+pub mod symbol_table;
+pub mod synth;
+pub mod theory;
\ No newline at end of file
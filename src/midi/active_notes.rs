@@ -0,0 +1,78 @@
+use serde::{Serialize, Deserialize};
+
+use crate::pipe::Pipe;
+
+use super::event::MidiEvent;
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[repr(C)]
+pub struct ActiveNotes
+{
+    channels: [u128; 16],
+}
+
+impl ActiveNotes
+{
+    pub fn new() -> ActiveNotes
+    {
+        ActiveNotes::default()
+    }
+
+    pub fn process(&mut self, event: &MidiEvent)
+    {
+        match *event
+        {
+            MidiEvent::NoteOn { channel, pitch, velocity } if velocity > 0 => self.note_on(channel, pitch),
+            MidiEvent::NoteOn { channel, pitch, .. } | MidiEvent::NoteOff { channel, pitch, .. } => self.note_off(channel, pitch),
+            MidiEvent::ControllerChange { channel, controller: 120 | 123, .. } => self.channels[channel as usize & 0x0F] = 0,
+            _ => {}
+        }
+    }
+
+    pub fn note_on(&mut self, channel: u8, pitch: u8)
+    {
+        self.channels[channel as usize & 0x0F] |= 1u128 << (pitch & 0x7F);
+    }
+
+    pub fn note_off(&mut self, channel: u8, pitch: u8)
+    {
+        self.channels[channel as usize & 0x0F] &= !(1u128 << (pitch & 0x7F));
+    }
+
+    pub fn is_active(&self, channel: u8, pitch: u8) -> bool
+    {
+        self.channels[channel as usize & 0x0F] & (1u128 << (pitch & 0x7F)) != 0
+    }
+
+    pub fn is_empty(&self) -> bool
+    {
+        self.channels.iter().all(|&c| c == 0)
+    }
+
+    pub fn len(&self) -> usize
+    {
+        self.channels.iter().map(|c| c.count_ones() as usize).sum()
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (u8, u8)> + '_
+    {
+        (0..16u8).flat_map(move |channel|
+        {
+            (0..128u8).filter(move |&pitch| self.is_active(channel, pitch)).map(move |pitch| (channel, pitch))
+        })
+    }
+
+    pub fn clear(&mut self)
+    {
+        self.channels = [0; 16];
+    }
+
+    pub fn release_all<P: Pipe<MidiEvent>>(&mut self, midi_pipe: &mut P)
+    {
+        for (channel, pitch) in self.iter()
+        {
+            midi_pipe.send(MidiEvent::NoteOff { channel, pitch, velocity: 0 });
+        }
+        self.clear();
+    }
+}
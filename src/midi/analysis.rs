@@ -0,0 +1,269 @@
+use serde::{Serialize, Deserialize};
+
+use crate::theory::{chord::Chord, key::{Key, KeyEstimate}};
+
+use super::{event::{MidiEvent, PERCUSSION_CHANNEL}, midi_song::MidiSong, note::Note, tempo_map::{BarBeat, TempoMap}};
+
+#[derive(Debug, Copy, Clone, PartialEq, Default, Serialize, Deserialize)]
+#[repr(C)]
+pub struct TrackStats
+{
+    pub track: usize,
+    pub note_count: usize,
+    pub pitch_range: Option<(u8, u8)>,
+    pub average_velocity: f32,
+    pub notes_per_second: f32,
+    pub channels: u16,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Default, Serialize, Deserialize)]
+#[repr(C)]
+pub struct BeatChord
+{
+    pub tick: usize,
+    pub bar_beat: BarBeat,
+    pub chord: Option<Chord>,
+}
+
+#[derive(Debug, Clone, PartialEq, Default, Serialize, Deserialize)]
+#[repr(C)]
+pub struct SongAnalysis
+{
+    pub key: Key,
+    pub key_from_signature: bool,
+    pub estimated_key: KeyEstimate,
+    pub time_signature: (u8, u8),
+    pub time_signature_from_events: bool,
+    pub initial_bpm: f64,
+    pub average_bpm: f64,
+    pub duration_seconds: f64,
+    pub tracks: Vec<TrackStats>,
+    pub chords: Vec<BeatChord>,
+}
+
+fn is_pitched(note: &Note) -> bool
+{
+    note.channel & 0x0F != PERCUSSION_CHANNEL
+}
+
+fn pitch_class_weights<'a, I: IntoIterator<Item = &'a Note>>(notes: I) -> [f32; 12]
+{
+    let mut weights = [0.0; 12];
+    for note in notes.into_iter().filter(|n| is_pitched(n))
+    {
+        // zero length notes still count a little so grace notes are not lost entirely
+        weights[(note.pitch % 12) as usize] += note.duration_ticks().max(1) as f32;
+    }
+    weights
+}
+
+fn beat_ticks(tempo_map: &TempoMap, end_tick: usize) -> Vec<usize>
+{
+    let mut ticks = Vec::new();
+    let mut tick = 0;
+    while tick < end_tick
+    {
+        ticks.push(tick);
+        let time_signature = tempo_map.time_signature_at(tick);
+        let next_change = tempo_map.time_signatures.iter().map(|t| t.tick).find(|&t| t > tick).unwrap_or(usize::MAX);
+        tick = (tick + tempo_map.ticks_per_beat(&time_signature).max(1)).min(next_change);
+    }
+    ticks
+}
+
+// sums the onset velocity on every quarter note, counted from tick 0 so pickup bars shift the phase
+fn beat_accents(notes: &[Note], ticks_per_quarter_note: f64) -> Vec<f32>
+{
+    let mut accents = Vec::new();
+    for note in notes
+    {
+        let beat = (note.start_tick as f64 / ticks_per_quarter_note.max(1.0)).round() as usize;
+        if accents.len() <= beat
+        {
+            accents.resize(beat + 1, 0.0);
+        }
+        accents[beat] += note.velocity as f32;
+    }
+    accents
+}
+
+// correlation of the accents with themselves shifted by lag beats, with the mean removed so that
+// a single strong downbeat does not favour whichever lag has fewer terms
+fn accent_correlation(accents: &[f32], lag: usize) -> f32
+{
+    if accents.len() <= lag
+    {
+        return 0.0;
+    }
+    let mean = accents.iter().sum::<f32>() / accents.len() as f32;
+    let sum: f32 = accents.iter().zip(&accents[lag..]).map(|(a, b)| (a - mean) * (b - mean)).sum();
+    sum / (accents.len() - lag) as f32
+}
+
+impl MidiSong
+{
+    pub fn length_ticks(&self) -> usize
+    {
+        self.tracks.iter()
+            .map(|track| track.absolute_ticks().last().map(|(tick, _)| tick).unwrap_or(0))
+            .max()
+            .unwrap_or(0)
+    }
+
+    pub fn key_signature(&self) -> Option<Key>
+    {
+        self.tracks.iter()
+            .flat_map(|track| track.absolute_ticks())
+            .filter_map(|(tick, event)| match *event
+            {
+                MidiEvent::KeySignature { sf, mi } => Some((tick, Key::from_key_signature(sf as i8, mi != 0))),
+                _ => None,
+            })
+            .min_by_key(|(tick, _)| *tick)
+            .map(|(_, key)| key)
+    }
+
+    pub fn estimate_key(&self) -> KeyEstimate
+    {
+        Key::estimate(&pitch_class_weights(&self.notes()))
+    }
+
+    pub fn key(&self) -> Key
+    {
+        self.key_signature().unwrap_or_else(|| self.estimate_key().key)
+    }
+
+    // only tells triple from duple meter, so the estimate is either 3/4 or 4/4
+    pub fn estimate_time_signature(&self) -> (u8, u8)
+    {
+        let tempo_map = TempoMap::new(self);
+        self.estimate_time_signature_with(&tempo_map, &self.notes_with_tempo_map(&tempo_map))
+    }
+
+    pub fn estimate_time_signature_with(&self, tempo_map: &TempoMap, notes: &[Note]) -> (u8, u8)
+    {
+        let accents = beat_accents(notes, tempo_map.ticks_per_quarter_note());
+        let triple = accent_correlation(&accents, 3);
+        let duple = accent_correlation(&accents, 2);
+        if triple > 0.0 && triple > duple { (3, 4) } else { (4, 4) }
+    }
+
+    pub fn beat_chords(&self) -> Vec<BeatChord>
+    {
+        let tempo_map = TempoMap::new(self);
+        self.beat_chords_with(&tempo_map, &self.notes_with_tempo_map(&tempo_map))
+    }
+
+    pub fn beat_chords_with(&self, tempo_map: &TempoMap, notes: &[Note]) -> Vec<BeatChord>
+    {
+        let end_tick = notes.iter().map(|n| n.end_tick).max().unwrap_or(0);
+        let beats = beat_ticks(tempo_map, end_tick);
+
+        let mut weights = vec![[0.0f32; 12]; beats.len()];
+        for note in notes.iter().filter(|n| is_pitched(n))
+        {
+            let first = beats.partition_point(|&b| b <= note.start_tick).saturating_sub(1);
+            for (i, &beat_start) in beats.iter().enumerate().skip(first)
+            {
+                if beat_start >= note.end_tick.max(note.start_tick + 1)
+                {
+                    break;
+                }
+                let beat_end = beats.get(i + 1).copied().unwrap_or(end_tick);
+                let overlap = note.end_tick.min(beat_end).saturating_sub(note.start_tick.max(beat_start)).max(1);
+                weights[i][(note.pitch % 12) as usize] += overlap as f32;
+            }
+        }
+
+        beats.iter()
+            .zip(&weights)
+            .map(|(&tick, weights)| BeatChord { tick, bar_beat: tempo_map.bar_beat(tick), chord: Chord::detect(weights) })
+            .collect()
+    }
+
+    pub fn track_stats(&self) -> Vec<TrackStats>
+    {
+        self.track_stats_with(&TempoMap::new(self), &self.notes())
+    }
+
+    pub fn track_stats_with(&self, tempo_map: &TempoMap, notes: &[Note]) -> Vec<TrackStats>
+    {
+        let duration_seconds = tempo_map.ticks_to_seconds(self.length_ticks());
+
+        (0..self.tracks.len())
+            .map(|track|
+            {
+                let mut stats = TrackStats { track, ..Default::default() };
+                let mut velocity_sum = 0.0;
+                for note in notes.iter().filter(|n| n.track == track)
+                {
+                    stats.note_count += 1;
+                    stats.channels |= 1 << (note.channel & 0x0F);
+                    velocity_sum += note.velocity as f32;
+                    stats.pitch_range = Some(match stats.pitch_range
+                    {
+                        Some((low, high)) => (low.min(note.pitch), high.max(note.pitch)),
+                        None => (note.pitch, note.pitch),
+                    });
+                }
+
+                if stats.note_count > 0
+                {
+                    stats.average_velocity = velocity_sum / stats.note_count as f32;
+                }
+                if duration_seconds > 0.0
+                {
+                    stats.notes_per_second = (stats.note_count as f64 / duration_seconds) as f32;
+                }
+                stats
+            })
+            .collect()
+    }
+
+    pub fn analyze(&self) -> SongAnalysis
+    {
+        let tempo_map = TempoMap::new(self);
+        let notes = self.notes_with_tempo_map(&tempo_map);
+
+        let key_signature = self.key_signature();
+        let estimated_key = Key::estimate(&pitch_class_weights(&notes));
+
+        let time_signature_from_events = self.tracks.iter()
+            .flat_map(|track| track.events.iter())
+            .any(|event| matches!(event, MidiEvent::TimeSignature { .. }));
+        let time_signature = if time_signature_from_events
+        {
+            let time_signature = tempo_map.time_signature_at(0);
+            (time_signature.numerator, time_signature.denominator)
+        }
+        else
+        {
+            self.estimate_time_signature_with(&tempo_map, &notes)
+        };
+
+        let length_ticks = self.length_ticks();
+        let duration_seconds = tempo_map.ticks_to_seconds(length_ticks);
+        let average_bpm = if duration_seconds > 0.0
+        {
+            length_ticks as f64 / tempo_map.ticks_per_quarter_note() / duration_seconds * 60.0
+        }
+        else
+        {
+            tempo_map.bpm_at(0)
+        };
+
+        SongAnalysis
+        {
+            key: key_signature.unwrap_or(estimated_key.key),
+            key_from_signature: key_signature.is_some(),
+            estimated_key,
+            time_signature,
+            time_signature_from_events,
+            initial_bpm: tempo_map.bpm_at(0),
+            average_bpm,
+            duration_seconds,
+            tracks: self.track_stats_with(&tempo_map, &notes),
+            chords: self.beat_chords_with(&tempo_map, &notes),
+        }
+    }
+}
@@ -0,0 +1,350 @@
+use std::collections::HashMap;
+
+use serde::{Serialize, Deserialize};
+
+use super::event::MidiEvent;
+
+pub const RPN_PITCH_BEND_SENSITIVITY: u16 = 0x0000;
+pub const RPN_FINE_TUNING: u16 = 0x0001;
+pub const RPN_COARSE_TUNING: u16 = 0x0002;
+pub const RPN_MPE_CONFIGURATION: u16 = 0x0006;
+pub const RPN_NULL: u16 = 0x3FFF;
+
+const CENTER_14BIT: u16 = 0x2000;
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[repr(C)]
+pub enum ParameterNumber
+{
+    Registered(u16),
+    NonRegistered(u16),
+}
+
+impl ParameterNumber
+{
+    pub fn number(&self) -> u16
+    {
+        match *self
+        {
+            ParameterNumber::Registered(number) | ParameterNumber::NonRegistered(number) => number,
+        }
+    }
+
+    fn default_value(&self) -> Option<u16>
+    {
+        match *self
+        {
+            ParameterNumber::Registered(RPN_PITCH_BEND_SENSITIVITY) => Some(2 << 7),
+            ParameterNumber::Registered(RPN_FINE_TUNING) | ParameterNumber::Registered(RPN_COARSE_TUNING) => Some(CENTER_14BIT),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[repr(C)]
+pub enum ControllerChange
+{
+    Controller { controller: u8, value: u8, },
+    Controller14 { controller: u8, value: u16, },
+    Parameter { parameter: ParameterNumber, value: u16, },
+    PitchBend { value: i16, },
+}
+
+pub fn pitch_bend_value(bend_lsb: u8, position_msb: u8) -> i16
+{
+    ((bend_lsb as i16 & 0x7F) | (position_msb as i16 & 0x7F) << 7) - CENTER_14BIT as i16
+}
+
+pub fn pitch_bend_event(channel: u8, value: i16) -> MidiEvent
+{
+    let value = (value.clamp(-8192, 8191) + CENTER_14BIT as i16) as u16;
+    MidiEvent::PitchBend { channel, bend_lsb: (value & 0x7F) as u8, position_msb: (value >> 7) as u8 }
+}
+
+pub fn controller_14bit_events(channel: u8, controller: u8, value: u16) -> [MidiEvent; 2]
+{
+    let controller = controller & 0x1F;
+    [
+        MidiEvent::ControllerChange { channel, controller, value: (value >> 7) as u8 & 0x7F },
+        MidiEvent::ControllerChange { channel, controller: controller + 32, value: value as u8 & 0x7F },
+    ]
+}
+
+pub fn parameter_events(channel: u8, parameter: ParameterNumber, value: u16, deselect: bool) -> Vec<MidiEvent>
+{
+    let cc = |controller: u8, value: u16| MidiEvent::ControllerChange { channel, controller, value: value as u8 & 0x7F };
+    let (msb_controller, lsb_controller) = match parameter
+    {
+        ParameterNumber::Registered(_) => (101, 100),
+        ParameterNumber::NonRegistered(_) => (99, 98),
+    };
+
+    let number = parameter.number();
+    let mut events = vec![cc(msb_controller, number >> 7), cc(lsb_controller, number), cc(6, value >> 7), cc(38, value)];
+    if deselect
+    {
+        events.push(cc(101, 127));
+        events.push(cc(100, 127));
+    }
+    events
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[repr(C)]
+pub struct ControllerTracker
+{
+    pub channel: u8,
+    values: Vec<u8>,
+    parameter: Option<ParameterNumber>,
+    parameters: HashMap<ParameterNumber, u16>,
+    pitch_bend: i16,
+}
+
+impl ControllerTracker
+{
+    pub fn new(channel: u8) -> ControllerTracker
+    {
+        let mut tracker = ControllerTracker
+        {
+            channel: channel & 0xF,
+            values: vec![0; 128],
+            parameter: None,
+            parameters: HashMap::new(),
+            pitch_bend: 0,
+        };
+        tracker.values[7] = 100;
+        tracker.values[10] = 64;
+        tracker.values[11] = 127;
+        for controller in 71..=79
+        {
+            tracker.values[controller] = 64;
+        }
+        tracker.values[98] = 127;
+        tracker.values[99] = 127;
+        tracker.values[100] = 127;
+        tracker.values[101] = 127;
+        tracker
+    }
+
+    pub fn value(&self, controller: u8) -> u8
+    {
+        self.values[controller as usize & 0x7F]
+    }
+
+    pub fn value_14bit(&self, controller: u8) -> u16
+    {
+        let controller = controller as usize & 0x1F;
+        (self.values[controller] as u16) << 7 | self.values[controller + 32] as u16
+    }
+
+    pub fn selected_parameter(&self) -> Option<ParameterNumber>
+    {
+        self.parameter
+    }
+
+    pub fn parameter(&self, parameter: ParameterNumber) -> Option<u16>
+    {
+        self.parameters.get(&parameter).copied().or_else(|| parameter.default_value())
+    }
+
+    pub fn pitch_bend(&self) -> i16
+    {
+        self.pitch_bend
+    }
+
+    pub fn pitch_bend_range(&self) -> f32
+    {
+        let value = self.parameter(ParameterNumber::Registered(RPN_PITCH_BEND_SENSITIVITY)).unwrap_or(2 << 7);
+        (value >> 7) as f32 + (value & 0x7F).min(99) as f32 / 100.0
+    }
+
+    pub fn pitch_bend_semitones(&self) -> f32
+    {
+        self.pitch_bend as f32 / CENTER_14BIT as f32 * self.pitch_bend_range()
+    }
+
+    pub fn fine_tuning(&self) -> f32
+    {
+        let value = self.parameter(ParameterNumber::Registered(RPN_FINE_TUNING)).unwrap_or(CENTER_14BIT);
+        (value as f32 - CENTER_14BIT as f32) / CENTER_14BIT as f32 * 100.0
+    }
+
+    pub fn coarse_tuning(&self) -> i8
+    {
+        let value = self.parameter(ParameterNumber::Registered(RPN_COARSE_TUNING)).unwrap_or(CENTER_14BIT);
+        (value >> 7) as i8 - 64
+    }
+
+    pub fn tuning_semitones(&self) -> f32
+    {
+        self.coarse_tuning() as f32 + self.fine_tuning() / 100.0
+    }
+
+    pub fn reset(&mut self)
+    {
+        *self = ControllerTracker::new(self.channel);
+    }
+
+    fn select_parameter(&mut self)
+    {
+        let registered = (self.values[101] as u16) << 7 | self.values[100] as u16;
+        let non_registered = (self.values[99] as u16) << 7 | self.values[98] as u16;
+        self.parameter = match (registered, non_registered)
+        {
+            (RPN_NULL, RPN_NULL) => None,
+            (RPN_NULL, number) => Some(ParameterNumber::NonRegistered(number)),
+            (number, _) => Some(ParameterNumber::Registered(number)),
+        };
+    }
+
+    fn set_parameter(&mut self, value: impl FnOnce(u16) -> u16) -> Option<ControllerChange>
+    {
+        let parameter = self.parameter?;
+        let value = value(self.parameter(parameter).unwrap_or(0)).min(0x3FFF);
+        self.parameters.insert(parameter, value);
+        Some(ControllerChange::Parameter { parameter, value })
+    }
+
+    // RP-015: volume, pan, bank select and parameter values survive a reset of all controllers
+    fn reset_all_controllers(&mut self)
+    {
+        for controller in (1..=5).chain(11..=31).chain(33..=37).chain(43..=63).chain(64..=69)
+        {
+            self.values[controller] = 0;
+        }
+        self.values[11] = 127;
+        for controller in 98..=101
+        {
+            self.values[controller] = 127;
+        }
+        self.parameter = None;
+        self.pitch_bend = 0;
+    }
+
+    pub fn process(&mut self, event: &MidiEvent) -> Option<ControllerChange>
+    {
+        match *event
+        {
+            MidiEvent::ControllerChange { channel, controller, value } if channel & 0xF == self.channel =>
+                self.process_controller(controller, value),
+            MidiEvent::PitchBend { channel, bend_lsb, position_msb } if channel & 0xF == self.channel =>
+            {
+                self.pitch_bend = pitch_bend_value(bend_lsb, position_msb);
+                Some(ControllerChange::PitchBend { value: self.pitch_bend })
+            }
+            _ => None,
+        }
+    }
+
+    pub fn process_controller(&mut self, controller: u8, value: u8) -> Option<ControllerChange>
+    {
+        let controller = controller & 0x7F;
+        let value = value & 0x7F;
+
+        match controller
+        {
+            6 =>
+            {
+                self.values[6] = value;
+                self.values[38] = 0;
+                self.set_parameter(|_| (value as u16) << 7)
+            }
+            38 =>
+            {
+                self.values[38] = value;
+                self.set_parameter(|current| (current & !0x7F) | value as u16)
+            }
+            96 => self.set_parameter(|current| current + 1),
+            97 => self.set_parameter(|current| current.saturating_sub(1)),
+            98..=101 =>
+            {
+                self.values[controller as usize] = value;
+                // selecting one kind of parameter deselects the other
+                match controller
+                {
+                    98 | 99 => { self.values[100] = 127; self.values[101] = 127; }
+                    _ => { self.values[98] = 127; self.values[99] = 127; }
+                }
+                self.select_parameter();
+                None
+            }
+            121 =>
+            {
+                self.reset_all_controllers();
+                Some(ControllerChange::Controller { controller, value })
+            }
+            0..=31 =>
+            {
+                self.values[controller as usize] = value;
+                self.values[controller as usize + 32] = 0;
+                Some(ControllerChange::Controller14 { controller, value: self.value_14bit(controller) })
+            }
+            32..=63 =>
+            {
+                self.values[controller as usize] = value;
+                Some(ControllerChange::Controller14 { controller: controller - 32, value: self.value_14bit(controller - 32) })
+            }
+            _ =>
+            {
+                self.values[controller as usize] = value;
+                Some(ControllerChange::Controller { controller, value })
+            }
+        }
+    }
+}
+
+impl Default for ControllerTracker
+{
+    fn default() -> Self
+    {
+        ControllerTracker::new(0)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[repr(C)]
+pub struct ChannelControllers
+{
+    pub channels: Vec<ControllerTracker>,
+}
+
+impl ChannelControllers
+{
+    pub fn new() -> ChannelControllers
+    {
+        ChannelControllers { channels: (0..16).map(ControllerTracker::new).collect() }
+    }
+
+    pub fn channel(&self, channel: u8) -> &ControllerTracker
+    {
+        &self.channels[channel as usize & 0xF]
+    }
+
+    pub fn channel_mut(&mut self, channel: u8) -> &mut ControllerTracker
+    {
+        &mut self.channels[channel as usize & 0xF]
+    }
+
+    pub fn reset(&mut self)
+    {
+        for tracker in &mut self.channels
+        {
+            tracker.reset();
+        }
+    }
+
+    pub fn process(&mut self, event: &MidiEvent) -> Option<(u8, ControllerChange)>
+    {
+        let channel = event.channel()? & 0xF;
+        self.channel_mut(channel).process(event).map(|change| (channel, change))
+    }
+}
+
+impl Default for ChannelControllers
+{
+    fn default() -> Self
+    {
+        ChannelControllers::new()
+    }
+}
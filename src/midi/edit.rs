@@ -0,0 +1,180 @@
+use super::{event::{MidiEvent, PERCUSSION_CHANNEL}, midi_song::{MidiSong, MidiTrack}};
+
+fn is_note_start(event: &MidiEvent) -> bool
+{
+    matches!(event, MidiEvent::NoteOn { velocity, .. } if *velocity > 0)
+}
+
+fn rebuild_track(mut events: Vec<(usize, MidiEvent)>) -> MidiTrack
+{
+    let end_of_track = events.iter()
+        .filter(|(_, event)| *event == MidiEvent::EndOfTrack)
+        .map(|(tick, _)| *tick)
+        .max();
+    events.retain(|(_, event)| *event != MidiEvent::EndOfTrack);
+    events.sort_by_key(|(tick, event)| (*tick, is_note_start(event)));
+
+    if let Some(end_of_track) = end_of_track
+    {
+        let last_tick = events.last().map(|(tick, _)| *tick).unwrap_or(0);
+        events.push((end_of_track.max(last_tick), MidiEvent::EndOfTrack));
+    }
+
+    MidiTrack::from_absolute_ticks(events)
+}
+
+fn absolute_events(track: &MidiTrack) -> Vec<(usize, MidiEvent)>
+{
+    track.absolute_ticks().map(|(tick, event)| (tick, event.clone())).collect()
+}
+
+fn snap_to_grid(tick: usize, grid_ticks: usize, swing: f64) -> usize
+{
+    let swing_ticks = (grid_ticks as f64 * swing.clamp(0.0, 0.99)).round() as usize;
+    let below = tick / grid_ticks;
+
+    let candidates = [below.saturating_sub(1), below, below + 1].map(|i| i * grid_ticks + if i % 2 == 1 { swing_ticks } else { 0 });
+    candidates.into_iter().min_by_key(|&c| c.abs_diff(tick)).unwrap()
+}
+
+impl MidiSong
+{
+    pub fn quantize(&mut self, grid_ticks: usize, swing: f64, quantize_ends: bool)
+    {
+        if grid_ticks == 0
+        {
+            return;
+        }
+
+        for track in &mut self.tracks
+        {
+            let mut events = absolute_events(track);
+            let end_of_track = events.last().map(|(tick, _)| *tick).unwrap_or(0);
+            for (start, end) in track.note_pairs()
+            {
+                let start_tick = events[start].0;
+                let end_tick = end.map(|end| events[end].0).unwrap_or(end_of_track);
+                let new_start = snap_to_grid(start_tick, grid_ticks, swing);
+                let new_end = if quantize_ends
+                {
+                    let snapped = snap_to_grid(end_tick, grid_ticks, swing);
+                    if snapped > new_start { snapped } else { snap_to_grid(new_start + grid_ticks, grid_ticks, swing) }
+                }
+                else
+                {
+                    new_start + (end_tick - start_tick)
+                };
+
+                events[start].0 = new_start;
+                if let Some(end) = end
+                {
+                    events[end].0 = new_end;
+                }
+            }
+            *track = rebuild_track(events);
+        }
+    }
+
+    pub fn transpose(&mut self, semitones: i32, min_pitch: u8, max_pitch: u8)
+    {
+        let max_pitch = max_pitch.clamp(min_pitch, 127);
+        let transpose_pitch = |pitch: &mut u8| *pitch = (*pitch as i32 + semitones).clamp(min_pitch as i32, max_pitch as i32) as u8;
+
+        for track in &mut self.tracks
+        {
+            for event in &mut track.events
+            {
+                // drum kits map pitches to instruments, shifting them would swap the kit pieces
+                match event
+                {
+                    MidiEvent::NoteOn { channel, pitch, .. }
+                    | MidiEvent::NoteOff { channel, pitch, .. }
+                    | MidiEvent::PolyPressure { channel, pitch, .. } if *channel & 0x0F != PERCUSSION_CHANNEL => transpose_pitch(pitch),
+                    _ => {}
+                }
+            }
+        }
+    }
+
+    pub fn scale_velocities(&mut self, factor: f32)
+    {
+        for track in &mut self.tracks
+        {
+            for event in &mut track.events
+            {
+                if let MidiEvent::NoteOn { velocity, .. } = event
+                {
+                    if *velocity > 0
+                    {
+                        *velocity = (*velocity as f32 * factor).round().clamp(1.0, 127.0) as u8;
+                    }
+                }
+            }
+        }
+    }
+
+    pub fn time_stretch(&mut self, factor: f64)
+    {
+        let factor = factor.max(0.0);
+        for track in &mut self.tracks
+        {
+            let events = absolute_events(track)
+                .into_iter()
+                .map(|(tick, event)| ((tick as f64 * factor).round() as usize, event))
+                .collect();
+            *track = MidiTrack::from_absolute_ticks(events);
+        }
+    }
+
+    pub fn merge_tracks(&self) -> MidiSong
+    {
+        let events = self.tracks.iter().flat_map(absolute_events).collect();
+
+        MidiSong
+        {
+            t: 0,
+            time_division: self.time_division,
+            tracks: vec![rebuild_track(events)],
+        }
+    }
+
+    pub fn split_by_channel(&self) -> MidiSong
+    {
+        let merged = self.merge_tracks();
+        let events = absolute_events(&merged.tracks[0]);
+        let end_of_track = events.last().map(|(tick, _)| *tick).unwrap_or(0);
+
+        let mut channels: Vec<u8> = events.iter().filter_map(|(_, event)| event.channel()).collect();
+        channels.sort();
+        channels.dedup();
+
+        let mut split_events = vec![Vec::new(); channels.len() + 1];
+        for (tick, event) in events
+        {
+            let i = match event.channel()
+            {
+                Some(channel) => 1 + channels.binary_search(&channel).unwrap(),
+                None => 0,
+            };
+            split_events[i].push((tick, event));
+        }
+
+        let tracks = split_events.into_iter()
+            .map(|mut events|
+            {
+                if events.last().map(|(_, event)| event) != Some(&MidiEvent::EndOfTrack)
+                {
+                    events.push((end_of_track, MidiEvent::EndOfTrack));
+                }
+                MidiTrack::from_absolute_ticks(events)
+            })
+            .collect();
+
+        MidiSong
+        {
+            t: 1,
+            time_division: self.time_division,
+            tracks,
+        }
+    }
+}
@@ -1,16 +1,22 @@
 use serde::{Serialize, Deserialize};
 
+use super::text::MidiText;
+
+pub const PERCUSSION_CHANNEL: u8 = 9;
+
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 #[repr(C)]
 pub enum MidiEvent
 {
     NoteOn { channel: u8, pitch: u8, velocity: u8, },
     NoteOff{ channel: u8, pitch: u8, velocity: u8, },
+    PolyPressure{ channel: u8, pitch: u8, pressure: u8, },
     ControllerChange{ channel: u8, controller: u8, value: u8, },
     ProgramChange{ channel: u8, preset: u8, },
     ChannelPressure{ channel: u8, pressure: u8, },
     PitchBend{ channel: u8, bend_lsb: u8, position_msb: u8, },
     SysEx{ data: Vec::<u8>, },
+    SysExEscape{ data: Vec::<u8>, },
     SongPosition{ position_lsb: u8, position_msb: u8, },
     SongSelect{ song_number: u8, },
     BusSelect{ bus_number: u8, },
@@ -20,19 +26,22 @@ pub enum MidiEvent
     ContinueSong,
     StopSong,
     ActiveSensing,
-    Text { text: String, },
-    Copyright { text: String, },
-    Lyrics { text: String, },
-    Marker { text: String, },
-    TrackName { name: String, },
-    InstrumentName { name: String, },
-    DeviceName { name: String, },
+    Text { text: MidiText, },
+    Copyright { text: MidiText, },
+    Lyrics { text: MidiText, },
+    Marker { text: MidiText, },
+    TrackName { name: MidiText, },
+    InstrumentName { name: MidiText, },
+    ProgramName { name: MidiText, },
+    DeviceName { name: MidiText, },
+    ChannelPrefix { channel: u8, },
     MidiPort { port: u8, },
     EndOfTrack,
     SetTempo { microseconds_per_quarter_note: u32, },
     SMTPEOffset { hh: u8, mm: u8, ss: u8, fr: u8, ff: u8, },
     TimeSignature { nn: u8, dd: u8, cc: u8, bb: u8, },
     KeySignature { sf: u8, mi: u8, },
+    SequencerSpecific { data: Vec::<u8>, },
     UnknownMetaMessage { meta_type: u8, data: Vec::<u8> },
 }
 
@@ -42,3 +51,43 @@ impl Default for MidiEvent
         MidiEvent::NoteOn { channel: 0, pitch: 0, velocity: 0 }
     }
 }
+
+impl MidiEvent
+{
+    pub fn channel(&self) -> Option<u8>
+    {
+        match *self
+        {
+            MidiEvent::NoteOn { channel, .. }
+            | MidiEvent::NoteOff { channel, .. }
+            | MidiEvent::PolyPressure { channel, .. }
+            | MidiEvent::ControllerChange { channel, .. }
+            | MidiEvent::ProgramChange { channel, .. }
+            | MidiEvent::ChannelPressure { channel, .. }
+            | MidiEvent::PitchBend { channel, .. } => Some(channel),
+            _ => None,
+        }
+    }
+
+    pub fn is_meta(&self) -> bool
+    {
+        matches!(self,
+            MidiEvent::Text { .. }
+            | MidiEvent::Copyright { .. }
+            | MidiEvent::Lyrics { .. }
+            | MidiEvent::Marker { .. }
+            | MidiEvent::TrackName { .. }
+            | MidiEvent::InstrumentName { .. }
+            | MidiEvent::ProgramName { .. }
+            | MidiEvent::DeviceName { .. }
+            | MidiEvent::ChannelPrefix { .. }
+            | MidiEvent::MidiPort { .. }
+            | MidiEvent::EndOfTrack
+            | MidiEvent::SetTempo { .. }
+            | MidiEvent::SMTPEOffset { .. }
+            | MidiEvent::TimeSignature { .. }
+            | MidiEvent::KeySignature { .. }
+            | MidiEvent::SequencerSpecific { .. }
+            | MidiEvent::UnknownMetaMessage { .. })
+    }
+}
@@ -0,0 +1,256 @@
+use serde::{Serialize, Deserialize};
+
+use super::{event::MidiEvent, midi_song::MidiSong, tempo_map::TempoMap, text::MidiText};
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, Default, Serialize, Deserialize)]
+#[repr(C)]
+pub enum LyricBreak
+{
+    #[default]
+    None,
+    Line,
+    Paragraph,
+}
+
+impl LyricBreak
+{
+    fn max_break(self, other: LyricBreak) -> LyricBreak
+    {
+        match (self, other)
+        {
+            (LyricBreak::Paragraph, _) | (_, LyricBreak::Paragraph) => LyricBreak::Paragraph,
+            (LyricBreak::Line, _) | (_, LyricBreak::Line) => LyricBreak::Line,
+            _ => LyricBreak::None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Default, Serialize, Deserialize)]
+#[repr(C)]
+pub struct TimedText
+{
+    pub tick: usize,
+    pub seconds: f64,
+    pub text: String,
+    pub track: usize,
+}
+
+#[derive(Debug, Clone, PartialEq, Default, Serialize, Deserialize)]
+#[repr(C)]
+pub struct Syllable
+{
+    pub tick: usize,
+    pub seconds: f64,
+    pub text: String,
+    pub track: usize,
+    pub line: usize,
+    pub paragraph: usize,
+}
+
+#[derive(Debug, Clone, PartialEq, Default, Serialize, Deserialize)]
+#[repr(C)]
+pub struct LyricLine
+{
+    pub paragraph: usize,
+    pub start_seconds: f64,
+    pub end_seconds: f64,
+    pub first_syllable: usize,
+    pub syllable_count: usize,
+    pub text: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Default, Serialize, Deserialize)]
+#[repr(C)]
+pub struct KaraokeHeader
+{
+    pub kind: char,
+    pub text: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Default, Serialize, Deserialize)]
+#[repr(C)]
+pub struct LyricsTimeline
+{
+    pub syllables: Vec<Syllable>,
+    pub lines: Vec<LyricLine>,
+    pub markers: Vec<TimedText>,
+    pub texts: Vec<TimedText>,
+    pub headers: Vec<KaraokeHeader>,
+}
+
+// .kar files put a "/" before a syllable that starts a line and a "\" before one that starts a paragraph,
+// plain lyric events usually end a line with a carriage return or line feed instead
+fn split_breaks(text: &str) -> (LyricBreak, &str, bool)
+{
+    let (break_before, rest) = match text.chars().next()
+    {
+        Some('\\') => (LyricBreak::Paragraph, &text[1..]),
+        Some('/') => (LyricBreak::Line, &text[1..]),
+        _ => (LyricBreak::None, text),
+    };
+
+    let trimmed = rest.trim_end_matches(['\r', '\n']);
+    (break_before, trimmed, trimmed.len() != rest.len())
+}
+
+fn index_at<T>(items: &[T], seconds: f64, item_seconds: impl Fn(&T) -> f64) -> Option<usize>
+{
+    items.partition_point(|item| item_seconds(item) <= seconds).checked_sub(1)
+}
+
+impl LyricsTimeline
+{
+    pub fn is_karaoke(&self) -> bool
+    {
+        !self.headers.is_empty()
+    }
+
+    pub fn syllable_index_at(&self, seconds: f64) -> Option<usize>
+    {
+        index_at(&self.syllables, seconds, |s| s.seconds)
+    }
+
+    pub fn syllable_at(&self, seconds: f64) -> Option<&Syllable>
+    {
+        self.syllable_index_at(seconds).map(|i| &self.syllables[i])
+    }
+
+    pub fn line_at(&self, seconds: f64) -> Option<&LyricLine>
+    {
+        index_at(&self.lines, seconds, |l| l.start_seconds).map(|i| &self.lines[i])
+    }
+
+    pub fn marker_at(&self, seconds: f64) -> Option<&TimedText>
+    {
+        index_at(&self.markers, seconds, |m| m.seconds).map(|i| &self.markers[i])
+    }
+
+    pub fn header(&self, kind: char) -> impl Iterator<Item = &str>
+    {
+        self.headers.iter().filter(move |h| h.kind == kind).map(|h| h.text.as_str())
+    }
+
+    fn push_syllable(&mut self, text: &TimedText, pending_break: &mut LyricBreak)
+    {
+        let (break_before, syllable, break_after) = split_breaks(&text.text);
+        let break_before = (*pending_break).max_break(break_before);
+
+        if !syllable.is_empty()
+        {
+            let (mut line, mut paragraph) = self.syllables.last().map(|s| (s.line, s.paragraph)).unwrap_or((0, 0));
+            if !self.syllables.is_empty()
+            {
+                match break_before
+                {
+                    LyricBreak::Paragraph => { line += 1; paragraph += 1; }
+                    LyricBreak::Line => line += 1,
+                    LyricBreak::None => {}
+                }
+            }
+
+            self.syllables.push(Syllable { tick: text.tick, seconds: text.seconds, text: syllable.to_string(), track: text.track, line, paragraph });
+            *pending_break = LyricBreak::None;
+        }
+        else
+        {
+            *pending_break = break_before;
+        }
+
+        if break_after
+        {
+            *pending_break = (*pending_break).max_break(LyricBreak::Line);
+        }
+    }
+
+    fn build_lines(&mut self)
+    {
+        for (i, syllable) in self.syllables.iter().enumerate()
+        {
+            match self.lines.last_mut()
+            {
+                Some(line) if self.syllables[line.first_syllable].line == syllable.line =>
+                {
+                    line.syllable_count += 1;
+                    line.end_seconds = syllable.seconds;
+                    line.text.push_str(&syllable.text);
+                }
+                _ => self.lines.push(LyricLine
+                {
+                    paragraph: syllable.paragraph,
+                    start_seconds: syllable.seconds,
+                    end_seconds: syllable.seconds,
+                    first_syllable: i,
+                    syllable_count: 1,
+                    text: syllable.text.clone(),
+                }),
+            }
+        }
+
+        // a line stays on screen until the next one starts
+        for i in 1..self.lines.len()
+        {
+            self.lines[i - 1].end_seconds = self.lines[i].start_seconds;
+        }
+    }
+}
+
+impl MidiSong
+{
+    pub fn lyrics_timeline(&self) -> LyricsTimeline
+    {
+        self.lyrics_timeline_with(&TempoMap::new(self))
+    }
+
+    pub fn lyrics_timeline_with(&self, tempo_map: &TempoMap) -> LyricsTimeline
+    {
+        let mut lyrics = Vec::new();
+        let mut texts = Vec::new();
+        let mut markers = Vec::new();
+
+        for (track, events) in self.tracks.iter().enumerate()
+        {
+            for (tick, event) in events.absolute_ticks()
+            {
+                let timed = |text: &MidiText| TimedText { tick, seconds: tempo_map.ticks_to_seconds(tick), text: text.decode().into_owned(), track };
+                match event
+                {
+                    MidiEvent::Lyrics { text } => lyrics.push(timed(text)),
+                    MidiEvent::Text { text } => texts.push(timed(text)),
+                    MidiEvent::Marker { text } => markers.push(timed(text)),
+                    _ => {}
+                }
+            }
+        }
+
+        for list in [&mut lyrics, &mut texts, &mut markers]
+        {
+            list.sort_by_key(|t| (t.tick, t.track));
+        }
+
+        let mut timeline = LyricsTimeline { markers, ..Default::default() };
+
+        // karaoke files carry their words in text events next to "@" headers
+        let karaoke = texts.iter().any(|t| t.text.starts_with('@'));
+        let syllables = if karaoke
+        {
+            let (headers, words): (Vec<_>, Vec<_>) = texts.into_iter().partition(|t| t.text.starts_with('@'));
+            timeline.headers = headers.iter()
+                .filter_map(|t| t.text[1..].chars().next().map(|kind| KaraokeHeader { kind, text: t.text[1 + kind.len_utf8()..].to_string() }))
+                .collect();
+            words
+        }
+        else
+        {
+            timeline.texts = texts;
+            lyrics
+        };
+
+        let mut pending_break = LyricBreak::None;
+        for text in &syllables
+        {
+            timeline.push_syllable(text, &mut pending_break);
+        }
+        timeline.build_lines();
+        timeline
+    }
+}
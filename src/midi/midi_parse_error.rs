@@ -0,0 +1,85 @@
+use std::fmt;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum MidiParseError
+{
+    Io { kind: std::io::ErrorKind },
+    TruncatedChunk { offset: usize, track: Option<usize> },
+    BadMagic { found: [u8; 4], offset: usize, track: Option<usize> },
+    UnsupportedStatus { status: u8, offset: usize, track: Option<usize> },
+    InvalidUtf8 { meta_type: u8, offset: usize, track: Option<usize> },
+    BadVarLen { offset: usize, track: Option<usize> },
+}
+
+impl MidiParseError
+{
+    pub fn offset(&self) -> Option<usize>
+    {
+        match self
+        {
+            MidiParseError::Io { .. } => None,
+            MidiParseError::TruncatedChunk { offset, .. }
+            | MidiParseError::BadMagic { offset, .. }
+            | MidiParseError::UnsupportedStatus { offset, .. }
+            | MidiParseError::InvalidUtf8 { offset, .. }
+            | MidiParseError::BadVarLen { offset, .. } => Some(*offset),
+        }
+    }
+
+    pub fn track(&self) -> Option<usize>
+    {
+        match self
+        {
+            MidiParseError::Io { .. } => None,
+            MidiParseError::TruncatedChunk { track, .. }
+            | MidiParseError::BadMagic { track, .. }
+            | MidiParseError::UnsupportedStatus { track, .. }
+            | MidiParseError::InvalidUtf8 { track, .. }
+            | MidiParseError::BadVarLen { track, .. } => *track,
+        }
+    }
+
+    pub fn with_track(mut self, index: usize) -> MidiParseError
+    {
+        match &mut self
+        {
+            MidiParseError::Io { .. } => {}
+            MidiParseError::TruncatedChunk { track, .. }
+            | MidiParseError::BadMagic { track, .. }
+            | MidiParseError::UnsupportedStatus { track, .. }
+            | MidiParseError::InvalidUtf8 { track, .. }
+            | MidiParseError::BadVarLen { track, .. } => *track = Some(index),
+        }
+        self
+    }
+}
+
+impl fmt::Display for MidiParseError
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result
+    {
+        match self
+        {
+            MidiParseError::Io { kind } => return write!(f, "could not read midi file: {}", kind),
+            MidiParseError::TruncatedChunk { .. } => write!(f, "truncated chunk")?,
+            MidiParseError::BadMagic { found, .. } => write!(f, "bad chunk magic {:?}", found)?,
+            MidiParseError::UnsupportedStatus { status, .. } => write!(f, "unsupported status byte {:#04X}", status)?,
+            MidiParseError::InvalidUtf8 { meta_type, .. } => write!(f, "invalid utf-8 in meta event {:#04X}", meta_type)?,
+            MidiParseError::BadVarLen { .. } => write!(f, "bad variable length quantity")?,
+        }
+
+        if let Some(track) = self.track()
+        {
+            write!(f, " in track {}", track)?;
+        }
+
+        if let Some(offset) = self.offset()
+        {
+            write!(f, " at byte {}", offset)?;
+        }
+
+        Ok(())
+    }
+}
+
+impl std::error::Error for MidiParseError {}
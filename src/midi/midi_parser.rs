@@ -2,12 +2,24 @@ use std::path::Path;
 
 use serde::{Serialize, Deserialize};
 
-use super::{midi_song::{MidiSong, MidiTrack}, event::MidiEvent};
+use super::{midi_song::{MidiSong, MidiTrack, TimeDivision}, event::MidiEvent, midi_parse_error::MidiParseError, text::{MidiText, TextEncoding}};
 
-pub fn load_midi_file<P: AsRef<Path>>(path: P) -> Option<MidiSong>
+#[derive(Debug, Copy, Clone, PartialEq, Default, Serialize, Deserialize)]
+#[repr(C)]
+pub struct ParseOptions {
+    pub text_encoding: TextEncoding,
+    pub strict_utf8: bool,
+}
+
+pub fn load_midi_file<P: AsRef<Path>>(path: P) -> Result<MidiSong, MidiParseError>
+{
+    load_midi_file_with(path, &ParseOptions::default())
+}
+
+pub fn load_midi_file_with<P: AsRef<Path>>(path: P, options: &ParseOptions) -> Result<MidiSong, MidiParseError>
 {
-    let bytes = std::fs::read(path).ok()?;
-    read_midi_file(&bytes)
+    let bytes = std::fs::read(path).map_err(|e| MidiParseError::Io { kind: e.kind() })?;
+    read_midi_file_with(&bytes, options)
 }
 
 #[derive(Debug, Copy, Clone, PartialEq, Default, Serialize, Deserialize)]
@@ -15,256 +27,255 @@ pub fn load_midi_file<P: AsRef<Path>>(path: P) -> Option<MidiSong>
 pub struct MidiHeader {
     pub t: u32,
     pub ntracks: u32,
-    pub pulses_per_quarter_note: u32,
+    pub time_division: TimeDivision,
 }
 
-pub fn read_midi_file(bytes: &[u8]) -> Option<MidiSong> {
+pub fn read_midi_file(bytes: &[u8]) -> Result<MidiSong, MidiParseError> {
+    read_midi_file_with(bytes, &ParseOptions::default())
+}
+
+pub fn read_midi_file_with(bytes: &[u8], options: &ParseOptions) -> Result<MidiSong, MidiParseError> {
     let mut cursor = 0;
-    let header = read_mthd_chunck(bytes, &mut cursor);
+    let header = read_mthd_chunck(bytes, &mut cursor)?;
 
     let mut midi_song = MidiSong {
         t: header.t,
-        pulses_per_quarter_note: header.pulses_per_quarter_note,
+        time_division: header.time_division,
         tracks: Vec::new(),
     };
 
-    for _ in 0..header.ntracks {
-        if let Some(track) = read_mtrk_chunck(bytes, &mut cursor) {
-            midi_song.tracks.push(track);
-        } else {
-            return None;
-        }
+    for i in 0..header.ntracks as usize {
+        let track = read_mtrk_chunck(bytes, &mut cursor, options).map_err(|e| e.with_track(i))?;
+        midi_song.tracks.push(track);
     }
 
-    Some(midi_song)
+    Ok(midi_song)
 }
 
-pub fn read_mthd_chunck(bytes: &[u8], cursor: &mut usize) -> MidiHeader {
-    let _mthd_header_bytes = raw_read::<[u8; 4]>(bytes, cursor);
+pub fn read_mthd_chunck(bytes: &[u8], cursor: &mut usize) -> Result<MidiHeader, MidiParseError> {
+    let start = *cursor;
+    let mthd_header_bytes = raw_read::<[u8; 4]>(bytes, cursor)?;
+    if mthd_header_bytes != *b"MThd" {
+        return Err(MidiParseError::BadMagic { found: mthd_header_bytes, offset: start, track: None });
+    }
 
-    let _header_length = raw_read::<[u8; 4]>(bytes, cursor);
+    let header_length = u32::from_be_bytes(raw_read::<[u8; 4]>(bytes, cursor)?) as usize;
 
-    let header_u8s = raw_read::<[u8; 6]>(bytes, cursor);
+    let header_u8s = raw_read::<[u8; 6]>(bytes, cursor)?;
 
-    MidiHeader {
+    read_slice(bytes, cursor, header_length.saturating_sub(6))?;
+
+    Ok(MidiHeader {
         t: header_u8s[1] as u32 + ((header_u8s[0] as u32) << 8),
         ntracks: header_u8s[3] as u32 + ((header_u8s[2] as u32) << 8),
-        pulses_per_quarter_note: header_u8s[5] as u32 + ((header_u8s[4] as u32) << 8),
-    }
+        time_division: TimeDivision::from(header_u8s[5] as u16 + ((header_u8s[4] as u16) << 8)),
+    })
 }
 
-pub fn raw_read<T: Sized>(bytes: &[u8], cursor: &mut usize) -> T
-where
-    T: Copy,
-{
-    unsafe {
-        let bs = &bytes[*cursor..*cursor + std::mem::size_of::<T>()];
-        *cursor += std::mem::size_of::<T>();
-        let ptr = bs as *const _ as *const T;
-        let x = ptr.as_ref().unwrap();
-        *x
-    }
+pub fn raw_read<T: Copy>(bytes: &[u8], cursor: &mut usize) -> Result<T, MidiParseError> {
+    let bs = read_slice(bytes, cursor, std::mem::size_of::<T>())?;
+    unsafe { Ok(std::ptr::read_unaligned(bs.as_ptr() as *const T)) }
 }
 
-pub fn read_mtrk_chunck(bytes: &[u8], cursor: &mut usize) -> Option<MidiTrack> {
-    let mtrk_header_u8s = raw_read::<[u8; 4]>(bytes, cursor);
-
-    let chunck_length = raw_read::<[u8; 4]>(bytes, cursor);
-
-    let length: usize = ((chunck_length[3] as usize) << 0)
-        + ((chunck_length[2] as usize) << 8)
-        + ((chunck_length[1] as usize) << 16)
-        + ((chunck_length[0] as usize) << 24);
+pub fn read_slice<'a>(bytes: &'a [u8], cursor: &mut usize, length: usize) -> Result<&'a [u8], MidiParseError> {
+    let end = cursor.checked_add(length).filter(|&end| end <= bytes.len());
+    match end {
+        Some(end) => {
+            let bs = &bytes[*cursor..end];
+            *cursor = end;
+            Ok(bs)
+        }
+        None => Err(MidiParseError::TruncatedChunk { offset: *cursor, track: None }),
+    }
+}
 
-    let data = &bytes[*cursor..*cursor + length];
-    *cursor += length;
+pub fn read_mtrk_chunck(bytes: &[u8], cursor: &mut usize, options: &ParseOptions) -> Result<MidiTrack, MidiParseError> {
+    // chunks other than MTrk are vendor extensions, readers are expected to skip them
+    let length = loop {
+        let chunk_type = raw_read::<[u8; 4]>(bytes, cursor)?;
+        let length = u32::from_be_bytes(raw_read::<[u8; 4]>(bytes, cursor)?) as usize;
+        if chunk_type == *b"MTrk" {
+            break length;
+        }
+        read_slice(bytes, cursor, length)?;
+    };
 
-    if mtrk_header_u8s != *b"MTrk" {
-        println!("skipping non MTrk chunck: {:?}", mtrk_header_u8s);
-        return None;
-    }
+    let data_start = *cursor;
+    read_slice(bytes, cursor, length)?;
+    let data = &bytes[..*cursor];
 
     let mut track = MidiTrack {
         dts: Vec::new(),
         events: Vec::new(),
     };
 
-    let mut cursor = 0;
+    let mut cursor = data_start;
     let mut status = 0;
     while cursor < data.len() {
-        let dt = read_var_len(data, &mut cursor);
+        let dt = read_var_len(data, &mut cursor)?;
         track.dts.push(dt);
-        if let Some(event) = read_midi_event(data, &mut cursor, &mut status) {
-            track.events.push(event);
-        } else {
-            return None;
-        }
+        let event = read_midi_event_with(data, &mut cursor, &mut status, options)?;
+        track.events.push(event);
     }
 
-    Some(track)
+    Ok(track)
 }
 
-pub fn read_var_len(bytes: &[u8], cursor: &mut usize) -> usize {
+pub fn read_var_len(bytes: &[u8], cursor: &mut usize) -> Result<usize, MidiParseError> {
+    let start = *cursor;
     let mut value: usize = 0;
-    let mut is_last = false;
-    while !is_last {
-        value = value << 7;
-        let b = bytes[*cursor];
-        *cursor += 1;
-        is_last = (b & 0x80) == 0;
-        let d = b & 0x7F;
-        value += d as usize;
+    for _ in 0..4 {
+        let b = raw_read::<u8>(bytes, cursor)?;
+        value = (value << 7) + (b & 0x7F) as usize;
+        if (b & 0x80) == 0 {
+            return Ok(value);
+        }
     }
-    return value;
+    Err(MidiParseError::BadVarLen { offset: start, track: None })
 }
 
-pub fn read_midi_event(bs: &[u8], cursor: &mut usize, status: &mut u8) -> Option<MidiEvent> {
-    if bs.len() < *cursor + 1 {
-        return None;
-    }
-    let first_u8 = bs[*cursor];
-    if first_u8 & 0x80 != 0 {
-        *status = first_u8;
-        *cursor += 1;
+fn read_text(bs: &[u8], meta_type: u8, offset: usize, options: &ParseOptions) -> Result<MidiText, MidiParseError> {
+    if options.strict_utf8 && std::str::from_utf8(bs).is_err() {
+        return Err(MidiParseError::InvalidUtf8 { meta_type, offset, track: None });
     }
+    Ok(MidiText::new(bs.to_owned(), options.text_encoding))
+}
+
+fn read_fixed<const N: usize>(bs: &[u8], offset: usize) -> Result<[u8; N], MidiParseError> {
+    bs.get(..N)
+        .and_then(|xs| xs.try_into().ok())
+        .ok_or(MidiParseError::TruncatedChunk { offset, track: None })
+}
+
+pub fn read_midi_event(bs: &[u8], cursor: &mut usize, status: &mut u8) -> Result<MidiEvent, MidiParseError> {
+    read_midi_event_with(bs, cursor, status, &ParseOptions::default())
+}
+
+pub fn read_midi_event_with(bs: &[u8], cursor: &mut usize, status: &mut u8, options: &ParseOptions) -> Result<MidiEvent, MidiParseError> {
+    let start = *cursor;
+    let first_u8 = raw_read::<u8>(bs, cursor)?;
+    let event_status = if first_u8 & 0x80 == 0 {
+        *cursor = start;
+        *status
+    } else {
+        match first_u8 {
+            0x80..=0xEF => *status = first_u8,
+            0xF8..=0xFE => {}
+            _ => *status = 0,
+        }
+        first_u8
+    };
 
-    let a = *status >> 4;
-    let b = *status & 0x0F;
+    let a = event_status >> 4;
+    let b = event_status & 0x0F;
 
-    return match a {
+    match a {
         0x08 => {
-            if bs.len() < *cursor + 2 {
-                return None;
-            }
-            let r = Some(MidiEvent::NoteOff {
-                channel: b,
-                pitch: bs[*cursor],
-                velocity: bs[*cursor + 1],
-            });
-            *cursor += 2;
-            r
+            let [pitch, velocity] = raw_read::<[u8; 2]>(bs, cursor)?;
+            Ok(MidiEvent::NoteOff { channel: b, pitch, velocity })
         }
         0x09 => {
-            if bs.len() < *cursor + 2 {
-                return None;
-            }
-            let r = Some(MidiEvent::NoteOn {
-                channel: b,
-                pitch: bs[*cursor],
-                velocity: bs[*cursor + 1],
-            });
-            *cursor += 2;
-            r
+            let [pitch, velocity] = raw_read::<[u8; 2]>(bs, cursor)?;
+            Ok(MidiEvent::NoteOn { channel: b, pitch, velocity })
+        }
+        0x0A => {
+            let [pitch, pressure] = raw_read::<[u8; 2]>(bs, cursor)?;
+            Ok(MidiEvent::PolyPressure { channel: b, pitch, pressure })
         }
         0x0B => {
-            if bs.len() < *cursor + 2 {
-                return None;
-            }
-            let r = Some(MidiEvent::ControllerChange {
-                channel: b,
-                controller: bs[*cursor],
-                value: bs[*cursor + 1],
-            });
-            *cursor += 2;
-            r
+            let [controller, value] = raw_read::<[u8; 2]>(bs, cursor)?;
+            Ok(MidiEvent::ControllerChange { channel: b, controller, value })
         }
         0x0C => {
-            let preset = bs[*cursor];
-            *cursor += 1;
-            Some(MidiEvent::ProgramChange { channel: b, preset })
+            let preset = raw_read::<u8>(bs, cursor)?;
+            Ok(MidiEvent::ProgramChange { channel: b, preset })
         }
         0x0D => {
-            if bs.len() < *cursor + 1 {
-                return None;
-            }
-            let r = Some(MidiEvent::ChannelPressure {
-                channel: b,
-                pressure: bs[*cursor],
-            });
-            *cursor += 1;
-            r
+            let pressure = raw_read::<u8>(bs, cursor)?;
+            Ok(MidiEvent::ChannelPressure { channel: b, pressure })
         }
         0x0E => {
-            if bs.len() < *cursor + 2 {
-                return None;
-            }
-            let r = Some(MidiEvent::PitchBend {
-                channel: b,
-                bend_lsb: bs[*cursor],
-                position_msb: bs[*cursor + 1],
-            });
-            *cursor += 2;
-            r
+            let [bend_lsb, position_msb] = raw_read::<[u8; 2]>(bs, cursor)?;
+            Ok(MidiEvent::PitchBend { channel: b, bend_lsb, position_msb })
         }
-        0x0F => match b {
-            0x0F => {
-                let meta_type = bs[*cursor];
-                *cursor += 1;
-                let length = bs[*cursor];
-                *cursor += 1;
-
-                let meta_event = match meta_type {
-                    0x01 => {
-                        let xs = &bs[*cursor..*cursor + length as usize];
-                        let text = String::from_utf8(xs.to_owned()).unwrap();
-                        Some(MidiEvent::Text { text })
-                    }
-                    0x02 => {
-                        let xs = &bs[*cursor..*cursor + length as usize];
-                        let text = String::from_utf8(xs.to_owned()).unwrap();
-                        Some(MidiEvent::Copyright  { text })
-                    }
-                    0x03 => {
-                        let xs = &bs[*cursor..*cursor + length as usize];
-                        let name = String::from_utf8(xs.to_owned()).unwrap();
-                        Some(MidiEvent::TrackName { name })
-                    }
-                    0x05 => {
-                        let xs = &bs[*cursor..*cursor + length as usize];
-                        let text = String::from_utf8(xs.to_owned()).unwrap();
-                        Some(MidiEvent::Lyrics { text })
-                    }
-                    0x06 => {
-                        let xs = &bs[*cursor..*cursor + length as usize];
-                        let text = String::from_utf8(xs.to_owned()).unwrap();
-                        Some(MidiEvent::Marker { text })
-                    }
-                    0x2F => Some(MidiEvent::EndOfTrack),
-                    0x51 => Some(MidiEvent::SetTempo {
-                        microseconds_per_quarter_note: 
-                              ((bs[*cursor + 0] as u32) << 16)
-                            + ((bs[*cursor + 1] as u32) << 8)
-                            + ((bs[*cursor + 2] as u32) << 0),
-                    }),
-                    0x58 => Some(MidiEvent::TimeSignature {
-                        nn: bs[*cursor + 0],
-                        dd: bs[*cursor + 1],
-                        cc: bs[*cursor + 2],
-                        bb: bs[*cursor + 3],
-                    }),
-                    0x59 => Some(MidiEvent::KeySignature {
-                        sf: bs[*cursor + 0],
-                        mi: bs[*cursor + 1],
-                    }),
-                    _ => {
-                        println!("unparsed meta event: {:#X}", meta_type);
-
-                        let data = bs[*cursor..*cursor + length as usize].to_owned();
-                        Some(MidiEvent::UnknownMetaMessage { meta_type, data })
-                    }
-                };
-
-                *cursor += length as usize;
-                meta_event
+        0x0F => match event_status {
+            0xF0 | 0xF7 => {
+                let length = read_var_len(bs, cursor)?;
+                let data = read_slice(bs, cursor, length)?.to_owned();
+                if event_status == 0xF0 {
+                    Ok(MidiEvent::SysEx { data })
+                } else {
+                    Ok(MidiEvent::SysExEscape { data })
+                }
             }
-            _ => {
-                println!("unparsed F message: {:#X}", b);
-                None
+            0xF2 => {
+                let [position_lsb, position_msb] = raw_read::<[u8; 2]>(bs, cursor)?;
+                Ok(MidiEvent::SongPosition { position_lsb, position_msb })
             }
+            0xF3 => {
+                let song_number = raw_read::<u8>(bs, cursor)?;
+                Ok(MidiEvent::SongSelect { song_number })
+            }
+            0xF5 => {
+                let bus_number = raw_read::<u8>(bs, cursor)?;
+                Ok(MidiEvent::BusSelect { bus_number })
+            }
+            0xF6 => Ok(MidiEvent::TuneRequest),
+            0xF8 => Ok(MidiEvent::TimingTick),
+            0xFA => Ok(MidiEvent::StartSong),
+            0xFB => Ok(MidiEvent::ContinueSong),
+            0xFC => Ok(MidiEvent::StopSong),
+            0xFE => Ok(MidiEvent::ActiveSensing),
+            0xFF => read_meta_event(bs, cursor, options),
+            _ => Err(MidiParseError::UnsupportedStatus { status: event_status, offset: start, track: None }),
         },
-        x => {
-            println!("{:#X}: not not implemented midi value", x);
-            None
+        _ => Err(MidiParseError::UnsupportedStatus { status: event_status, offset: start, track: None }),
+    }
+}
+
+pub fn read_meta_event(bs: &[u8], cursor: &mut usize, options: &ParseOptions) -> Result<MidiEvent, MidiParseError> {
+    let meta_type = raw_read::<u8>(bs, cursor)?;
+    let length = read_var_len(bs, cursor)?;
+    let data_start = *cursor;
+    let xs = read_slice(bs, cursor, length)?;
+
+    match meta_type {
+        0x01 => Ok(MidiEvent::Text { text: read_text(xs, meta_type, data_start, options)? }),
+        0x02 => Ok(MidiEvent::Copyright { text: read_text(xs, meta_type, data_start, options)? }),
+        0x03 => Ok(MidiEvent::TrackName { name: read_text(xs, meta_type, data_start, options)? }),
+        0x04 => Ok(MidiEvent::InstrumentName { name: read_text(xs, meta_type, data_start, options)? }),
+        0x05 => Ok(MidiEvent::Lyrics { text: read_text(xs, meta_type, data_start, options)? }),
+        0x06 => Ok(MidiEvent::Marker { text: read_text(xs, meta_type, data_start, options)? }),
+        0x08 => Ok(MidiEvent::ProgramName { name: read_text(xs, meta_type, data_start, options)? }),
+        0x09 => Ok(MidiEvent::DeviceName { name: read_text(xs, meta_type, data_start, options)? }),
+        0x20 => {
+            let [channel] = read_fixed(xs, data_start)?;
+            Ok(MidiEvent::ChannelPrefix { channel })
         }
-    };
+        0x21 => {
+            let [port] = read_fixed(xs, data_start)?;
+            Ok(MidiEvent::MidiPort { port })
+        }
+        0x2F => Ok(MidiEvent::EndOfTrack),
+        0x51 => {
+            let [t0, t1, t2] = read_fixed(xs, data_start)?;
+            Ok(MidiEvent::SetTempo {
+                microseconds_per_quarter_note: u32::from_be_bytes([0, t0, t1, t2]),
+            })
+        }
+        0x54 => {
+            let [hh, mm, ss, fr, ff] = read_fixed(xs, data_start)?;
+            Ok(MidiEvent::SMTPEOffset { hh, mm, ss, fr, ff })
+        }
+        0x58 => {
+            let [nn, dd, cc, bb] = read_fixed(xs, data_start)?;
+            Ok(MidiEvent::TimeSignature { nn, dd, cc, bb })
+        }
+        0x59 => {
+            let [sf, mi] = read_fixed(xs, data_start)?;
+            Ok(MidiEvent::KeySignature { sf, mi })
+        }
+        0x7F => Ok(MidiEvent::SequencerSpecific { data: xs.to_owned() }),
+        _ => Ok(MidiEvent::UnknownMetaMessage { meta_type, data: xs.to_owned() }),
+    }
 }
@@ -7,7 +7,7 @@ use super::event::MidiEvent;
 pub struct MidiSong
 {
     pub t: u32,
-    pub pulses_per_quarter_note: u32,
+    pub time_division: TimeDivision,
     pub tracks: Vec<MidiTrack>,
 }
 
@@ -18,3 +18,116 @@ pub struct MidiTrack
     pub dts: Vec<usize>,
     pub events: Vec<MidiEvent>,
 }
+
+impl MidiTrack
+{
+    pub fn absolute_ticks(&self) -> impl Iterator<Item = (usize, &MidiEvent)> + '_
+    {
+        self.dts.iter().scan(0, |tick, dt|
+        {
+            *tick += dt;
+            Some(*tick)
+        })
+        .zip(self.events.iter())
+    }
+
+    pub fn from_absolute_ticks(mut events: Vec<(usize, MidiEvent)>) -> MidiTrack
+    {
+        events.sort_by_key(|(tick, _)| *tick);
+
+        let mut track = MidiTrack::default();
+        let mut last_tick = 0;
+        for (tick, event) in events
+        {
+            track.dts.push(tick - last_tick);
+            track.events.push(event);
+            last_tick = tick;
+        }
+        track
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Serialize, Deserialize)]
+#[repr(C)]
+pub enum TimeDivision
+{
+    Metrical { pulses_per_quarter_note: u16, },
+    Smpte { frames_per_second: u8, ticks_per_frame: u8, },
+}
+
+impl Default for TimeDivision
+{
+    fn default() -> Self
+    {
+        TimeDivision::Metrical { pulses_per_quarter_note: 96 }
+    }
+}
+
+impl TimeDivision
+{
+    pub const DEFAULT_MICROSECONDS_PER_QUARTER_NOTE: u32 = 500000;
+
+    pub fn frames_per_second(&self) -> Option<f64>
+    {
+        match *self
+        {
+            TimeDivision::Metrical { .. } => None,
+            TimeDivision::Smpte { frames_per_second: 29, .. } => Some(30000.0 / 1001.0),
+            TimeDivision::Smpte { frames_per_second, .. } => Some(frames_per_second as f64),
+        }
+    }
+
+    pub fn seconds_per_pulse(&self, microseconds_per_quarter_note: u32) -> f64
+    {
+        match *self
+        {
+            TimeDivision::Metrical { pulses_per_quarter_note } =>
+            {
+                microseconds_per_quarter_note as f64 / (pulses_per_quarter_note.max(1) as f64 * 1000000.0)
+            }
+            TimeDivision::Smpte { ticks_per_frame, .. } =>
+            {
+                let frames_per_second = self.frames_per_second().unwrap_or(30.0);
+                1.0 / (frames_per_second * ticks_per_frame.max(1) as f64)
+            }
+        }
+    }
+
+    pub fn is_metrical(&self) -> bool
+    {
+        matches!(self, TimeDivision::Metrical { .. })
+    }
+}
+
+impl From<u16> for TimeDivision
+{
+    fn from(value: u16) -> Self
+    {
+        if value & 0x8000 == 0
+        {
+            TimeDivision::Metrical { pulses_per_quarter_note: value }
+        }
+        else
+        {
+            let frames_per_second = ((value >> 8) as u8 as i8).unsigned_abs();
+            let ticks_per_frame = (value & 0xFF) as u8;
+            TimeDivision::Smpte { frames_per_second, ticks_per_frame }
+        }
+    }
+}
+
+impl From<TimeDivision> for u16
+{
+    fn from(value: TimeDivision) -> Self
+    {
+        match value
+        {
+            TimeDivision::Metrical { pulses_per_quarter_note } => pulses_per_quarter_note & 0x7FFF,
+            TimeDivision::Smpte { frames_per_second, ticks_per_frame } =>
+            {
+                let negative_fps = (frames_per_second as i8).wrapping_neg() as u8;
+                ((negative_fps as u16) << 8) | ticks_per_frame as u16
+            }
+        }
+    }
+}
@@ -0,0 +1,148 @@
+use std::path::Path;
+
+use super::{midi_song::{MidiSong, MidiTrack}, event::MidiEvent};
+
+pub fn save_midi_file<P: AsRef<Path>>(path: P, song: &MidiSong) -> std::io::Result<()>
+{
+    std::fs::write(path, write_midi_file(song))
+}
+
+pub fn write_midi_file(song: &MidiSong) -> Vec<u8>
+{
+    let mut bytes = Vec::new();
+    write_mthd_chunck(&mut bytes, song);
+
+    for track in &song.tracks
+    {
+        write_mtrk_chunck(&mut bytes, track);
+    }
+
+    bytes
+}
+
+pub fn write_mthd_chunck(bytes: &mut Vec<u8>, song: &MidiSong)
+{
+    bytes.extend_from_slice(b"MThd");
+    bytes.extend_from_slice(&6u32.to_be_bytes());
+    bytes.extend_from_slice(&(song.t as u16).to_be_bytes());
+    bytes.extend_from_slice(&(song.tracks.len() as u16).to_be_bytes());
+    bytes.extend_from_slice(&u16::from(song.time_division).to_be_bytes());
+}
+
+pub fn write_mtrk_chunck(bytes: &mut Vec<u8>, track: &MidiTrack)
+{
+    let mut data = Vec::new();
+    let mut status = 0;
+    for (dt, event) in track.dts.iter().zip(&track.events)
+    {
+        write_var_len(&mut data, *dt);
+        write_midi_event(&mut data, event, &mut status);
+    }
+
+    bytes.extend_from_slice(b"MTrk");
+    bytes.extend_from_slice(&(data.len() as u32).to_be_bytes());
+    bytes.extend_from_slice(&data);
+}
+
+pub fn write_var_len(bytes: &mut Vec<u8>, mut value: usize)
+{
+    let mut buffer = [0u8; 10];
+    let mut n = 0;
+    loop
+    {
+        buffer[n] = (value & 0x7F) as u8;
+        n += 1;
+        value >>= 7;
+        if value == 0
+        {
+            break;
+        }
+    }
+
+    for (i, b) in buffer[..n].iter().enumerate().rev()
+    {
+        let continuation = if i > 0 { 0x80 } else { 0x00 };
+        bytes.push(b | continuation);
+    }
+}
+
+// out of range channels and data bytes are masked so they can't turn into a different status byte
+fn write_channel_event(bytes: &mut Vec<u8>, status: &mut u8, kind: u8, channel: u8, data: &[u8])
+{
+    let new_status = kind | (channel & 0x0F);
+    if *status != new_status
+    {
+        bytes.push(new_status);
+        *status = new_status;
+    }
+    bytes.extend(data.iter().map(|b| b & 0x7F));
+}
+
+fn write_meta_event(bytes: &mut Vec<u8>, status: &mut u8, meta_type: u8, data: &[u8])
+{
+    bytes.push(0xFF);
+    bytes.push(meta_type);
+    write_var_len(bytes, data.len());
+    bytes.extend_from_slice(data);
+    *status = 0;
+}
+
+fn write_sysex_event(bytes: &mut Vec<u8>, status: &mut u8, sysex_type: u8, data: &[u8])
+{
+    bytes.push(sysex_type);
+    write_var_len(bytes, data.len());
+    bytes.extend_from_slice(data);
+    *status = 0;
+}
+
+fn write_system_event(bytes: &mut Vec<u8>, status: &mut u8, data: &[u8])
+{
+    bytes.extend_from_slice(data);
+    *status = 0;
+}
+
+pub fn write_midi_event(bytes: &mut Vec<u8>, event: &MidiEvent, status: &mut u8)
+{
+    match event
+    {
+        MidiEvent::NoteOff { channel, pitch, velocity } => write_channel_event(bytes, status, 0x80, *channel, &[*pitch, *velocity]),
+        MidiEvent::NoteOn { channel, pitch, velocity } => write_channel_event(bytes, status, 0x90, *channel, &[*pitch, *velocity]),
+        MidiEvent::PolyPressure { channel, pitch, pressure } => write_channel_event(bytes, status, 0xA0, *channel, &[*pitch, *pressure]),
+        MidiEvent::ControllerChange { channel, controller, value } => write_channel_event(bytes, status, 0xB0, *channel, &[*controller, *value]),
+        MidiEvent::ProgramChange { channel, preset } => write_channel_event(bytes, status, 0xC0, *channel, &[*preset]),
+        MidiEvent::ChannelPressure { channel, pressure } => write_channel_event(bytes, status, 0xD0, *channel, &[*pressure]),
+        MidiEvent::PitchBend { channel, bend_lsb, position_msb } => write_channel_event(bytes, status, 0xE0, *channel, &[*bend_lsb, *position_msb]),
+        MidiEvent::SysEx { data } => write_sysex_event(bytes, status, 0xF0, data),
+        MidiEvent::SysExEscape { data } => write_sysex_event(bytes, status, 0xF7, data),
+        MidiEvent::SongPosition { position_lsb, position_msb } => write_system_event(bytes, status, &[0xF2, *position_lsb, *position_msb]),
+        MidiEvent::SongSelect { song_number } => write_system_event(bytes, status, &[0xF3, *song_number]),
+        MidiEvent::BusSelect { bus_number } => write_system_event(bytes, status, &[0xF5, *bus_number]),
+        MidiEvent::TuneRequest => write_system_event(bytes, status, &[0xF6]),
+        MidiEvent::TimingTick => bytes.push(0xF8),
+        MidiEvent::StartSong => bytes.push(0xFA),
+        MidiEvent::ContinueSong => bytes.push(0xFB),
+        MidiEvent::StopSong => bytes.push(0xFC),
+        MidiEvent::ActiveSensing => bytes.push(0xFE),
+        MidiEvent::Text { text } => write_meta_event(bytes, status, 0x01, text.as_bytes()),
+        MidiEvent::Copyright { text } => write_meta_event(bytes, status, 0x02, text.as_bytes()),
+        MidiEvent::TrackName { name } => write_meta_event(bytes, status, 0x03, name.as_bytes()),
+        MidiEvent::InstrumentName { name } => write_meta_event(bytes, status, 0x04, name.as_bytes()),
+        MidiEvent::Lyrics { text } => write_meta_event(bytes, status, 0x05, text.as_bytes()),
+        MidiEvent::Marker { text } => write_meta_event(bytes, status, 0x06, text.as_bytes()),
+        MidiEvent::ProgramName { name } => write_meta_event(bytes, status, 0x08, name.as_bytes()),
+        MidiEvent::DeviceName { name } => write_meta_event(bytes, status, 0x09, name.as_bytes()),
+        MidiEvent::ChannelPrefix { channel } => write_meta_event(bytes, status, 0x20, &[*channel]),
+        MidiEvent::MidiPort { port } => write_meta_event(bytes, status, 0x21, &[*port]),
+        MidiEvent::EndOfTrack => write_meta_event(bytes, status, 0x2F, &[]),
+        MidiEvent::SetTempo { microseconds_per_quarter_note } =>
+        {
+            let t = microseconds_per_quarter_note.to_be_bytes();
+            write_meta_event(bytes, status, 0x51, &t[1..4])
+        }
+        MidiEvent::SMTPEOffset { hh, mm, ss, fr, ff } => write_meta_event(bytes, status, 0x54, &[*hh, *mm, *ss, *fr, *ff]),
+        MidiEvent::TimeSignature { nn, dd, cc, bb } => write_meta_event(bytes, status, 0x58, &[*nn, *dd, *cc, *bb]),
+        MidiEvent::KeySignature { sf, mi } => write_meta_event(bytes, status, 0x59, &[*sf, *mi]),
+        MidiEvent::SequencerSpecific { data } => write_meta_event(bytes, status, 0x7F, data),
+        MidiEvent::UnknownMetaMessage { meta_type, data } => write_meta_event(bytes, status, *meta_type, data),
+    }
+}
@@ -1,6 +1,21 @@
+pub mod active_notes;
+pub mod analysis;
+pub mod controllers;
+pub mod edit;
 pub mod event;
+pub mod lyrics;
+pub mod midi_parse_error;
 pub mod midi_parser;
+pub mod midi_writer;
+pub mod mpe;
+pub mod note;
 pub mod play;
 pub mod midi_song;
 pub mod print_note_name;
+pub mod stream;
+pub mod tempo_map;
+pub mod text;
+pub mod ump;
 pub mod util;
+
+#[cfg(test)] mod tests;
\ No newline at end of file
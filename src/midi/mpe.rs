@@ -0,0 +1,503 @@
+use std::ops::RangeInclusive;
+
+use serde::{Serialize, Deserialize};
+
+use crate::pipe::Pipe;
+
+use super::{controllers::{ChannelControllers, ControllerChange, ParameterNumber, RPN_MPE_CONFIGURATION, RPN_PITCH_BEND_SENSITIVITY}, event::MidiEvent};
+
+pub const MPE_TIMBRE_CONTROLLER: u8 = 74;
+pub const MPE_DEFAULT_MEMBER_PITCH_BEND_RANGE: f32 = 48.0;
+pub const MPE_DEFAULT_MANAGER_PITCH_BEND_RANGE: f32 = 2.0;
+
+const PITCH_BEND_CENTER: u16 = 0x2000;
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, Default, Serialize, Deserialize)]
+#[repr(C)]
+pub enum MpeZoneKind
+{
+    #[default]
+    Lower,
+    Upper,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Default, Serialize, Deserialize)]
+#[repr(C)]
+pub struct MpeZone
+{
+    pub kind: MpeZoneKind,
+    pub member_count: u8,
+    pub member_pitch_bend_range: f32,
+    pub manager_pitch_bend_range: f32,
+}
+
+impl MpeZone
+{
+    pub fn new(kind: MpeZoneKind, member_count: u8) -> MpeZone
+    {
+        MpeZone
+        {
+            kind,
+            member_count: member_count.min(15),
+            member_pitch_bend_range: MPE_DEFAULT_MEMBER_PITCH_BEND_RANGE,
+            manager_pitch_bend_range: MPE_DEFAULT_MANAGER_PITCH_BEND_RANGE,
+        }
+    }
+
+    pub fn lower(member_count: u8) -> MpeZone
+    {
+        MpeZone::new(MpeZoneKind::Lower, member_count)
+    }
+
+    pub fn upper(member_count: u8) -> MpeZone
+    {
+        MpeZone::new(MpeZoneKind::Upper, member_count)
+    }
+
+    pub fn is_enabled(&self) -> bool
+    {
+        self.member_count > 0
+    }
+
+    pub fn manager_channel(&self) -> u8
+    {
+        match self.kind
+        {
+            MpeZoneKind::Lower => 0,
+            MpeZoneKind::Upper => 15,
+        }
+    }
+
+    // member_count is public, so a zone built by hand can ask for more channels than exist
+    pub fn member_channels(&self) -> RangeInclusive<u8>
+    {
+        let member_count = self.member_count.min(15);
+        match self.kind
+        {
+            MpeZoneKind::Lower => 1..=member_count,
+            MpeZoneKind::Upper => 15 - member_count..=14,
+        }
+    }
+
+    pub fn is_member(&self, channel: u8) -> bool
+    {
+        self.is_enabled() && self.member_channels().contains(&channel)
+    }
+
+    pub fn contains(&self, channel: u8) -> bool
+    {
+        self.is_enabled() && (channel == self.manager_channel() || self.is_member(channel))
+    }
+
+    pub fn pitch_bend_range(&self, channel: u8) -> f32
+    {
+        if channel == self.manager_channel() { self.manager_pitch_bend_range } else { self.member_pitch_bend_range }
+    }
+
+    pub fn configuration_events(&self) -> Vec<MidiEvent>
+    {
+        let channel = self.manager_channel();
+        let cc = |controller: u8, value: u8| MidiEvent::ControllerChange { channel, controller, value };
+        vec![cc(101, 0), cc(100, 6), cc(6, self.member_count.min(15)), cc(101, 127), cc(100, 127)]
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Serialize, Deserialize)]
+#[repr(C)]
+pub struct MpeConfiguration
+{
+    pub lower: MpeZone,
+    pub upper: MpeZone,
+}
+
+impl MpeConfiguration
+{
+    pub fn new() -> MpeConfiguration
+    {
+        MpeConfiguration { lower: MpeZone::lower(0), upper: MpeZone::upper(0) }
+    }
+
+    // a new zone takes the channels it needs and shrinks the other zone to what is left
+    pub fn set_zone(&mut self, mut zone: MpeZone)
+    {
+        zone.member_count = zone.member_count.min(15);
+        match zone.kind
+        {
+            MpeZoneKind::Lower =>
+            {
+                self.lower = zone;
+                self.upper.member_count = self.upper.member_count.min(14u8.saturating_sub(zone.member_count));
+            }
+            MpeZoneKind::Upper =>
+            {
+                self.upper = zone;
+                self.lower.member_count = self.lower.member_count.min(14u8.saturating_sub(zone.member_count));
+            }
+        }
+    }
+
+    pub fn zone_for_channel(&self, channel: u8) -> Option<&MpeZone>
+    {
+        [&self.lower, &self.upper].into_iter().find(|zone| zone.contains(channel))
+    }
+}
+
+impl Default for MpeConfiguration
+{
+    fn default() -> Self
+    {
+        MpeConfiguration::new()
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Default, Serialize, Deserialize)]
+#[repr(C)]
+pub struct Voice
+{
+    pub channel: u8,
+    pub pitch: u8,
+    pub velocity: u8,
+    pub pitch_bend: f32,
+    pub zone_pitch_bend: f32,
+    pub pressure: f32,
+    pub timbre: f32,
+}
+
+impl Voice
+{
+    pub fn bent_pitch(&self) -> f32
+    {
+        self.pitch as f32 + self.pitch_bend + self.zone_pitch_bend
+    }
+}
+
+fn bend_from_semitones(semitones: f32, range: f32) -> u16
+{
+    let offset = if range > 0.0 { semitones / range * PITCH_BEND_CENTER as f32 } else { 0.0 };
+    (PITCH_BEND_CENTER as f32 + offset).round().clamp(0.0, 16383.0) as u16
+}
+
+fn unit_to_7bit(value: f32) -> u8
+{
+    (value.clamp(0.0, 1.0) * 127.0).round() as u8
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[repr(C)]
+pub struct MpeDecoder
+{
+    config: MpeConfiguration,
+    controllers: ChannelControllers,
+    pressures: [u8; 16],
+    pitch_bend_ranges: [f32; 16],
+    voices: Vec<Voice>,
+}
+
+impl Default for MpeDecoder
+{
+    fn default() -> Self
+    {
+        MpeDecoder
+        {
+            config: MpeConfiguration::new(),
+            controllers: ChannelControllers::new(),
+            pressures: [0; 16],
+            pitch_bend_ranges: [MPE_DEFAULT_MANAGER_PITCH_BEND_RANGE; 16],
+            voices: Vec::new(),
+        }
+    }
+}
+
+impl MpeDecoder
+{
+    pub fn new() -> MpeDecoder
+    {
+        MpeDecoder::default()
+    }
+
+    pub fn with_configuration(config: MpeConfiguration) -> MpeDecoder
+    {
+        let mut decoder = MpeDecoder::default();
+        decoder.set_zone(config.lower);
+        decoder.set_zone(config.upper);
+        decoder
+    }
+
+    pub fn configuration(&self) -> &MpeConfiguration
+    {
+        &self.config
+    }
+
+    pub fn voices(&self) -> &[Voice]
+    {
+        &self.voices
+    }
+
+    pub fn voice(&self, channel: u8, pitch: u8) -> Option<&Voice>
+    {
+        self.voices.iter().find(|v| v.channel == channel && v.pitch == pitch)
+    }
+
+    pub fn controllers(&self) -> &ChannelControllers
+    {
+        &self.controllers
+    }
+
+    fn pitch_bend(&self, channel: u8) -> f32
+    {
+        self.controllers.channel(channel).pitch_bend() as f32 / PITCH_BEND_CENTER as f32 * self.pitch_bend_ranges[channel as usize & 0xF]
+    }
+
+    pub fn set_zone(&mut self, zone: MpeZone)
+    {
+        self.config.set_zone(zone);
+        for channel in 0..16u8
+        {
+            if let Some(zone) = self.config.zone_for_channel(channel)
+            {
+                self.pitch_bend_ranges[channel as usize] = zone.pitch_bend_range(channel);
+            }
+        }
+        self.update_voices();
+    }
+
+    fn zone_pitch_bend(&self, channel: u8) -> f32
+    {
+        match self.config.zone_for_channel(channel)
+        {
+            Some(zone) if zone.is_member(channel) => self.pitch_bend(zone.manager_channel()),
+            _ => 0.0,
+        }
+    }
+
+    fn update_voices(&mut self)
+    {
+        for i in 0..self.voices.len()
+        {
+            let channel = self.voices[i].channel;
+            let pitch_bend = self.pitch_bend(channel);
+            let zone_pitch_bend = self.zone_pitch_bend(channel);
+            let pressure = self.pressures[channel as usize & 0xF] as f32 / 127.0;
+            let timbre = self.controllers.channel(channel).value(MPE_TIMBRE_CONTROLLER) as f32 / 127.0;
+
+            let voice = &mut self.voices[i];
+            voice.pitch_bend = pitch_bend;
+            voice.zone_pitch_bend = zone_pitch_bend;
+            voice.pressure = pressure;
+            voice.timbre = timbre;
+        }
+    }
+
+    fn release_channel(&mut self, channel: u8)
+    {
+        let channel = channel & 0xF;
+        match self.config.zone_for_channel(channel).copied()
+        {
+            Some(zone) if zone.manager_channel() == channel => self.voices.retain(|v| !zone.contains(v.channel)),
+            _ => self.voices.retain(|v| v.channel != channel),
+        }
+    }
+
+    fn set_pitch_bend_range(&mut self, channel: u8, range: f32)
+    {
+        let zone = self.config.zone_for_channel(channel).copied();
+        match zone
+        {
+            Some(mut zone) =>
+            {
+                if zone.manager_channel() == channel
+                {
+                    zone.manager_pitch_bend_range = range;
+                }
+                else
+                {
+                    zone.member_pitch_bend_range = range;
+                }
+                match zone.kind
+                {
+                    MpeZoneKind::Lower => self.config.lower = zone,
+                    MpeZoneKind::Upper => self.config.upper = zone,
+                }
+                for c in 0..16u8
+                {
+                    if zone.contains(c)
+                    {
+                        self.pitch_bend_ranges[c as usize] = zone.pitch_bend_range(c);
+                    }
+                }
+            }
+            None => self.pitch_bend_ranges[channel as usize & 0xF] = range,
+        }
+    }
+
+    fn parameter_change(&mut self, channel: u8, parameter: ParameterNumber, value: u16)
+    {
+        match parameter
+        {
+            ParameterNumber::Registered(RPN_PITCH_BEND_SENSITIVITY) =>
+                self.set_pitch_bend_range(channel, (value >> 7) as f32 + (value & 0x7F).min(99) as f32 / 100.0),
+            ParameterNumber::Registered(RPN_MPE_CONFIGURATION) => match channel
+            {
+                0 => self.set_zone(MpeZone::lower((value >> 7) as u8)),
+                15 => self.set_zone(MpeZone::upper((value >> 7) as u8)),
+                _ => {}
+            },
+            _ => {}
+        }
+    }
+
+    pub fn process(&mut self, event: &MidiEvent)
+    {
+        match *event
+        {
+            MidiEvent::NoteOn { channel, pitch, velocity } if velocity > 0 =>
+            {
+                let channel = channel & 0xF;
+                self.voices.retain(|v| v.channel != channel || v.pitch != pitch);
+                self.voices.push(Voice { channel, pitch, velocity, ..Default::default() });
+            }
+            MidiEvent::NoteOn { channel, pitch, .. } | MidiEvent::NoteOff { channel, pitch, .. } =>
+            {
+                self.voices.retain(|v| v.channel != channel & 0xF || v.pitch != pitch);
+            }
+            MidiEvent::ChannelPressure { channel, pressure } => self.pressures[channel as usize & 0xF] = pressure,
+            MidiEvent::ControllerChange { channel, controller: 120 | 123, .. } => self.release_channel(channel & 0xF),
+            MidiEvent::ControllerChange { .. } | MidiEvent::PitchBend { .. } =>
+            {
+                if let Some((channel, ControllerChange::Parameter { parameter, value })) = self.controllers.process(event)
+                {
+                    self.parameter_change(channel, parameter, value);
+                }
+            }
+            _ => return,
+        }
+        self.update_voices();
+    }
+}
+
+impl Pipe<MidiEvent> for MpeDecoder
+{
+    fn send(&mut self, event: MidiEvent)
+    {
+        self.process(&event);
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Default, Serialize, Deserialize)]
+#[repr(C)]
+pub struct MpeAllocator
+{
+    zone: MpeZone,
+    next_channel: u8,
+    notes: Vec<(u8, u8)>,
+}
+
+impl MpeAllocator
+{
+    pub fn new(zone: MpeZone) -> MpeAllocator
+    {
+        MpeAllocator { zone, next_channel: 0, notes: Vec::new() }
+    }
+
+    pub fn zone(&self) -> &MpeZone
+    {
+        &self.zone
+    }
+
+    pub fn configure<P: Pipe<MidiEvent>>(&self, midi_pipe: &mut P)
+    {
+        for event in self.zone.configuration_events()
+        {
+            midi_pipe.send(event);
+        }
+
+        let range = self.zone.member_pitch_bend_range.clamp(0.0, 127.0);
+        for channel in self.zone.member_channels()
+        {
+            let cc = |controller: u8, value: u8| MidiEvent::ControllerChange { channel, controller, value };
+            let cents = (range.fract() * 100.0).round() as u8;
+            for event in [cc(101, 0), cc(100, 0), cc(6, range as u8), cc(38, cents), cc(101, 127), cc(100, 127)]
+            {
+                midi_pipe.send(event);
+            }
+        }
+    }
+
+    pub fn channel_of(&self, pitch: u8) -> Option<u8>
+    {
+        self.notes.iter().find(|(_, p)| *p == pitch).map(|(channel, _)| *channel)
+    }
+
+    // rotates through the member channels, preferring the least busy one so fresh notes get a clean channel
+    fn allocate(&mut self) -> u8
+    {
+        let members: Vec<u8> = self.zone.member_channels().collect();
+        if members.is_empty()
+        {
+            return self.zone.manager_channel();
+        }
+
+        let start = members.iter().position(|&c| c == self.next_channel).unwrap_or(0);
+        let channel = (0..members.len())
+            .map(|i| members[(start + i) % members.len()])
+            .min_by_key(|&c| self.notes.iter().filter(|(channel, _)| *channel == c).count())
+            .unwrap();
+
+        let i = members.iter().position(|&c| c == channel).unwrap();
+        self.next_channel = members[(i + 1) % members.len()];
+        channel
+    }
+
+    pub fn note_on<P: Pipe<MidiEvent>>(&mut self, voice: &Voice, midi_pipe: &mut P) -> u8
+    {
+        let channel = self.allocate();
+        let pitch = voice.pitch;
+        let bend = bend_from_semitones(voice.pitch_bend, self.zone.member_pitch_bend_range);
+
+        midi_pipe.send(MidiEvent::PitchBend { channel, bend_lsb: (bend & 0x7F) as u8, position_msb: (bend >> 7) as u8 });
+        midi_pipe.send(MidiEvent::ControllerChange { channel, controller: MPE_TIMBRE_CONTROLLER, value: unit_to_7bit(voice.timbre) });
+        midi_pipe.send(MidiEvent::ChannelPressure { channel, pressure: unit_to_7bit(voice.pressure) });
+        midi_pipe.send(MidiEvent::NoteOn { channel, pitch, velocity: voice.velocity.max(1) });
+
+        self.notes.push((channel, pitch));
+        channel
+    }
+
+    pub fn note_off<P: Pipe<MidiEvent>>(&mut self, pitch: u8, velocity: u8, midi_pipe: &mut P) -> Option<u8>
+    {
+        let i = self.notes.iter().position(|(_, p)| *p == pitch)?;
+        let (channel, _) = self.notes.remove(i);
+        midi_pipe.send(MidiEvent::NoteOff { channel, pitch, velocity });
+        Some(channel)
+    }
+
+    pub fn pitch_bend<P: Pipe<MidiEvent>>(&self, pitch: u8, semitones: f32, midi_pipe: &mut P) -> Option<u8>
+    {
+        let channel = self.channel_of(pitch)?;
+        let bend = bend_from_semitones(semitones, self.zone.member_pitch_bend_range);
+        midi_pipe.send(MidiEvent::PitchBend { channel, bend_lsb: (bend & 0x7F) as u8, position_msb: (bend >> 7) as u8 });
+        Some(channel)
+    }
+
+    pub fn pressure<P: Pipe<MidiEvent>>(&self, pitch: u8, pressure: f32, midi_pipe: &mut P) -> Option<u8>
+    {
+        let channel = self.channel_of(pitch)?;
+        midi_pipe.send(MidiEvent::ChannelPressure { channel, pressure: unit_to_7bit(pressure) });
+        Some(channel)
+    }
+
+    pub fn timbre<P: Pipe<MidiEvent>>(&self, pitch: u8, timbre: f32, midi_pipe: &mut P) -> Option<u8>
+    {
+        let channel = self.channel_of(pitch)?;
+        midi_pipe.send(MidiEvent::ControllerChange { channel, controller: MPE_TIMBRE_CONTROLLER, value: unit_to_7bit(timbre) });
+        Some(channel)
+    }
+
+    pub fn release_all<P: Pipe<MidiEvent>>(&mut self, midi_pipe: &mut P)
+    {
+        for (channel, pitch) in self.notes.drain(..)
+        {
+            midi_pipe.send(MidiEvent::NoteOff { channel, pitch, velocity: 0 });
+        }
+    }
+}
@@ -0,0 +1,131 @@
+use std::collections::{HashMap, VecDeque};
+
+use serde::{Serialize, Deserialize};
+
+use super::{event::MidiEvent, midi_song::{MidiSong, MidiTrack}, tempo_map::TempoMap};
+
+#[derive(Debug, Copy, Clone, PartialEq, Default, Serialize, Deserialize)]
+#[repr(C)]
+pub struct Note
+{
+    pub channel: u8,
+    pub pitch: u8,
+    pub velocity: u8,
+    pub start_tick: usize,
+    pub end_tick: usize,
+    pub start_seconds: f64,
+    pub duration_seconds: f64,
+    pub track: usize,
+}
+
+impl Note
+{
+    pub fn duration_ticks(&self) -> usize
+    {
+        self.end_tick - self.start_tick
+    }
+
+    pub fn end_seconds(&self) -> f64
+    {
+        self.start_seconds + self.duration_seconds
+    }
+}
+
+impl MidiSong
+{
+    pub fn notes(&self) -> Vec<Note>
+    {
+        self.notes_with_tempo_map(&TempoMap::new(self))
+    }
+
+    pub fn notes_with_tempo_map(&self, tempo_map: &TempoMap) -> Vec<Note>
+    {
+        let mut notes = Vec::new();
+
+        for (track_index, track) in self.tracks.iter().enumerate()
+        {
+            let events: Vec<_> = track.absolute_ticks().collect();
+            let end_of_track = events.last().map(|(tick, _)| *tick).unwrap_or(0);
+
+            for (start, end) in track.note_pairs()
+            {
+                let (start_tick, event) = events[start];
+                let MidiEvent::NoteOn { channel, pitch, velocity } = *event else { continue };
+                let end_tick = end.map(|end| events[end].0).unwrap_or(end_of_track);
+                let start_seconds = tempo_map.ticks_to_seconds(start_tick);
+                notes.push(Note
+                {
+                    channel,
+                    pitch,
+                    velocity,
+                    start_tick,
+                    end_tick,
+                    start_seconds,
+                    duration_seconds: tempo_map.ticks_to_seconds(end_tick) - start_seconds,
+                    track: track_index,
+                });
+            }
+        }
+
+        notes.sort_by_key(|n| (n.start_tick, n.track, n.channel, n.pitch, n.end_tick));
+        notes
+    }
+}
+
+impl MidiTrack
+{
+    // event indices of every NoteOn and the NoteOff that ends it, matched first in first out per channel and pitch,
+    // notes still held when the track ends have no end event
+    pub fn note_pairs(&self) -> Vec<(usize, Option<usize>)>
+    {
+        let mut open_notes: HashMap<(u8, u8), VecDeque<usize>> = HashMap::new();
+        let mut pairs = Vec::new();
+
+        for (i, event) in self.events.iter().enumerate()
+        {
+            match *event
+            {
+                MidiEvent::NoteOn { channel, pitch, velocity } if velocity > 0 =>
+                {
+                    open_notes.entry((channel, pitch)).or_default().push_back(i);
+                }
+                MidiEvent::NoteOn { channel, pitch, .. } | MidiEvent::NoteOff { channel, pitch, .. } =>
+                {
+                    if let Some(start) = open_notes.get_mut(&(channel, pitch)).and_then(|q| q.pop_front())
+                    {
+                        pairs.push((start, Some(i)));
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        pairs.extend(open_notes.into_values().flatten().map(|start| (start, None)));
+        pairs
+    }
+
+    pub fn from_notes<'a, I: IntoIterator<Item = &'a Note>>(notes: I) -> MidiTrack
+    {
+        let mut events = Vec::new();
+        for note in notes
+        {
+            events.push((note.start_tick, 1, MidiEvent::NoteOn { channel: note.channel, pitch: note.pitch, velocity: note.velocity }));
+            events.push((note.end_tick, 0, MidiEvent::NoteOff { channel: note.channel, pitch: note.pitch, velocity: 0 }));
+        }
+        events.sort_by_key(|(tick, order, _)| (*tick, *order));
+
+        let end_tick = events.last().map(|(tick, _, _)| *tick).unwrap_or(0);
+        let mut events: Vec<_> = events.into_iter().map(|(tick, _, event)| (tick, event)).collect();
+        events.push((end_tick, MidiEvent::EndOfTrack));
+
+        MidiTrack::from_absolute_ticks(events)
+    }
+}
+
+pub fn notes_to_tracks(notes: &[Note]) -> Vec<MidiTrack>
+{
+    let ntracks = notes.iter().map(|n| n.track + 1).max().unwrap_or(0);
+    (0..ntracks)
+        .map(|i| MidiTrack::from_notes(notes.iter().filter(|n| n.track == i)))
+        .collect()
+}
@@ -2,64 +2,290 @@ use serde::{Serialize, Deserialize};
 
 use crate::pipe::Pipe;
 
-use super::{event::MidiEvent, midi_song::MidiSong};
+use super::{active_notes::ActiveNotes, event::MidiEvent, midi_song::MidiSong, tempo_map::TempoMap};
 
-#[derive(Debug, Clone, PartialEq, Default, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 #[repr(C)]
 pub struct MidiPlayer
 {
     cursors: Vec<MidiTrackCursor>,
-    pulse_duration: f64,
+    tempo_map: TempoMap,
+    elapsed_seconds: f64,
+    playback_rate: f64,
+    paused: bool,
+    loop_region: Option<MidiLoop>,
+    active_notes: ActiveNotes,
+    send_all_notes_off: bool,
+    send_all_sound_off: bool,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[repr(C)]
+pub struct MidiLoop
+{
+    pub start_tick: usize,
+    pub end_tick: usize,
+}
+
+impl Default for MidiPlayer
+{
+    fn default() -> Self
+    {
+        MidiPlayer::new(&MidiSong::default())
+    }
 }
 
 impl MidiPlayer
 {
     pub fn new(song: &MidiSong) -> MidiPlayer
     {
-        let quarter_notes_per_second = 2.0;
-
         MidiPlayer
         {
-            cursors: vec![MidiTrackCursor{ elapsed_seconds: 0.0, index: 0 }; song.tracks.len()],
-            pulse_duration: 1.0 / (song.pulses_per_quarter_note as f64 * quarter_notes_per_second),
+            cursors: vec![MidiTrackCursor{ tick: 0, index: 0 }; song.tracks.len()],
+            tempo_map: TempoMap::new(song),
+            elapsed_seconds: 0.0,
+            playback_rate: 1.0,
+            paused: false,
+            loop_region: None,
+            active_notes: ActiveNotes::new(),
+            send_all_notes_off: false,
+            send_all_sound_off: false,
         }
     }
 
-    pub fn reset(&mut self, song: &MidiSong)
+    pub fn reset<P: Pipe<MidiEvent>>(&mut self, song: &MidiSong, midi_pipe: &mut P)
     {
+        self.release_notes(midi_pipe);
+        let send_all_notes_off = self.send_all_notes_off;
+        let send_all_sound_off = self.send_all_sound_off;
         *self = Self::new(song);
+        self.send_all_notes_off = send_all_notes_off;
+        self.send_all_sound_off = send_all_sound_off;
     }
-    
-    pub fn update<P: Pipe<MidiEvent>>(&mut self, dt: f64, song: &MidiSong, midi_pipe: &mut P)
+
+    pub fn stop<P: Pipe<MidiEvent>>(&mut self, midi_pipe: &mut P)
     {
-        for (i, cursor) in self.cursors.iter_mut().enumerate()
+        self.release_notes(midi_pipe);
+        self.paused = true;
+        self.elapsed_seconds = 0.0;
+        for cursor in &mut self.cursors
         {
-            cursor.elapsed_seconds += dt;
-            let track = &song.tracks[i];
+            *cursor = MidiTrackCursor { tick: 0, index: 0 };
+        }
+    }
+
+    pub fn active_notes(&self) -> &ActiveNotes
+    {
+        &self.active_notes
+    }
+
+    pub fn set_send_all_notes_off(&mut self, enabled: bool)
+    {
+        self.send_all_notes_off = enabled;
+    }
+
+    pub fn set_send_all_sound_off(&mut self, enabled: bool)
+    {
+        self.send_all_sound_off = enabled;
+    }
+
+    fn release_notes<P: Pipe<MidiEvent>>(&mut self, midi_pipe: &mut P)
+    {
+        self.active_notes.release_all(midi_pipe);
+        for channel in 0..16
+        {
+            if self.send_all_sound_off
+            {
+                midi_pipe.send(MidiEvent::ControllerChange { channel, controller: 120, value: 0 });
+            }
+            if self.send_all_notes_off
+            {
+                midi_pipe.send(MidiEvent::ControllerChange { channel, controller: 123, value: 0 });
+            }
+        }
+    }
+
+    pub fn tempo_map(&self) -> &TempoMap
+    {
+        &self.tempo_map
+    }
+
+    pub fn position(&self) -> f64
+    {
+        self.elapsed_seconds
+    }
+
+    pub fn position_ticks(&self) -> f64
+    {
+        self.tempo_map.seconds_to_ticks(self.elapsed_seconds)
+    }
+
+    pub fn pause(&mut self)
+    {
+        self.paused = true;
+    }
+
+    pub fn resume(&mut self)
+    {
+        self.paused = false;
+    }
+
+    pub fn is_paused(&self) -> bool
+    {
+        self.paused
+    }
+
+    pub fn playback_rate(&self) -> f64
+    {
+        self.playback_rate
+    }
+
+    pub fn set_playback_rate(&mut self, rate: f64)
+    {
+        self.playback_rate = rate.max(0.0);
+    }
+
+    pub fn loop_region(&self) -> Option<MidiLoop>
+    {
+        self.loop_region
+    }
+
+    pub fn set_loop_ticks(&mut self, start_tick: usize, end_tick: usize)
+    {
+        self.loop_region = Some(MidiLoop { start_tick, end_tick });
+    }
+
+    pub fn set_loop_markers(&mut self, song: &MidiSong, start_marker: &str, end_marker: &str) -> bool
+    {
+        let find_marker = |name: &str| song.tracks.iter()
+            .flat_map(|track| track.absolute_ticks())
+            .filter(|(_, event)| matches!(event, MidiEvent::Marker { text } if text.decode() == name))
+            .map(|(tick, _)| tick)
+            .min();
+
+        match (find_marker(start_marker), find_marker(end_marker))
+        {
+            (Some(start_tick), Some(end_tick)) if start_tick < end_tick =>
+            {
+                self.set_loop_ticks(start_tick, end_tick);
+                true
+            }
+            _ => false,
+        }
+    }
+
+    pub fn clear_loop(&mut self)
+    {
+        self.loop_region = None;
+    }
 
-            while cursor.index < track.events.len()
+    fn next_event(&self, song: &MidiSong) -> Option<(usize, usize)>
+    {
+        self.cursors.iter().enumerate()
+            .filter_map(|(i, cursor)|
             {
-                let pulses_till_next_event = track.dts[cursor.index] as f64;
-                let seconds_till_next_event = pulses_till_next_event * self.pulse_duration;
-                if seconds_till_next_event > cursor.elapsed_seconds
+                let dt = song.tracks.get(i)?.dts.get(cursor.index)?;
+                Some((cursor.tick + dt, i))
+            })
+            .min()
+    }
+
+    pub fn next_event_seconds(&self, song: &MidiSong) -> Option<f64>
+    {
+        self.next_event(song).map(|(tick, _)| self.tempo_map.ticks_to_seconds(tick))
+    }
+
+    pub fn is_finished(&self, song: &MidiSong) -> bool
+    {
+        self.next_event(song).is_none()
+    }
+
+    pub fn seek_ticks<P: Pipe<MidiEvent>>(&mut self, tick: usize, song: &MidiSong, midi_pipe: &mut P)
+    {
+        let seconds = self.tempo_map.ticks_to_seconds(tick);
+        self.seek_seconds(seconds, song, midi_pipe);
+    }
+
+    pub fn seek_seconds<P: Pipe<MidiEvent>>(&mut self, seconds: f64, song: &MidiSong, midi_pipe: &mut P)
+    {
+        let seconds = seconds.max(0.0);
+        let mut chase = ChaseState::default();
+        self.release_notes(midi_pipe);
+
+        for (i, track) in song.tracks.iter().enumerate()
+        {
+            let mut cursor = MidiTrackCursor { tick: 0, index: 0 };
+            for (tick, event) in track.absolute_ticks()
+            {
+                if self.tempo_map.ticks_to_seconds(tick) >= seconds
                 {
                     break;
                 }
+                chase.record((tick, i, cursor.index), event);
+                cursor = MidiTrackCursor { tick, index: cursor.index + 1 };
+            }
 
-                let midi_event = track.events[cursor.index].clone();
-                cursor.elapsed_seconds -= seconds_till_next_event;
-                cursor.index += 1;
-                
-                if let MidiEvent::SetTempo { microseconds_per_quarter_note } = midi_event
-                {
-                    self.pulse_duration = microseconds_per_quarter_note as f64 / (song.pulses_per_quarter_note as f64 * 1000000.0);
-                }
-                else
+            if let Some(c) = self.cursors.get_mut(i)
+            {
+                *c = cursor;
+            }
+        }
+
+        self.elapsed_seconds = seconds;
+        chase.send(midi_pipe);
+    }
+
+    // plays every event up to seconds, and the events exactly at seconds when inclusive, ignoring pause, rate and loop
+    pub fn advance_to_seconds<P: Pipe<MidiEvent>>(&mut self, seconds: f64, inclusive: bool, song: &MidiSong, midi_pipe: &mut P)
+    {
+        while let Some((tick, i)) = self.next_event(song)
+        {
+            let event_seconds = self.tempo_map.ticks_to_seconds(tick);
+            if event_seconds > seconds || (!inclusive && event_seconds == seconds)
+            {
+                break;
+            }
+
+            let cursor = &mut self.cursors[i];
+            let midi_event = song.tracks[i].events[cursor.index].clone();
+            cursor.tick = tick;
+            cursor.index += 1;
+
+            if !matches!(midi_event, MidiEvent::SetTempo { .. })
+            {
+                self.active_notes.process(&midi_event);
+                midi_pipe.send(midi_event);
+            }
+        }
+
+        self.elapsed_seconds = seconds;
+    }
+
+    pub fn update<P: Pipe<MidiEvent>>(&mut self, dt: f64, song: &MidiSong, midi_pipe: &mut P)
+    {
+        if self.paused
+        {
+            return;
+        }
+
+        let mut target = self.elapsed_seconds + dt * self.playback_rate;
+
+        if let Some(region) = self.loop_region
+        {
+            let start_seconds = self.tempo_map.ticks_to_seconds(region.start_tick);
+            let end_seconds = self.tempo_map.ticks_to_seconds(region.end_tick);
+            if start_seconds < end_seconds && self.elapsed_seconds < end_seconds
+            {
+                while target >= end_seconds
                 {
-                    midi_pipe.send(midi_event);
+                    self.advance_to_seconds(end_seconds, false, song, midi_pipe);
+                    target = start_seconds + (target - end_seconds) % (end_seconds - start_seconds);
+                    self.seek_seconds(start_seconds, song, midi_pipe);
                 }
             }
         }
+
+        self.advance_to_seconds(target, true, song, midi_pipe);
     }
 }
 
@@ -67,6 +293,59 @@ impl MidiPlayer
 #[repr(C)]
 struct MidiTrackCursor
 {
-    elapsed_seconds: f64,
+    tick: usize,
     index: usize,
 }
+
+const CHASE_SLOTS_PER_CHANNEL: usize = 120 + 3;
+
+type ChaseOrder = (usize, usize, usize);
+
+struct ChaseState
+{
+    slots: Vec<Option<(ChaseOrder, MidiEvent)>>,
+}
+
+impl Default for ChaseState
+{
+    fn default() -> Self
+    {
+        ChaseState { slots: vec![None; 16 * CHASE_SLOTS_PER_CHANNEL] }
+    }
+}
+
+impl ChaseState
+{
+    fn record(&mut self, order: ChaseOrder, event: &MidiEvent)
+    {
+        let slot = match *event
+        {
+            MidiEvent::ControllerChange { channel, controller, .. } if controller < 120 => (channel, controller as usize),
+            MidiEvent::ProgramChange { channel, .. } => (channel, 120),
+            MidiEvent::ChannelPressure { channel, .. } => (channel, 121),
+            MidiEvent::PitchBend { channel, .. } => (channel, 122),
+            _ => return,
+        };
+
+        let i = (slot.0 as usize & 0x0F) * CHASE_SLOTS_PER_CHANNEL + slot.1;
+        self.slots[i] = Some((order, event.clone()));
+    }
+
+    // state set after the seek target has to be undone too, so every channel starts from defaults before the chase
+    fn send<P: Pipe<MidiEvent>>(self, midi_pipe: &mut P)
+    {
+        for channel in 0..16
+        {
+            midi_pipe.send(MidiEvent::ControllerChange { channel, controller: 121, value: 0 });
+            midi_pipe.send(MidiEvent::PitchBend { channel, bend_lsb: 0x00, position_msb: 0x40 });
+            midi_pipe.send(MidiEvent::ChannelPressure { channel, pressure: 0 });
+        }
+
+        let mut events: Vec<_> = self.slots.into_iter().flatten().collect();
+        events.sort_by_key(|(order, _)| *order);
+        for (_, event) in events
+        {
+            midi_pipe.send(event);
+        }
+    }
+}
@@ -0,0 +1,175 @@
+use serde::{Serialize, Deserialize};
+
+use crate::pipe::Pipe;
+
+use super::{event::MidiEvent, midi_parser::read_midi_event, midi_writer::write_midi_event};
+
+#[derive(Debug, Clone, PartialEq, Default, Serialize, Deserialize)]
+#[repr(C)]
+pub struct MidiStreamDecoder
+{
+    status: u8,
+    message: [u8; 3],
+    message_len: usize,
+    sysex: Option<Vec<u8>>,
+}
+
+fn data_length(status: u8) -> usize
+{
+    match status
+    {
+        0x80..=0xBF | 0xE0..=0xEF | 0xF2 => 2,
+        0xC0..=0xDF | 0xF1 | 0xF3 | 0xF5 => 1,
+        _ => 0,
+    }
+}
+
+fn send_message<P: Pipe<MidiEvent>>(message: &[u8], midi_pipe: &mut P)
+{
+    if let Ok(event) = read_midi_event(message, &mut 0, &mut 0)
+    {
+        midi_pipe.send(event);
+    }
+}
+
+impl MidiStreamDecoder
+{
+    pub fn new() -> MidiStreamDecoder
+    {
+        MidiStreamDecoder::default()
+    }
+
+    pub fn reset(&mut self)
+    {
+        *self = MidiStreamDecoder::default();
+    }
+
+    pub fn push<P: Pipe<MidiEvent>>(&mut self, bytes: &[u8], midi_pipe: &mut P)
+    {
+        for &byte in bytes
+        {
+            self.push_byte(byte, midi_pipe);
+        }
+    }
+
+    pub fn push_byte<P: Pipe<MidiEvent>>(&mut self, byte: u8, midi_pipe: &mut P)
+    {
+        match byte
+        {
+            0xF8..=0xFF => send_message(&[byte], midi_pipe),
+            0x80..=0xF7 =>
+            {
+                if let Some(mut data) = self.sysex.take()
+                {
+                    if byte == 0xF7
+                    {
+                        data.push(byte);
+                        midi_pipe.send(MidiEvent::SysEx { data });
+                        return;
+                    }
+                    midi_pipe.send(MidiEvent::SysEx { data });
+                }
+
+                self.status = if byte < 0xF0 { byte } else { 0 };
+                self.message_len = 0;
+
+                if byte == 0xF0
+                {
+                    self.sysex = Some(Vec::new());
+                }
+                else if data_length(byte) == 0
+                {
+                    send_message(&[byte], midi_pipe);
+                }
+                else
+                {
+                    self.message[0] = byte;
+                    self.message_len = 1;
+                }
+            }
+            _ =>
+            {
+                if let Some(data) = &mut self.sysex
+                {
+                    data.push(byte);
+                    return;
+                }
+
+                if self.message_len == 0
+                {
+                    if self.status == 0
+                    {
+                        return;
+                    }
+                    self.message[0] = self.status;
+                    self.message_len = 1;
+                }
+
+                self.message[self.message_len] = byte;
+                self.message_len += 1;
+
+                if self.message_len == 1 + data_length(self.message[0])
+                {
+                    send_message(&self.message[..self.message_len], midi_pipe);
+                    self.message_len = 0;
+                }
+            }
+        }
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[repr(C)]
+pub struct MidiStreamEncoder
+{
+    status: u8,
+    use_running_status: bool,
+}
+
+impl MidiStreamEncoder
+{
+    pub fn new(use_running_status: bool) -> MidiStreamEncoder
+    {
+        MidiStreamEncoder { status: 0, use_running_status }
+    }
+
+    pub fn reset(&mut self)
+    {
+        self.status = 0;
+    }
+
+    pub fn encode(&mut self, event: &MidiEvent, bytes: &mut Vec<u8>) -> bool
+    {
+        match event
+        {
+            MidiEvent::SysEx { data } =>
+            {
+                bytes.push(0xF0);
+                bytes.extend_from_slice(data);
+                self.status = 0;
+            }
+            MidiEvent::SysExEscape { data } =>
+            {
+                bytes.extend_from_slice(data);
+                self.status = 0;
+            }
+            event if event.is_meta() => return false,
+            event =>
+            {
+                if !self.use_running_status
+                {
+                    self.status = 0;
+                }
+                write_midi_event(bytes, event, &mut self.status);
+            }
+        }
+        true
+    }
+}
+
+pub fn encode_midi_event(event: &MidiEvent) -> Vec<u8>
+{
+    let mut bytes = Vec::new();
+    MidiStreamEncoder::new(false).encode(event, &mut bytes);
+    bytes
+}
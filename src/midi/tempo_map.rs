@@ -0,0 +1,201 @@
+use serde::{Serialize, Deserialize};
+
+use super::{event::MidiEvent, midi_song::{MidiSong, TimeDivision}};
+
+#[derive(Debug, Clone, PartialEq, Default, Serialize, Deserialize)]
+#[repr(C)]
+pub struct TempoMap
+{
+    pub time_division: TimeDivision,
+    pub tempos: Vec<TempoChange>,
+    pub time_signatures: Vec<TimeSignatureChange>,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Default, Serialize, Deserialize)]
+#[repr(C)]
+pub struct TempoChange
+{
+    pub tick: usize,
+    pub seconds: f64,
+    pub microseconds_per_quarter_note: u32,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Default, Serialize, Deserialize)]
+#[repr(C)]
+pub struct TimeSignatureChange
+{
+    pub tick: usize,
+    pub bar: usize,
+    pub numerator: u8,
+    pub denominator: u8,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[repr(C)]
+pub struct BarBeat
+{
+    pub bar: usize,
+    pub beat: usize,
+    pub ticks: usize,
+}
+
+impl TempoMap
+{
+    pub fn new(song: &MidiSong) -> TempoMap
+    {
+        let mut tempo_events = Vec::new();
+        let mut signature_events = Vec::new();
+        for track in &song.tracks
+        {
+            for (tick, event) in track.absolute_ticks()
+            {
+                match *event
+                {
+                    MidiEvent::SetTempo { microseconds_per_quarter_note } => tempo_events.push((tick, microseconds_per_quarter_note)),
+                    MidiEvent::TimeSignature { nn, dd, .. } => signature_events.push((tick, nn, 1u8.checked_shl(dd as u32).unwrap_or(0))),
+                    _ => {}
+                }
+            }
+        }
+        tempo_events.sort_by_key(|&(tick, _)| tick);
+        signature_events.sort_by_key(|&(tick, _, _)| tick);
+
+        let mut map = TempoMap
+        {
+            time_division: song.time_division,
+            tempos: vec![TempoChange { tick: 0, seconds: 0.0, microseconds_per_quarter_note: TimeDivision::DEFAULT_MICROSECONDS_PER_QUARTER_NOTE }],
+            time_signatures: vec![TimeSignatureChange { tick: 0, bar: 0, numerator: 4, denominator: 4 }],
+        };
+
+        for (tick, microseconds_per_quarter_note) in tempo_events
+        {
+            let seconds = map.ticks_to_seconds(tick);
+            let last = map.tempos.last_mut().unwrap();
+            if last.tick == tick
+            {
+                last.microseconds_per_quarter_note = microseconds_per_quarter_note;
+            }
+            else
+            {
+                map.tempos.push(TempoChange { tick, seconds, microseconds_per_quarter_note });
+            }
+        }
+
+        for (tick, numerator, denominator) in signature_events
+        {
+            if numerator == 0 || denominator == 0
+            {
+                continue;
+            }
+
+            let last = *map.time_signatures.last().unwrap();
+            if last.tick == tick
+            {
+                let change = map.time_signatures.last_mut().unwrap();
+                change.numerator = numerator;
+                change.denominator = denominator;
+            }
+            else
+            {
+                let ticks_per_bar = map.ticks_per_bar(&last).max(1);
+                let bar = last.bar + (tick - last.tick).div_ceil(ticks_per_bar);
+                map.time_signatures.push(TimeSignatureChange { tick, bar, numerator, denominator });
+            }
+        }
+
+        map
+    }
+
+    // smpte songs have no notion of quarter notes, so beats assume the default tempo there
+    pub fn ticks_per_quarter_note(&self) -> f64
+    {
+        match self.time_division
+        {
+            TimeDivision::Metrical { pulses_per_quarter_note } => pulses_per_quarter_note as f64,
+            TimeDivision::Smpte { .. } =>
+            {
+                let seconds_per_quarter_note = TimeDivision::DEFAULT_MICROSECONDS_PER_QUARTER_NOTE as f64 / 1000000.0;
+                seconds_per_quarter_note / self.time_division.seconds_per_pulse(TimeDivision::DEFAULT_MICROSECONDS_PER_QUARTER_NOTE)
+            }
+        }
+    }
+
+    pub fn ticks_per_beat(&self, time_signature: &TimeSignatureChange) -> usize
+    {
+        (self.ticks_per_quarter_note() * 4.0 / time_signature.denominator as f64).round() as usize
+    }
+
+    pub fn ticks_per_bar(&self, time_signature: &TimeSignatureChange) -> usize
+    {
+        self.ticks_per_beat(time_signature) * time_signature.numerator as usize
+    }
+
+    fn tempo_index_at_tick(&self, tick: usize) -> usize
+    {
+        self.tempos.partition_point(|t| t.tick <= tick).saturating_sub(1)
+    }
+
+    fn tempo_index_at_seconds(&self, seconds: f64) -> usize
+    {
+        self.tempos.partition_point(|t| t.seconds <= seconds).saturating_sub(1)
+    }
+
+    pub fn tempo_at(&self, tick: usize) -> u32
+    {
+        self.tempos[self.tempo_index_at_tick(tick)].microseconds_per_quarter_note
+    }
+
+    pub fn bpm_at(&self, tick: usize) -> f64
+    {
+        60000000.0 / self.tempo_at(tick) as f64
+    }
+
+    pub fn ticks_to_seconds(&self, tick: usize) -> f64
+    {
+        let tempo = &self.tempos[self.tempo_index_at_tick(tick)];
+        let seconds_per_pulse = self.time_division.seconds_per_pulse(tempo.microseconds_per_quarter_note);
+        tempo.seconds + (tick - tempo.tick) as f64 * seconds_per_pulse
+    }
+
+    pub fn seconds_to_ticks(&self, seconds: f64) -> f64
+    {
+        let tempo = &self.tempos[self.tempo_index_at_seconds(seconds)];
+        let seconds_per_pulse = self.time_division.seconds_per_pulse(tempo.microseconds_per_quarter_note);
+        tempo.tick as f64 + (seconds - tempo.seconds).max(0.0) / seconds_per_pulse
+    }
+
+    pub fn time_signature_at(&self, tick: usize) -> TimeSignatureChange
+    {
+        let i = self.time_signatures.partition_point(|t| t.tick <= tick).saturating_sub(1);
+        self.time_signatures[i]
+    }
+
+    pub fn bar_beat(&self, tick: usize) -> BarBeat
+    {
+        let time_signature = self.time_signature_at(tick);
+        let ticks_per_beat = self.ticks_per_beat(&time_signature).max(1);
+        let ticks_per_bar = ticks_per_beat * time_signature.numerator as usize;
+
+        let offset = tick - time_signature.tick;
+        let ticks_in_bar = offset % ticks_per_bar;
+        BarBeat
+        {
+            bar: time_signature.bar + offset / ticks_per_bar,
+            beat: ticks_in_bar / ticks_per_beat,
+            ticks: ticks_in_bar % ticks_per_beat,
+        }
+    }
+
+    pub fn bar_beat_to_ticks(&self, bar_beat: BarBeat) -> usize
+    {
+        let i = self.time_signatures.partition_point(|t| t.bar <= bar_beat.bar).saturating_sub(1);
+        let time_signature = &self.time_signatures[i];
+        let ticks_per_beat = self.ticks_per_beat(time_signature);
+        let ticks_per_bar = ticks_per_beat * time_signature.numerator as usize;
+
+        time_signature.tick
+            + (bar_beat.bar - time_signature.bar) * ticks_per_bar
+            + bar_beat.beat * ticks_per_beat
+            + bar_beat.ticks
+    }
+}
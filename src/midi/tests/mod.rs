@@ -0,0 +1,14 @@
+#[cfg(test)] mod test_active_notes;
+#[cfg(test)] mod test_analysis;
+#[cfg(test)] mod test_controllers;
+#[cfg(test)] mod test_edit;
+#[cfg(test)] mod test_lyrics;
+#[cfg(test)] mod test_midi_parser;
+#[cfg(test)] mod test_midi_song;
+#[cfg(test)] mod test_midi_writer;
+#[cfg(test)] mod test_mpe;
+#[cfg(test)] mod test_note;
+#[cfg(test)] mod test_play;
+#[cfg(test)] mod test_stream;
+#[cfg(test)] mod test_tempo_map;
+#[cfg(test)] mod test_ump;
\ No newline at end of file
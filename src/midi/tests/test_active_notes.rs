@@ -0,0 +1,27 @@
+use crate::midi::{active_notes::ActiveNotes, event::MidiEvent};
+
+#[test]
+fn test_active_notes()
+{
+    let mut notes = ActiveNotes::new();
+    notes.process(&MidiEvent::NoteOn { channel: 0, pitch: 127, velocity: 1 });
+    notes.process(&MidiEvent::NoteOn { channel: 15, pitch: 0, velocity: 100 });
+    notes.process(&MidiEvent::NoteOn { channel: 3, pitch: 64, velocity: 100 });
+    notes.process(&MidiEvent::NoteOn { channel: 3, pitch: 64, velocity: 0 });
+    notes.process(&MidiEvent::NoteOn { channel: 4, pitch: 64, velocity: 100 });
+    notes.process(&MidiEvent::NoteOff { channel: 4, pitch: 64, velocity: 64 });
+    notes.process(&MidiEvent::NoteOn { channel: 5, pitch: 1, velocity: 100 });
+    notes.process(&MidiEvent::ControllerChange { channel: 5, controller: 123, value: 0 });
+
+    assert_eq!(notes.iter().collect::<Vec<_>>(), vec![(0, 127), (15, 0)]);
+    assert!(notes.is_active(0, 127));
+    assert!(!notes.is_active(3, 64));
+
+    let mut events = Vec::new();
+    notes.release_all(&mut |e| events.push(e));
+    assert_eq!(events, vec![
+        MidiEvent::NoteOff { channel: 0, pitch: 127, velocity: 0 },
+        MidiEvent::NoteOff { channel: 15, pitch: 0, velocity: 0 },
+    ]);
+    assert!(notes.is_empty());
+}
@@ -0,0 +1,170 @@
+use crate::midi::{event::MidiEvent, midi_song::{MidiSong, MidiTrack, TimeDivision}, note::Note, tempo_map::TempoMap};
+use crate::theory::prelude::*;
+
+fn note(pitch: u8, velocity: u8, start_tick: usize, end_tick: usize) -> Note
+{
+    Note { channel: 0, pitch, velocity, start_tick, end_tick, ..Default::default() }
+}
+
+fn chord_notes(pitches: &[u8], start_tick: usize) -> Vec<Note>
+{
+    pitches.iter().map(|&p| note(p, 80, start_tick, start_tick + 100)).collect()
+}
+
+fn song(meta: Vec<MidiEvent>) -> MidiSong
+{
+    // C, G, Am, F, C with a drum hit that must not influence the harmony
+    let mut notes = Vec::new();
+    notes.extend(chord_notes(&[60, 64, 67], 0));
+    notes.extend(chord_notes(&[55, 59, 62], 100));
+    notes.extend(chord_notes(&[57, 60, 64], 200));
+    notes.extend(chord_notes(&[53, 57, 60], 300));
+    notes.extend(chord_notes(&[60, 64, 67], 400));
+    notes.push(Note { channel: 9, pitch: 37, velocity: 127, start_tick: 0, end_tick: 500, ..Default::default() });
+
+    let mut meta_dts = vec![0; meta.len()];
+    let mut meta = meta;
+    meta.push(MidiEvent::EndOfTrack);
+    meta_dts.push(0);
+
+    MidiSong
+    {
+        t: 1,
+        time_division: TimeDivision::Metrical { pulses_per_quarter_note: 100 },
+        tracks: vec![
+            MidiTrack { dts: meta_dts, events: meta },
+            MidiTrack::from_notes(&notes),
+        ],
+    }
+}
+
+#[test]
+fn test_estimate_key()
+{
+    let song = song(vec![]);
+    assert_eq!(song.key_signature(), None);
+    assert_eq!(song.estimate_key().key, Key::new(0, Mode::Major));
+    assert_eq!(song.key(), Key::new(0, Mode::Major));
+
+    let a_minor = [5.0, 0.0, 1.0, 0.0, 3.0, 1.0, 0.0, 1.0, 1.0, 6.0, 0.0, 1.0];
+    assert_eq!(Key::estimate(&a_minor).key, Key::new(9, Mode::Minor));
+}
+
+#[test]
+fn test_key_signature_takes_precedence()
+{
+    let song = song(vec![MidiEvent::KeySignature { sf: (-3i8) as u8, mi: 1 }]);
+    assert_eq!(song.key(), Key::new(0, Mode::Minor));
+    assert_eq!(song.key().key_signature(), -3);
+    assert_eq!(song.key().to_string(), "C minor");
+    assert_eq!(Key::from_key_signature(2, false).to_string(), "D major");
+    assert_eq!(Key::from_key_signature(-1, true).to_string(), "D minor");
+    assert_eq!(Key::new(6, Mode::Major).key_signature(), 6);
+
+    let analysis = song.analyze();
+    assert!(analysis.key_from_signature);
+    assert_eq!(analysis.estimated_key.key, Key::new(0, Mode::Major));
+}
+
+#[test]
+fn test_beat_chords()
+{
+    let chords: Vec<_> = song(vec![MidiEvent::TimeSignature { nn: 3, dd: 2, cc: 24, bb: 8 }])
+        .beat_chords()
+        .iter()
+        .map(|b| (b.tick, b.bar_beat.bar, b.bar_beat.beat, b.chord.map(|c| c.to_string())))
+        .collect();
+
+    assert_eq!(chords, vec![
+        (0, 0, 0, Some("C".to_string())),
+        (100, 0, 1, Some("G".to_string())),
+        (200, 0, 2, Some("Am".to_string())),
+        (300, 1, 0, Some("F".to_string())),
+        (400, 1, 1, Some("C".to_string())),
+    ]);
+
+    let mut weights = [0.0; 12];
+    weights[2] = 1.0;
+    weights[5] = 1.0;
+    weights[9] = 1.0;
+    weights[0] = 1.0;
+    assert_eq!(Chord::detect(&weights), Some(Chord::new(2, ChordQuality::Minor7)));
+    assert_eq!(Chord::detect(&[0.0; 12]), None);
+}
+
+#[test]
+fn test_track_stats_and_tempo()
+{
+    let song = song(vec![MidiEvent::SetTempo { microseconds_per_quarter_note: 1000000 }]);
+    let analysis = song.analyze();
+
+    assert!(!analysis.key_from_signature);
+    assert!(!analysis.time_signature_from_events);
+    assert_eq!(analysis.time_signature, (4, 4));
+    assert_eq!(analysis.initial_bpm, 60.0);
+    assert_eq!(analysis.average_bpm, 60.0);
+    assert_eq!(analysis.duration_seconds, 5.0);
+
+    assert_eq!(analysis.tracks.len(), 2);
+    assert_eq!(analysis.tracks[0].note_count, 0);
+    assert_eq!(analysis.tracks[0].pitch_range, None);
+
+    let stats = &analysis.tracks[1];
+    assert_eq!(stats.note_count, 16);
+    assert_eq!(stats.pitch_range, Some((37, 67)));
+    assert_eq!(stats.average_velocity, (15.0 * 80.0 + 127.0) / 16.0);
+    assert_eq!(stats.notes_per_second, 3.2);
+    assert_eq!(stats.channels, 0b10_0000_0001);
+}
+
+#[test]
+fn test_estimate_time_signature()
+{
+    let accented = |beats_per_bar: usize, strong: &[usize]| -> MidiSong
+    {
+        let notes: Vec<_> = (0..8 * beats_per_bar)
+            .map(|beat| note(60, if strong.contains(&(beat % beats_per_bar)) { 120 } else { 50 }, beat * 100, beat * 100 + 50))
+            .collect();
+        MidiSong
+        {
+            t: 0,
+            time_division: TimeDivision::Metrical { pulses_per_quarter_note: 100 },
+            tracks: vec![MidiTrack::from_notes(&notes)],
+        }
+    };
+
+    assert_eq!(accented(3, &[0]).estimate_time_signature(), (3, 4));
+    assert_eq!(accented(3, &[0]).analyze().time_signature, (3, 4));
+    assert_eq!(accented(4, &[0]).estimate_time_signature(), (4, 4));
+    assert_eq!(accented(4, &[0, 2]).estimate_time_signature(), (4, 4));
+    assert_eq!(MidiSong::default().estimate_time_signature(), (4, 4));
+
+    let song = song(vec![MidiEvent::TimeSignature { nn: 3, dd: 2, cc: 24, bb: 8 }]);
+    let analysis = song.analyze();
+    assert!(analysis.time_signature_from_events);
+    assert_eq!(analysis.time_signature, (3, 4));
+}
+
+#[test]
+fn test_track_stats_out_of_range_channel()
+{
+    let notes = [Note { channel: 16, ..note(60, 80, 0, 100) }, Note { channel: 0xFF, ..note(62, 80, 0, 100) }];
+    let song = MidiSong { t: 0, time_division: TimeDivision::Metrical { pulses_per_quarter_note: 100 }, tracks: vec![MidiTrack::default()] };
+    let stats = song.track_stats_with(&TempoMap::new(&song), &notes);
+    assert_eq!(stats[0].channels, 0b1000_0000_0000_0001);
+}
+
+#[test]
+fn test_percussion_channel_is_masked()
+{
+    // channel 0x19 is the percussion channel once masked, its F# triad must not outweigh the C triad
+    let pitched = chord_notes(&[60, 64, 67], 0);
+    let mut notes = pitched.clone();
+    notes.extend([54, 58, 61].map(|p| Note { channel: 0x19, ..note(p, 127, 0, 100) }));
+
+    let song = MidiSong { t: 0, time_division: TimeDivision::Metrical { pulses_per_quarter_note: 100 }, tracks: vec![MidiTrack::default()] };
+    let tempo_map = TempoMap::new(&song);
+    let chords = song.beat_chords_with(&tempo_map, &notes);
+    assert_eq!(chords, song.beat_chords_with(&tempo_map, &pitched));
+    assert_eq!(chords[0].chord.map(|c| c.to_string()), Some("C".to_string()));
+}
\ No newline at end of file
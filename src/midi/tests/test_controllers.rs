@@ -0,0 +1,121 @@
+use crate::midi::{controllers::*, event::MidiEvent};
+
+fn cc(channel: u8, controller: u8, value: u8) -> MidiEvent
+{
+    MidiEvent::ControllerChange { channel, controller, value }
+}
+
+#[test]
+fn test_14bit_controllers()
+{
+    let mut tracker = ControllerTracker::new(3);
+    assert_eq!(tracker.value(7), 100);
+    assert_eq!(tracker.value_14bit(1), 0);
+
+    assert_eq!(tracker.process(&cc(3, 1, 0x40)), Some(ControllerChange::Controller14 { controller: 1, value: 0x2000 }));
+    assert_eq!(tracker.process(&cc(3, 33, 0x05)), Some(ControllerChange::Controller14 { controller: 1, value: 0x2005 }));
+    assert_eq!(tracker.value_14bit(1), 0x2005);
+
+    // a new coarse value clears the fine part
+    tracker.process(&cc(3, 1, 0x41));
+    assert_eq!(tracker.value_14bit(1), 0x2080);
+
+    assert_eq!(tracker.process(&cc(3, 64, 127)), Some(ControllerChange::Controller { controller: 64, value: 127 }));
+    assert_eq!(tracker.process(&cc(2, 64, 0)), None);
+    assert_eq!(tracker.value(64), 127);
+
+    let mut tracker = ControllerTracker::new(0);
+    for event in controller_14bit_events(0, 7, 0x1234)
+    {
+        tracker.process(&event);
+    }
+    assert_eq!(tracker.value_14bit(7), 0x1234);
+}
+
+#[test]
+fn test_registered_parameters()
+{
+    let mut tracker = ControllerTracker::new(0);
+    assert_eq!(tracker.pitch_bend_range(), 2.0);
+    assert_eq!(tracker.tuning_semitones(), 0.0);
+
+    assert_eq!(tracker.process(&cc(0, 101, 0)), None);
+    assert_eq!(tracker.process(&cc(0, 100, 0)), None);
+    assert_eq!(tracker.selected_parameter(), Some(ParameterNumber::Registered(RPN_PITCH_BEND_SENSITIVITY)));
+    assert_eq!(tracker.process(&cc(0, 6, 12)), Some(ControllerChange::Parameter { parameter: ParameterNumber::Registered(0), value: 12 << 7 }));
+    tracker.process(&cc(0, 38, 50));
+    assert_eq!(tracker.pitch_bend_range(), 12.5);
+
+    tracker.process(&cc(0, 96, 0));
+    assert_eq!(tracker.parameter(ParameterNumber::Registered(0)), Some(12 << 7 | 51));
+
+    for event in parameter_events(0, ParameterNumber::Registered(RPN_COARSE_TUNING), 62 << 7, false)
+    {
+        tracker.process(&event);
+    }
+    for event in parameter_events(0, ParameterNumber::Registered(RPN_FINE_TUNING), 0x3000, true)
+    {
+        tracker.process(&event);
+    }
+    assert_eq!(tracker.coarse_tuning(), -2);
+    assert_eq!(tracker.fine_tuning(), 50.0);
+    assert_eq!(tracker.tuning_semitones(), -1.5);
+
+    // data entry without a selected parameter is ignored
+    assert_eq!(tracker.selected_parameter(), None);
+    assert_eq!(tracker.process(&cc(0, 6, 1)), None);
+    assert_eq!(tracker.pitch_bend_range(), 12.51);
+}
+
+#[test]
+fn test_non_registered_parameters()
+{
+    let mut tracker = ControllerTracker::new(9);
+    for event in parameter_events(9, ParameterNumber::NonRegistered(0x1A2B), 0x0FFF, false)
+    {
+        tracker.process(&event);
+    }
+    assert_eq!(tracker.selected_parameter(), Some(ParameterNumber::NonRegistered(0x1A2B)));
+    assert_eq!(tracker.parameter(ParameterNumber::NonRegistered(0x1A2B)), Some(0x0FFF));
+    assert_eq!(tracker.parameter(ParameterNumber::NonRegistered(0x0001)), None);
+
+    // selecting a registered parameter deselects the non registered one
+    tracker.process(&cc(9, 101, 0));
+    tracker.process(&cc(9, 100, 1));
+    assert_eq!(tracker.selected_parameter(), Some(ParameterNumber::Registered(RPN_FINE_TUNING)));
+}
+
+#[test]
+fn test_pitch_bend_and_reset()
+{
+    assert_eq!(pitch_bend_value(0x00, 0x40), 0);
+    assert_eq!(pitch_bend_value(0x00, 0x00), -8192);
+    assert_eq!(pitch_bend_value(0x7F, 0x7F), 8191);
+    assert_eq!(pitch_bend_event(4, -8192), MidiEvent::PitchBend { channel: 4, bend_lsb: 0, position_msb: 0 });
+    assert_eq!(pitch_bend_event(4, 8191), MidiEvent::PitchBend { channel: 4, bend_lsb: 0x7F, position_msb: 0x7F });
+
+    let mut controllers = ChannelControllers::new();
+    assert_eq!(controllers.process(&pitch_bend_event(4, 4096)), Some((4, ControllerChange::PitchBend { value: 4096 })));
+    assert_eq!(controllers.channel(4).pitch_bend_semitones(), 1.0);
+    assert_eq!(controllers.process(&MidiEvent::NoteOn { channel: 4, pitch: 60, velocity: 1 }), None);
+
+    controllers.process(&cc(4, 7, 30));
+    controllers.process(&cc(4, 11, 30));
+    controllers.process(&cc(4, 64, 127));
+    controllers.process(&cc(4, 121, 0));
+    let tracker = controllers.channel(4);
+    assert_eq!(tracker.pitch_bend(), 0);
+    assert_eq!(tracker.value(7), 30);
+    assert_eq!(tracker.value(11), 127);
+    assert_eq!(tracker.value(64), 0);
+}
+
+#[test]
+fn test_out_of_range_channels_are_masked()
+{
+    let mut controllers = ChannelControllers::new();
+    assert_eq!(controllers.process(&pitch_bend_event(0x14, 4096)), Some((4, ControllerChange::PitchBend { value: 4096 })));
+    controllers.process(&cc(0x24, 7, 30));
+    assert_eq!(controllers.channel(4).pitch_bend(), 4096);
+    assert_eq!(controllers.channel(4).value(7), 30);
+}
@@ -0,0 +1,142 @@
+use crate::midi::{event::MidiEvent, midi_song::{MidiSong, MidiTrack, TimeDivision}};
+
+fn on(channel: u8, pitch: u8, velocity: u8) -> MidiEvent
+{
+    MidiEvent::NoteOn { channel, pitch, velocity }
+}
+
+fn off(channel: u8, pitch: u8) -> MidiEvent
+{
+    MidiEvent::NoteOff { channel, pitch, velocity: 0 }
+}
+
+fn song() -> MidiSong
+{
+    let tracks = vec![
+        MidiTrack::from_absolute_ticks(vec![
+            (0, MidiEvent::SetTempo { microseconds_per_quarter_note: 500000 }),
+            (0, MidiEvent::EndOfTrack),
+        ]),
+        MidiTrack::from_absolute_ticks(vec![
+            (0, MidiEvent::ProgramChange { channel: 0, preset: 1 }),
+            (3, on(0, 60, 100)),
+            (50, off(0, 60)),
+            (98, on(0, 62, 80)),
+            (190, off(0, 62)),
+            (190, MidiEvent::EndOfTrack),
+        ]),
+        MidiTrack::from_absolute_ticks(vec![
+            (0, MidiEvent::ProgramChange { channel: 9, preset: 0 }),
+            (10, on(9, 36, 127)),
+            (20, off(9, 36)),
+            (20, MidiEvent::EndOfTrack),
+        ]),
+    ];
+
+    MidiSong { t: 1, time_division: TimeDivision::Metrical { pulses_per_quarter_note: 100 }, tracks }
+}
+
+fn note_ticks(song: &MidiSong) -> Vec<(u8, usize, usize)>
+{
+    song.notes().iter().map(|n| (n.pitch, n.start_tick, n.end_tick)).collect()
+}
+
+#[test]
+fn test_quantize()
+{
+    let mut s = song();
+    s.quantize(50, 0.0, false);
+    assert_eq!(note_ticks(&s), vec![(60, 0, 47), (36, 0, 10), (62, 100, 192)]);
+
+    let mut s = song();
+    s.quantize(50, 0.0, true);
+    assert_eq!(note_ticks(&s), vec![(60, 0, 50), (36, 0, 50), (62, 100, 200)]);
+    assert_eq!(s.tracks[1].events.last(), Some(&MidiEvent::EndOfTrack));
+    assert_eq!(s.tracks[1].absolute_ticks().last().map(|(tick, _)| tick), Some(200));
+
+    let mut s = song();
+    s.quantize(50, 0.4, true);
+    assert_eq!(note_ticks(&s), vec![(60, 0, 70), (36, 0, 70), (62, 100, 200)]);
+}
+
+#[test]
+fn test_quantize_hanging_note()
+{
+    let track = MidiTrack::from_absolute_ticks(vec![
+        (3, on(0, 60, 100)),
+        (27, on(0, 60, 90)),
+        (40, off(0, 60)),
+        (190, MidiEvent::EndOfTrack),
+    ]);
+    assert_eq!(track.note_pairs(), vec![(0, Some(2)), (1, None)]);
+
+    let mut s = MidiSong { t: 0, time_division: TimeDivision::Metrical { pulses_per_quarter_note: 100 }, tracks: vec![track] };
+    assert_eq!(note_ticks(&s), vec![(60, 3, 40), (60, 27, 190)]);
+
+    s.quantize(50, 0.0, false);
+    assert_eq!(note_ticks(&s), vec![(60, 0, 37), (60, 50, 190)]);
+}
+
+#[test]
+fn test_transpose_and_velocity()
+{
+    let mut s = song();
+    s.transpose(12, 0, 70);
+    s.scale_velocities(1.5);
+    let notes: Vec<_> = s.notes().iter().map(|n| (n.pitch, n.velocity)).collect();
+    assert_eq!(notes, vec![(70, 127), (36, 127), (70, 120)]);
+
+    let mut s = song();
+    s.scale_velocities(0.0);
+    assert!(s.notes().iter().all(|n| n.velocity == 1));
+}
+
+#[test]
+fn test_transpose_skips_drums()
+{
+    let mut s = song();
+    s.tracks[2] = MidiTrack::from_absolute_ticks(vec![
+        (10, on(9, 36, 127)),
+        (12, MidiEvent::PolyPressure { channel: 0x19, pitch: 38, pressure: 40 }),
+        (15, on(0x19, 38, 90)),
+        (20, off(9, 36)),
+        (25, off(0x19, 38)),
+        (25, MidiEvent::EndOfTrack),
+    ]);
+    let drums = s.tracks[2].clone();
+
+    s.transpose(-7, 0, 127);
+    assert_eq!(s.tracks[2], drums);
+    assert_eq!(s.notes().iter().filter(|n| n.track == 1).map(|n| n.pitch).collect::<Vec<_>>(), vec![53, 55]);
+}
+
+#[test]
+fn test_time_stretch()
+{
+    let mut s = song();
+    s.time_stretch(2.0);
+    assert_eq!(note_ticks(&s), vec![(60, 6, 100), (36, 20, 40), (62, 196, 380)]);
+}
+
+#[test]
+fn test_merge_and_split()
+{
+    let original = song();
+    let merged = original.merge_tracks();
+    assert_eq!(merged.t, 0);
+    assert_eq!(merged.tracks.len(), 1);
+    assert_eq!(merged.tracks[0].events.iter().filter(|e| **e == MidiEvent::EndOfTrack).count(), 1);
+    assert_eq!(merged.tracks[0].events.last(), Some(&MidiEvent::EndOfTrack));
+    assert_eq!(note_ticks(&merged), note_ticks(&original));
+
+    let split = merged.split_by_channel();
+    assert_eq!(split.t, 1);
+    assert_eq!(split.tracks.len(), 3);
+    assert_eq!(split.tracks[0].events, vec![MidiEvent::SetTempo { microseconds_per_quarter_note: 500000 }, MidiEvent::EndOfTrack]);
+    assert_eq!(split.tracks[1].events[0], MidiEvent::ProgramChange { channel: 0, preset: 1 });
+    assert_eq!(split.tracks[2].events[0], MidiEvent::ProgramChange { channel: 9, preset: 0 });
+    assert!(split.tracks.iter().all(|t| t.events.last() == Some(&MidiEvent::EndOfTrack)));
+
+    let split_notes: Vec<_> = split.notes().iter().map(|n| (n.pitch, n.start_tick, n.end_tick, n.track)).collect();
+    assert_eq!(split_notes, vec![(60, 3, 50, 1), (36, 10, 20, 2), (62, 98, 190, 1)]);
+}
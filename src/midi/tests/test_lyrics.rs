@@ -0,0 +1,96 @@
+use crate::midi::{event::MidiEvent, midi_song::{MidiSong, MidiTrack, TimeDivision}};
+
+fn song(events: Vec<(usize, MidiEvent)>) -> MidiSong
+{
+    let mut events = events;
+    let end = events.iter().map(|(tick, _)| *tick).max().unwrap_or(0);
+    events.push((end, MidiEvent::EndOfTrack));
+
+    MidiSong
+    {
+        t: 0,
+        time_division: TimeDivision::Metrical { pulses_per_quarter_note: 100 },
+        tracks: vec![MidiTrack::from_absolute_ticks(events)],
+    }
+}
+
+fn text(text: &str) -> MidiEvent
+{
+    MidiEvent::Text { text: text.into() }
+}
+
+fn lyric(text: &str) -> MidiEvent
+{
+    MidiEvent::Lyrics { text: text.into() }
+}
+
+#[test]
+fn test_karaoke_headers_and_breaks()
+{
+    let song = song(vec![
+        (0, text("@KMIDI KARAOKE FILE")),
+        (0, text("@LENGL")),
+        (0, text("@TTwinkle")),
+        (0, text("@TTraditional")),
+        (100, text("\\Twin")),
+        (150, text("kle ")),
+        (200, text("/lit")),
+        (250, text("tle")),
+        (300, text("\\How ")),
+        (350, text("I")),
+    ]);
+
+    let timeline = song.lyrics_timeline();
+    assert!(timeline.is_karaoke());
+    assert_eq!(timeline.header('T').collect::<Vec<_>>(), vec!["Twinkle", "Traditional"]);
+    assert_eq!(timeline.header('L').next(), Some("ENGL"));
+
+    let lines: Vec<_> = timeline.lines.iter().map(|l| (l.text.as_str(), l.paragraph)).collect();
+    assert_eq!(lines, vec![("Twinkle ", 0), ("little", 0), ("How I", 1)]);
+    assert_eq!(timeline.syllables[0].text, "Twin");
+    assert_eq!(timeline.syllables[4].line, 2);
+}
+
+#[test]
+fn test_lyric_events_with_line_feeds()
+{
+    let song = song(vec![
+        (0, text("a plain comment")),
+        (0, lyric("Hel")),
+        (50, lyric("lo\r")),
+        (100, lyric("\r")),
+        (150, lyric("world")),
+    ]);
+
+    let timeline = song.lyrics_timeline();
+    assert!(!timeline.is_karaoke());
+    assert_eq!(timeline.texts.len(), 1);
+
+    let lines: Vec<_> = timeline.lines.iter().map(|l| l.text.as_str()).collect();
+    assert_eq!(lines, vec!["Hello", "world"]);
+    assert_eq!(timeline.lines[0].end_seconds, timeline.lines[1].start_seconds);
+}
+
+#[test]
+fn test_syllable_at_honors_tempo()
+{
+    let song = song(vec![
+        (0, lyric("one ")),
+        (100, MidiEvent::SetTempo { microseconds_per_quarter_note: 1_000_000 }),
+        (100, lyric("two ")),
+        (200, lyric("three")),
+        (200, MidiEvent::Marker { text: "Chorus".into() }),
+    ]);
+
+    let timeline = song.lyrics_timeline();
+    let seconds: Vec<_> = timeline.syllables.iter().map(|s| s.seconds).collect();
+    assert_eq!(seconds, vec![0.0, 0.5, 1.5]);
+
+    assert_eq!(timeline.syllable_at(-0.1), None);
+    assert_eq!(timeline.syllable_at(0.49).map(|s| s.text.as_str()), Some("one "));
+    assert_eq!(timeline.syllable_at(0.5).map(|s| s.text.as_str()), Some("two "));
+    assert_eq!(timeline.syllable_at(10.0).map(|s| s.text.as_str()), Some("three"));
+
+    assert_eq!(timeline.marker_at(1.0), None);
+    assert_eq!(timeline.marker_at(1.5).map(|m| m.text.as_str()), Some("Chorus"));
+}
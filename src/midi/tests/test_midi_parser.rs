@@ -0,0 +1,134 @@
+use crate::midi::{event::MidiEvent, midi_parse_error::MidiParseError, midi_parser::{read_midi_event, read_midi_file, read_midi_file_with, ParseOptions}, midi_song::{MidiSong, MidiTrack, TimeDivision}, midi_writer::write_midi_file, text::TextEncoding};
+
+fn test_song() -> MidiSong
+{
+    MidiSong
+    {
+        t: 1,
+        time_division: TimeDivision::Metrical { pulses_per_quarter_note: 96 },
+        tracks: vec![
+            MidiTrack
+            {
+                dts: vec![0, 0, 0],
+                events: vec![
+                    MidiEvent::TrackName { name: "conductor".into() },
+                    MidiEvent::SetTempo { microseconds_per_quarter_note: 500000 },
+                    MidiEvent::EndOfTrack,
+                ],
+            },
+            MidiTrack
+            {
+                dts: vec![0, 96, 0],
+                events: vec![
+                    MidiEvent::NoteOn { channel: 0, pitch: 60, velocity: 100 },
+                    MidiEvent::NoteOff { channel: 0, pitch: 60, velocity: 0 },
+                    MidiEvent::EndOfTrack,
+                ],
+            },
+        ],
+    }
+}
+
+#[test]
+fn test_truncated_file_does_not_panic()
+{
+    let bytes = write_midi_file(&test_song());
+    for n in 0..bytes.len()
+    {
+        assert!(read_midi_file(&bytes[..n]).is_err());
+    }
+    assert!(read_midi_file(&bytes).is_ok());
+}
+
+#[test]
+fn test_bad_magic()
+{
+    let mut bytes = write_midi_file(&test_song());
+    bytes[0] = b'X';
+    assert_eq!(read_midi_file(&bytes), Err(MidiParseError::BadMagic { found: *b"XThd", offset: 0, track: None }));
+
+}
+
+#[test]
+fn test_unknown_chunks_are_skipped()
+{
+    let song = test_song();
+    let bytes = write_midi_file(&song);
+    let second_track = 22 + u32::from_be_bytes([bytes[18], bytes[19], bytes[20], bytes[21]]) as usize;
+
+    let mut alien = bytes[..second_track].to_vec();
+    alien.extend_from_slice(b"XFIH\0\0\0\x03abc");
+    alien.extend_from_slice(&bytes[second_track..]);
+    assert_eq!(read_midi_file(&alien), Ok(song));
+
+    let mut truncated = bytes[..second_track].to_vec();
+    truncated.extend_from_slice(b"XFIH\0\0\0\x08abc");
+    assert_eq!(read_midi_file(&truncated), Err(MidiParseError::TruncatedChunk { offset: second_track + 8, track: Some(1) }));
+}
+
+#[test]
+fn test_missing_running_status()
+{
+    let mut bytes = write_midi_file(&test_song());
+    let first_status = bytes.len() - 11;
+    bytes[first_status] = 0x40;
+    let err = read_midi_file(&bytes).unwrap_err();
+    assert_eq!(err, MidiParseError::UnsupportedStatus { status: 0, offset: first_status, track: Some(1) });
+    assert_eq!(err.offset(), Some(first_status));
+    assert_eq!(err.track(), Some(1));
+}
+
+#[test]
+fn test_invalid_utf8()
+{
+    let mut bytes = write_midi_file(&test_song());
+    bytes[26] = 0xFF;
+
+    let strict = ParseOptions { strict_utf8: true, ..Default::default() };
+    assert_eq!(read_midi_file_with(&bytes, &strict), Err(MidiParseError::InvalidUtf8 { meta_type: 0x03, offset: 26, track: Some(0) }));
+
+    let song = read_midi_file(&bytes).unwrap();
+    let MidiEvent::TrackName { name } = &song.tracks[0].events[0] else { panic!() };
+    assert_eq!(name.as_bytes(), b"\xFFonductor");
+    assert_eq!(name.as_utf8(), None);
+    assert_eq!(name.decode(), "\u{FF}onductor");
+    assert_eq!(name.to_utf8_lossy(), "\u{FFFD}onductor");
+
+    let lossy = ParseOptions { text_encoding: TextEncoding::Utf8Lossy, ..Default::default() };
+    let song = read_midi_file_with(&bytes, &lossy).unwrap();
+    let MidiEvent::TrackName { name } = &song.tracks[0].events[0] else { panic!() };
+    assert_eq!(name.decode(), "\u{FFFD}onductor");
+    assert_eq!(name.to_latin1(), "\u{FF}onductor");
+}
+
+#[test]
+fn test_bad_var_len()
+{
+    let mut bytes = write_midi_file(&test_song());
+    let second_track_events = bytes.len() - 12;
+    bytes[second_track_events..second_track_events + 4].copy_from_slice(&[0xFF, 0xFF, 0xFF, 0xFF]);
+    assert_eq!(read_midi_file(&bytes), Err(MidiParseError::BadVarLen { offset: second_track_events, track: Some(1) }));
+}
+
+#[test]
+fn test_running_status_survives_real_time_bytes()
+{
+    let bs = [0x90, 60, 100, 0xF8, 64, 100, 0xFF, 0x2F, 0x00, 62, 100];
+    let mut cursor = 0;
+    let mut status = 0;
+
+    assert_eq!(read_midi_event(&bs, &mut cursor, &mut status), Ok(MidiEvent::NoteOn { channel: 0, pitch: 60, velocity: 100 }));
+    assert_eq!(read_midi_event(&bs, &mut cursor, &mut status), Ok(MidiEvent::TimingTick));
+    assert_eq!(read_midi_event(&bs, &mut cursor, &mut status), Ok(MidiEvent::NoteOn { channel: 0, pitch: 64, velocity: 100 }));
+    assert_eq!(read_midi_event(&bs, &mut cursor, &mut status), Ok(MidiEvent::EndOfTrack));
+    assert_eq!(read_midi_event(&bs, &mut cursor, &mut status), Err(MidiParseError::UnsupportedStatus { status: 0, offset: 9, track: None }));
+}
+
+#[test]
+fn test_unsupported_system_status()
+{
+    let bs = [0xF1, 0x00];
+    let mut cursor = 0;
+    let mut status = 0x90;
+    assert_eq!(read_midi_event(&bs, &mut cursor, &mut status), Err(MidiParseError::UnsupportedStatus { status: 0xF1, offset: 0, track: None }));
+}
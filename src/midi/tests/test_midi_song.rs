@@ -0,0 +1,34 @@
+use crate::midi::midi_song::TimeDivision;
+
+#[test]
+fn test_time_division_from_u16()
+{
+    assert_eq!(TimeDivision::from(0x01E0), TimeDivision::Metrical { pulses_per_quarter_note: 480 });
+    assert_eq!(TimeDivision::from(0xE250), TimeDivision::Smpte { frames_per_second: 30, ticks_per_frame: 80 });
+    assert_eq!(TimeDivision::from(0xE728), TimeDivision::Smpte { frames_per_second: 25, ticks_per_frame: 40 });
+    assert_eq!(TimeDivision::from(0xE304), TimeDivision::Smpte { frames_per_second: 29, ticks_per_frame: 4 });
+}
+
+#[test]
+fn test_time_division_to_u16()
+{
+    for x in [0x0060u16, 0x01E0, 0xE250, 0xE728, 0xE304, 0xE864]
+    {
+        assert_eq!(u16::from(TimeDivision::from(x)), x);
+    }
+}
+
+#[test]
+fn test_seconds_per_pulse()
+{
+    let metrical = TimeDivision::Metrical { pulses_per_quarter_note: 500 };
+    assert_eq!(metrical.seconds_per_pulse(500000), 0.001);
+    assert_eq!(metrical.seconds_per_pulse(250000), 0.0005);
+
+    let smpte = TimeDivision::Smpte { frames_per_second: 25, ticks_per_frame: 40 };
+    assert_eq!(smpte.seconds_per_pulse(500000), 0.001);
+    assert_eq!(smpte.seconds_per_pulse(250000), 0.001);
+
+    let drop_frame = TimeDivision::Smpte { frames_per_second: 29, ticks_per_frame: 1 };
+    assert!((drop_frame.seconds_per_pulse(500000) - 1001.0 / 30000.0).abs() < 1e-12);
+}
@@ -0,0 +1,148 @@
+use crate::midi::{event::MidiEvent, midi_parser::read_midi_file, midi_song::{MidiSong, MidiTrack, TimeDivision}, midi_writer::{write_midi_file, write_var_len}};
+
+fn song_from_events(events: Vec<MidiEvent>) -> MidiSong
+{
+    let dts = (0..events.len()).map(|i| i * 37).collect();
+    MidiSong
+    {
+        t: 1,
+        time_division: TimeDivision::Metrical { pulses_per_quarter_note: 480 },
+        tracks: vec![MidiTrack { dts, events }],
+    }
+}
+
+#[test]
+fn test_write_var_len()
+{
+    let cases: [(usize, &[u8]); 6] =
+    [
+        (0x00, &[0x00]),
+        (0x7F, &[0x7F]),
+        (0x80, &[0x81, 0x00]),
+        (0x2000, &[0xC0, 0x00]),
+        (0x3FFF, &[0xFF, 0x7F]),
+        (0x0FFFFFFF, &[0xFF, 0xFF, 0xFF, 0x7F]),
+    ];
+
+    for (value, expected) in cases
+    {
+        let mut bytes = Vec::new();
+        write_var_len(&mut bytes, value);
+        assert_eq!(bytes, expected);
+    }
+}
+
+#[test]
+fn test_running_status()
+{
+    let song = song_from_events(vec![
+        MidiEvent::NoteOn { channel: 2, pitch: 60, velocity: 100 },
+        MidiEvent::NoteOn { channel: 2, pitch: 64, velocity: 100 },
+        MidiEvent::EndOfTrack,
+    ]);
+
+    let bytes = write_midi_file(&song);
+    let track_data = &bytes[22..];
+    assert_eq!(track_data, &[0x00, 0x92, 60, 100, 0x25, 64, 100, 0x4A, 0xFF, 0x2F, 0x00]);
+}
+
+#[test]
+fn test_channel_and_data_are_masked()
+{
+    let song = song_from_events(vec![
+        MidiEvent::NoteOff { channel: 16, pitch: 60, velocity: 0 },
+        MidiEvent::NoteOn { channel: 0x13, pitch: 0xBC, velocity: 0xFF },
+        MidiEvent::PitchBend { channel: 0xFF, bend_lsb: 0x80, position_msb: 0xC0 },
+        MidiEvent::EndOfTrack,
+    ]);
+
+    let bytes = write_midi_file(&song);
+    let track_data = &bytes[22..];
+    assert_eq!(track_data, &[0x00, 0x80, 60, 0, 0x25, 0x93, 0x3C, 0x7F, 0x4A, 0xEF, 0x00, 0x40, 0x6F, 0xFF, 0x2F, 0x00]);
+}
+
+#[test]
+fn test_round_trip()
+{
+    let song = song_from_events(vec![
+        MidiEvent::TrackName { name: "piano".into() },
+        MidiEvent::Copyright { text: "(c) dol".into() },
+        MidiEvent::Text { text: "some text".into() },
+        MidiEvent::InstrumentName { name: "grand".into() },
+        MidiEvent::ProgramName { name: "bright".into() },
+        MidiEvent::DeviceName { name: "port a".into() },
+        MidiEvent::ChannelPrefix { channel: 3 },
+        MidiEvent::MidiPort { port: 1 },
+        MidiEvent::SMTPEOffset { hh: 0x60, mm: 1, ss: 2, fr: 3, ff: 4 },
+        MidiEvent::SetTempo { microseconds_per_quarter_note: 428571 },
+        MidiEvent::TimeSignature { nn: 6, dd: 3, cc: 24, bb: 8 },
+        MidiEvent::KeySignature { sf: 2, mi: 1 },
+        MidiEvent::Marker { text: "verse".into() },
+        MidiEvent::SequencerSpecific { data: vec![0x00, 0x00, 0x41, 0x12] },
+        MidiEvent::SysEx { data: vec![0x7E, 0x7F, 0x09, 0x01, 0xF7] },
+        MidiEvent::SysEx { data: vec![0x43, 0x12, 0x00] },
+        MidiEvent::SysExEscape { data: vec![0x43, 0xF7] },
+        MidiEvent::SongPosition { position_lsb: 0x10, position_msb: 0x02 },
+        MidiEvent::SongSelect { song_number: 4 },
+        MidiEvent::BusSelect { bus_number: 2 },
+        MidiEvent::TuneRequest,
+        MidiEvent::ProgramChange { channel: 0, preset: 5 },
+        MidiEvent::ControllerChange { channel: 0, controller: 7, value: 90 },
+        MidiEvent::StartSong,
+        MidiEvent::NoteOn { channel: 0, pitch: 60, velocity: 100 },
+        MidiEvent::TimingTick,
+        MidiEvent::NoteOn { channel: 0, pitch: 67, velocity: 80 },
+        MidiEvent::PolyPressure { channel: 0, pitch: 67, pressure: 50 },
+        MidiEvent::Lyrics { text: "la".into() },
+        MidiEvent::ChannelPressure { channel: 0, pressure: 30 },
+        MidiEvent::ActiveSensing,
+        MidiEvent::PitchBend { channel: 0, bend_lsb: 0x10, position_msb: 0x50 },
+        MidiEvent::StopSong,
+        MidiEvent::NoteOff { channel: 0, pitch: 60, velocity: 0 },
+        MidiEvent::ContinueSong,
+        MidiEvent::NoteOff { channel: 9, pitch: 67, velocity: 64 },
+        MidiEvent::UnknownMetaMessage { meta_type: 0x7E, data: vec![1, 2, 3] },
+        MidiEvent::EndOfTrack,
+    ]);
+
+    let bytes = write_midi_file(&song);
+    let parsed = read_midi_file(&bytes).unwrap();
+    assert_eq!(parsed, song);
+    assert_eq!(write_midi_file(&parsed), bytes);
+}
+
+#[test]
+fn test_round_trip_multiple_tracks()
+{
+    let mut song = song_from_events(vec![
+        MidiEvent::SetTempo { microseconds_per_quarter_note: 500000 },
+        MidiEvent::EndOfTrack,
+    ]);
+    song.tracks.push(MidiTrack
+    {
+        dts: vec![0, 200, 100000],
+        events: vec![
+            MidiEvent::NoteOn { channel: 1, pitch: 48, velocity: 64 },
+            MidiEvent::NoteOn { channel: 1, pitch: 48, velocity: 0 },
+            MidiEvent::EndOfTrack,
+        ],
+    });
+
+    let bytes = write_midi_file(&song);
+    assert_eq!(read_midi_file(&bytes).unwrap(), song);
+}
+
+#[test]
+fn test_round_trip_long_meta_and_smpte()
+{
+    let mut song = song_from_events(vec![
+        MidiEvent::Lyrics { text: "la ".repeat(100).into() },
+        MidiEvent::SysEx { data: vec![0x11; 300] },
+        MidiEvent::EndOfTrack,
+    ]);
+    song.time_division = TimeDivision::Smpte { frames_per_second: 25, ticks_per_frame: 40 };
+
+    let bytes = write_midi_file(&song);
+    assert_eq!(&bytes[12..14], &[0xE7, 0x28]);
+    assert_eq!(read_midi_file(&bytes).unwrap(), song);
+}
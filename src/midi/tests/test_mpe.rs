@@ -0,0 +1,155 @@
+use std::collections::VecDeque;
+
+use crate::{midi::{event::MidiEvent, mpe::*}, pipe::Pipe};
+
+fn cc(channel: u8, controller: u8, value: u8) -> MidiEvent
+{
+    MidiEvent::ControllerChange { channel, controller, value }
+}
+
+#[test]
+fn test_zone_configuration()
+{
+    let mut decoder = MpeDecoder::new();
+    for event in MpeZone::lower(7).configuration_events()
+    {
+        decoder.process(&event);
+    }
+    for event in MpeZone::upper(10).configuration_events()
+    {
+        decoder.process(&event);
+    }
+
+    let config = decoder.configuration();
+    assert_eq!(config.upper.member_count, 10);
+    assert_eq!(config.upper.member_channels(), 5..=14);
+    assert_eq!(config.lower.member_count, 4);
+    assert_eq!(config.lower.member_channels(), 1..=4);
+    assert_eq!(config.zone_for_channel(0).map(|z| z.kind), Some(MpeZoneKind::Lower));
+    assert_eq!(config.zone_for_channel(9).map(|z| z.kind), Some(MpeZoneKind::Upper));
+
+    // the manager channel of the upper zone can disable it again
+    decoder.process(&cc(15, 101, 0));
+    decoder.process(&cc(15, 100, 6));
+    decoder.process(&cc(15, 6, 0));
+    assert!(!decoder.configuration().upper.is_enabled());
+    assert_eq!(decoder.configuration().zone_for_channel(9), None);
+}
+
+#[test]
+fn test_zone_member_count_is_clamped()
+{
+    let zone = MpeZone { member_count: 200, ..MpeZone::upper(0) };
+    assert_eq!(zone.member_channels(), 0..=14);
+    assert_eq!(MpeZone { member_count: 16, ..MpeZone::lower(0) }.member_channels(), 1..=15);
+
+    let mut config = MpeConfiguration::new();
+    config.set_zone(zone);
+    assert_eq!(config.upper.member_count, 15);
+    assert_eq!(config.lower.member_count, 0);
+    assert_eq!(config.zone_for_channel(3).map(|z| z.kind), Some(MpeZoneKind::Upper));
+}
+
+#[test]
+fn test_out_of_range_channels_are_masked()
+{
+    let mut decoder = MpeDecoder::with_configuration(MpeConfiguration { lower: MpeZone::lower(15), ..Default::default() });
+
+    decoder.send(MidiEvent::NoteOn { channel: 16, pitch: 60, velocity: 100 });
+    decoder.send(MidiEvent::NoteOn { channel: 0x21, pitch: 62, velocity: 100 });
+    decoder.send(MidiEvent::ChannelPressure { channel: 0x11, pressure: 127 });
+    decoder.send(MidiEvent::PitchBend { channel: 0x11, bend_lsb: 0, position_msb: 0x50 });
+    assert!(decoder.voice(0, 60).is_some());
+    assert_eq!(decoder.voice(1, 62).unwrap().pressure, 1.0);
+    assert_eq!(decoder.voice(1, 62).unwrap().pitch_bend, 12.0);
+
+    decoder.send(MidiEvent::NoteOff { channel: 0x10, pitch: 60, velocity: 0 });
+    assert!(decoder.voice(0, 60).is_none());
+    decoder.send(cc(0x11, 120, 0));
+    assert!(decoder.voices().is_empty());
+}
+
+#[test]
+fn test_per_note_expression()
+{
+    let mut decoder = MpeDecoder::with_configuration(MpeConfiguration { lower: MpeZone::lower(15), ..Default::default() });
+
+    decoder.send(MidiEvent::PitchBend { channel: 1, bend_lsb: 0, position_msb: 0x50 });
+    decoder.send(cc(1, MPE_TIMBRE_CONTROLLER, 127));
+    decoder.send(MidiEvent::NoteOn { channel: 1, pitch: 60, velocity: 100 });
+    decoder.send(MidiEvent::NoteOn { channel: 2, pitch: 64, velocity: 90 });
+    decoder.send(MidiEvent::ChannelPressure { channel: 2, pressure: 127 });
+
+    let voice = *decoder.voice(1, 60).unwrap();
+    assert_eq!(voice.velocity, 100);
+    assert_eq!(voice.pitch_bend, 12.0);
+    assert_eq!(voice.timbre, 1.0);
+    assert_eq!(voice.pressure, 0.0);
+    assert_eq!(voice.bent_pitch(), 72.0);
+
+    let voice = *decoder.voice(2, 64).unwrap();
+    assert_eq!(voice.pressure, 1.0);
+    assert_eq!(voice.pitch_bend, 0.0);
+
+    // manager channel bend moves the whole zone with the manager range
+    decoder.send(MidiEvent::PitchBend { channel: 0, bend_lsb: 0, position_msb: 0 });
+    assert_eq!(decoder.voice(2, 64).unwrap().bent_pitch(), 62.0);
+
+    // member pitch bend range applies to every member channel
+    for event in [cc(3, 101, 0), cc(3, 100, 0), cc(3, 6, 24), cc(3, 38, 0)]
+    {
+        decoder.send(event);
+    }
+    assert_eq!(decoder.configuration().lower.member_pitch_bend_range, 24.0);
+    assert_eq!(decoder.voice(1, 60).unwrap().pitch_bend, 6.0);
+
+    decoder.send(MidiEvent::NoteOff { channel: 1, pitch: 60, velocity: 0 });
+    assert_eq!(decoder.voices().len(), 1);
+    decoder.send(cc(0, 123, 0));
+    assert!(decoder.voices().is_empty());
+}
+
+#[test]
+fn test_allocator_rotates_member_channels()
+{
+    let mut allocator = MpeAllocator::new(MpeZone::lower(3));
+    let mut events = VecDeque::new();
+
+    allocator.configure(&mut events);
+    assert_eq!(events.iter().take(3).cloned().collect::<Vec<_>>(), vec![cc(0, 101, 0), cc(0, 100, 6), cc(0, 6, 3)]);
+    assert!(events.contains(&cc(1, 6, 48)));
+    assert!(events.contains(&cc(3, 6, 48)));
+    events.clear();
+
+    let voice = |pitch| Voice { pitch, velocity: 100, pitch_bend: 24.0, pressure: 0.5, timbre: 0.0, ..Default::default() };
+    let channels: Vec<u8> = [60, 62, 64].iter().map(|&p| allocator.note_on(&voice(p), &mut events)).collect();
+    assert_eq!(channels, vec![1, 2, 3]);
+    assert_eq!(events.iter().take(4).cloned().collect::<Vec<_>>(), vec![
+        MidiEvent::PitchBend { channel: 1, bend_lsb: 0, position_msb: 0x60 },
+        cc(1, MPE_TIMBRE_CONTROLLER, 0),
+        MidiEvent::ChannelPressure { channel: 1, pressure: 64 },
+        MidiEvent::NoteOn { channel: 1, pitch: 60, velocity: 100 },
+    ]);
+
+    // a released channel is reused before doubling up on a busy one
+    assert_eq!(allocator.note_off(62, 0, &mut events), Some(2));
+    assert_eq!(allocator.note_on(&voice(65), &mut events), 2);
+    assert_eq!(allocator.note_on(&voice(67), &mut events), 3);
+
+    events.clear();
+    assert_eq!(allocator.timbre(64, 1.0, &mut events), Some(3));
+    assert_eq!(allocator.pressure(99, 1.0, &mut events), None);
+    assert_eq!(Vec::from(events.clone()), vec![cc(3, MPE_TIMBRE_CONTROLLER, 127)]);
+
+    events.clear();
+    allocator.release_all(&mut events);
+    assert_eq!(events.len(), 4);
+
+    // the decoder reconstructs what the allocator sends
+    let mut decoder = MpeDecoder::with_configuration(MpeConfiguration { lower: MpeZone::lower(3), ..Default::default() });
+    let mut allocator = MpeAllocator::new(MpeZone::lower(3));
+    allocator.note_on(&voice(60), &mut decoder);
+    let decoded = decoder.voice(1, 60).unwrap();
+    assert_eq!(decoded.pitch_bend, 24.0);
+    assert_eq!(decoded.pressure, 64.0 / 127.0);
+}
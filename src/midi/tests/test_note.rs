@@ -0,0 +1,80 @@
+use crate::midi::{event::MidiEvent, midi_song::{MidiSong, MidiTrack, TimeDivision}, note::{notes_to_tracks, Note}};
+
+fn on(pitch: u8, velocity: u8) -> MidiEvent
+{
+    MidiEvent::NoteOn { channel: 2, pitch, velocity }
+}
+
+fn off(pitch: u8) -> MidiEvent
+{
+    MidiEvent::NoteOff { channel: 2, pitch, velocity: 64 }
+}
+
+fn song() -> MidiSong
+{
+    MidiSong
+    {
+        t: 1,
+        time_division: TimeDivision::Metrical { pulses_per_quarter_note: 100 },
+        tracks: vec![
+            MidiTrack
+            {
+                dts: vec![0, 200, 0],
+                events: vec![
+                    MidiEvent::SetTempo { microseconds_per_quarter_note: 1000000 },
+                    MidiEvent::SetTempo { microseconds_per_quarter_note: 500000 },
+                    MidiEvent::EndOfTrack,
+                ],
+            },
+            MidiTrack
+            {
+                dts: vec![0, 0, 100, 50, 50, 100, 100, 0],
+                events: vec![on(60, 100), on(60, 90), off(60), on(64, 80), off(60), on(64, 0), on(67, 70), MidiEvent::EndOfTrack],
+            },
+        ],
+    }
+}
+
+#[test]
+fn test_notes()
+{
+    let notes = song().notes();
+    let simplified: Vec<_> = notes.iter().map(|n| (n.pitch, n.velocity, n.start_tick, n.end_tick, n.track)).collect();
+    assert_eq!(simplified, vec![
+        (60, 100, 0, 100, 1),
+        (60, 90, 0, 200, 1),
+        (64, 80, 150, 300, 1),
+        (67, 70, 400, 400, 1),
+    ]);
+
+    let n = notes.iter().find(|n| n.pitch == 64).unwrap();
+    assert_eq!(n.channel, 2);
+    assert_eq!(n.start_seconds, 1.5);
+    assert_eq!(n.duration_seconds, 1.0);
+    assert_eq!(n.end_seconds(), 2.5);
+    assert_eq!(n.duration_ticks(), 150);
+}
+
+#[test]
+fn test_notes_to_tracks()
+{
+    let notes = vec![
+        Note { channel: 0, pitch: 60, velocity: 100, start_tick: 0, end_tick: 100, track: 1, ..Default::default() },
+        Note { channel: 0, pitch: 62, velocity: 90, start_tick: 100, end_tick: 150, track: 1, ..Default::default() },
+        Note { channel: 1, pitch: 40, velocity: 80, start_tick: 50, end_tick: 60, track: 0, ..Default::default() },
+    ];
+
+    let tracks = notes_to_tracks(&notes);
+    assert_eq!(tracks.len(), 2);
+    assert_eq!(tracks[0].dts, vec![50, 10, 0]);
+    assert_eq!(tracks[1].dts, vec![0, 100, 0, 50, 0]);
+    assert_eq!(tracks[1].events[1], MidiEvent::NoteOff { channel: 0, pitch: 60, velocity: 0 });
+    assert_eq!(tracks[1].events[2], MidiEvent::NoteOn { channel: 0, pitch: 62, velocity: 90 });
+    assert_eq!(tracks[1].events[4], MidiEvent::EndOfTrack);
+
+    let song = MidiSong { t: 1, time_division: TimeDivision::default(), tracks };
+    let round_trip: Vec<_> = song.notes().iter().map(|n| (n.channel, n.pitch, n.velocity, n.start_tick, n.end_tick, n.track)).collect();
+    let mut expected: Vec<_> = notes.iter().map(|n| (n.channel, n.pitch, n.velocity, n.start_tick, n.end_tick, n.track)).collect();
+    expected.sort_by_key(|n| (n.3, n.5));
+    assert_eq!(round_trip, expected);
+}
@@ -0,0 +1,252 @@
+use std::collections::VecDeque;
+
+use crate::midi::{event::MidiEvent, midi_song::{MidiSong, MidiTrack, TimeDivision}, play::MidiPlayer};
+
+fn note(pitch: u8) -> MidiEvent
+{
+    MidiEvent::NoteOn { channel: 0, pitch, velocity: 100 }
+}
+
+fn two_track_song() -> MidiSong
+{
+    MidiSong
+    {
+        t: 1,
+        time_division: TimeDivision::Metrical { pulses_per_quarter_note: 100 },
+        tracks: vec![
+            MidiTrack
+            {
+                dts: vec![0, 100, 0],
+                events: vec![
+                    MidiEvent::SetTempo { microseconds_per_quarter_note: 1000000 },
+                    MidiEvent::SetTempo { microseconds_per_quarter_note: 250000 },
+                    MidiEvent::EndOfTrack,
+                ],
+            },
+            MidiTrack
+            {
+                dts: vec![50, 100, 100, 0],
+                events: vec![note(60), note(62), note(64), MidiEvent::EndOfTrack],
+            },
+        ],
+    }
+}
+
+#[test]
+fn test_player_uses_tempo_map_for_all_tracks()
+{
+    let song = two_track_song();
+    let mut player = MidiPlayer::new(&song);
+    let mut events = VecDeque::new();
+
+    player.update(0.49, &song, &mut events);
+    assert!(events.is_empty());
+    assert_eq!(player.next_event_seconds(&song), Some(0.5));
+
+    player.update(0.01, &song, &mut events);
+    assert_eq!(events.drain(..).collect::<Vec<_>>(), vec![note(60)]);
+
+    player.update(0.63, &song, &mut events);
+    assert_eq!(events.drain(..).collect::<Vec<_>>(), vec![MidiEvent::EndOfTrack, note(62)]);
+    assert_eq!(player.next_event_seconds(&song), Some(1.375));
+
+    player.update(1.0, &song, &mut events);
+    assert_eq!(events.drain(..).collect::<Vec<_>>(), vec![note(64), MidiEvent::EndOfTrack]);
+    assert!(player.is_finished(&song));
+}
+
+fn controller_reset() -> Vec<MidiEvent>
+{
+    (0..16).flat_map(|channel| [
+        MidiEvent::ControllerChange { channel, controller: 121, value: 0 },
+        MidiEvent::PitchBend { channel, bend_lsb: 0x00, position_msb: 0x40 },
+        MidiEvent::ChannelPressure { channel, pressure: 0 },
+    ]).collect()
+}
+
+fn chase_song() -> MidiSong
+{
+    MidiSong
+    {
+        t: 0,
+        time_division: TimeDivision::Metrical { pulses_per_quarter_note: 100 },
+        tracks: vec![
+            MidiTrack
+            {
+                dts: vec![0, 0, 0, 0, 10, 10, 80, 0, 100, 100, 0],
+                events: vec![
+                    MidiEvent::SetTempo { microseconds_per_quarter_note: 1000000 },
+                    MidiEvent::ControllerChange { channel: 1, controller: 0, value: 2 },
+                    MidiEvent::ProgramChange { channel: 1, preset: 10 },
+                    MidiEvent::ControllerChange { channel: 1, controller: 7, value: 100 },
+                    MidiEvent::PitchBend { channel: 1, bend_lsb: 0, position_msb: 70 },
+                    MidiEvent::ControllerChange { channel: 1, controller: 7, value: 50 },
+                    MidiEvent::Marker { text: "A".into() },
+                    note(60),
+                    note(62),
+                    MidiEvent::Marker { text: "B".into() },
+                    MidiEvent::EndOfTrack,
+                ],
+            },
+        ],
+    }
+}
+
+#[test]
+fn test_seek_chases_controllers()
+{
+    let song = chase_song();
+    let mut player = MidiPlayer::new(&song);
+    let mut events = VecDeque::new();
+
+    player.seek_ticks(150, &song, &mut events);
+    assert_eq!(events.drain(..48).collect::<Vec<_>>(), controller_reset());
+    assert_eq!(events.drain(..).collect::<Vec<_>>(), vec![
+        MidiEvent::ControllerChange { channel: 1, controller: 0, value: 2 },
+        MidiEvent::ProgramChange { channel: 1, preset: 10 },
+        MidiEvent::PitchBend { channel: 1, bend_lsb: 0, position_msb: 70 },
+        MidiEvent::ControllerChange { channel: 1, controller: 7, value: 50 },
+    ]);
+    assert_eq!(player.position(), 1.5);
+    assert_eq!(player.position_ticks(), 150.0);
+
+    player.update(0.5, &song, &mut events);
+    assert_eq!(events.drain(..).collect::<Vec<_>>(), vec![note(62)]);
+
+    player.seek_seconds(1.0, &song, &mut events);
+    events.clear();
+    player.update(0.0, &song, &mut events);
+    assert_eq!(events.drain(..).collect::<Vec<_>>(), vec![MidiEvent::Marker { text: "A".into() }, note(60)]);
+}
+
+#[test]
+fn test_seek_backward_resets_controllers()
+{
+    let song = chase_song();
+    let mut player = MidiPlayer::new(&song);
+    let mut events = VecDeque::new();
+
+    player.update(0.5, &song, &mut events);
+    assert!(events.contains(&MidiEvent::PitchBend { channel: 1, bend_lsb: 0, position_msb: 70 }));
+    events.clear();
+
+    player.seek_ticks(5, &song, &mut events);
+    let mut expected = controller_reset();
+    expected.extend([
+        MidiEvent::ControllerChange { channel: 1, controller: 0, value: 2 },
+        MidiEvent::ProgramChange { channel: 1, preset: 10 },
+        MidiEvent::ControllerChange { channel: 1, controller: 7, value: 100 },
+    ]);
+    assert_eq!(events.drain(..).collect::<Vec<_>>(), expected);
+}
+
+#[test]
+fn test_advance_to_seconds()
+{
+    let song = two_track_song();
+    let mut player = MidiPlayer::new(&song);
+    let mut events = VecDeque::new();
+
+    player.advance_to_seconds(0.5, false, &song, &mut events);
+    assert!(events.is_empty());
+    player.advance_to_seconds(0.5, true, &song, &mut events);
+    assert_eq!(events.drain(..).collect::<Vec<_>>(), vec![note(60)]);
+
+    let seconds = player.next_event_seconds(&song).unwrap();
+    player.advance_to_seconds(seconds, true, &song, &mut events);
+    assert_eq!(events.drain(..).collect::<Vec<_>>(), vec![MidiEvent::EndOfTrack]);
+    assert_eq!(player.position(), seconds);
+}
+
+#[test]
+fn test_pause_and_playback_rate()
+{
+    let song = chase_song();
+    let mut player = MidiPlayer::new(&song);
+    let mut events = VecDeque::new();
+
+    player.pause();
+    player.update(10.0, &song, &mut events);
+    assert!(events.is_empty());
+    assert_eq!(player.position(), 0.0);
+
+    player.resume();
+    player.set_playback_rate(2.0);
+    player.update(0.5, &song, &mut events);
+    assert_eq!(player.position(), 1.0);
+    assert_eq!(events.back(), Some(&note(60)));
+}
+
+#[test]
+fn test_loop_markers()
+{
+    let song = chase_song();
+    let mut player = MidiPlayer::new(&song);
+    let mut events = VecDeque::new();
+
+    assert!(!player.set_loop_markers(&song, "B", "A"));
+    assert!(player.set_loop_markers(&song, "A", "B"));
+    assert_eq!(player.loop_region().map(|l| (l.start_tick, l.end_tick)), Some((100, 300)));
+
+    player.update(2.5, &song, &mut events);
+    events.clear();
+
+    player.update(1.0, &song, &mut events);
+    assert!(events.contains(&MidiEvent::ProgramChange { channel: 1, preset: 10 }));
+    assert!(!events.contains(&MidiEvent::Marker { text: "B".into() }));
+    assert_eq!(events.back(), Some(&note(60)));
+    assert_eq!(player.position(), 1.5);
+
+    player.clear_loop();
+    player.update(10.0, &song, &mut events);
+    assert!(player.is_finished(&song));
+}
+
+#[test]
+fn test_seek_and_stop_release_active_notes()
+{
+    let song = chase_song();
+    let mut player = MidiPlayer::new(&song);
+    let mut events = VecDeque::new();
+
+    player.update(2.5, &song, &mut events);
+    assert_eq!(player.active_notes().len(), 2);
+    events.clear();
+
+    player.seek_seconds(0.0, &song, &mut events);
+    assert_eq!(events.drain(..2).collect::<Vec<_>>(), vec![
+        MidiEvent::NoteOff { channel: 0, pitch: 60, velocity: 0 },
+        MidiEvent::NoteOff { channel: 0, pitch: 62, velocity: 0 },
+    ]);
+    assert_eq!(events.drain(..).collect::<Vec<_>>(), controller_reset());
+    assert!(player.active_notes().is_empty());
+
+    player.update(1.5, &song, &mut events);
+    events.clear();
+    player.set_send_all_notes_off(true);
+    player.stop(&mut events);
+    assert_eq!(events.pop_front(), Some(MidiEvent::NoteOff { channel: 0, pitch: 60, velocity: 0 }));
+    assert_eq!(events.len(), 16);
+    assert!(events.iter().all(|e| matches!(e, MidiEvent::ControllerChange { controller: 123, value: 0, .. })));
+    assert!(player.is_paused());
+    assert_eq!(player.position(), 0.0);
+
+    events.clear();
+    player.stop(&mut events);
+    assert_eq!(events.len(), 16);
+}
+
+#[test]
+fn test_reset_releases_active_notes()
+{
+    let song = chase_song();
+    let mut player = MidiPlayer::new(&song);
+    let mut events = VecDeque::new();
+
+    player.update(1.0, &song, &mut events);
+    events.clear();
+
+    player.reset(&song, &mut events);
+    assert_eq!(events.drain(..).collect::<Vec<_>>(), vec![MidiEvent::NoteOff { channel: 0, pitch: 60, velocity: 0 }]);
+    assert!(player.active_notes().is_empty());
+}
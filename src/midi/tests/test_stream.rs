@@ -0,0 +1,99 @@
+use crate::midi::{event::MidiEvent, stream::{encode_midi_event, MidiStreamDecoder, MidiStreamEncoder}};
+
+fn decode_in_chunks(bytes: &[u8], chunk_size: usize) -> Vec<MidiEvent>
+{
+    let mut decoder = MidiStreamDecoder::new();
+    let mut events = Vec::new();
+    for chunk in bytes.chunks(chunk_size)
+    {
+        decoder.push(chunk, &mut |e| events.push(e));
+    }
+    events
+}
+
+#[test]
+fn test_decode_running_status_and_real_time()
+{
+    let bytes = [
+        0x3C, 0x40,
+        0x91, 0x3C, 0xF8, 0x40,
+        0x3E, 0x40,
+        0xC1, 0x05,
+        0x07,
+        0xF0, 0x7E, 0x7F, 0xFE, 0x09, 0x01, 0xF7,
+        0x3C, 0x00,
+        0xF2, 0x10, 0x20,
+        0xB0, 0x07, 0x64,
+    ];
+
+    let expected = vec![
+        MidiEvent::TimingTick,
+        MidiEvent::NoteOn { channel: 1, pitch: 0x3C, velocity: 0x40 },
+        MidiEvent::NoteOn { channel: 1, pitch: 0x3E, velocity: 0x40 },
+        MidiEvent::ProgramChange { channel: 1, preset: 5 },
+        MidiEvent::ProgramChange { channel: 1, preset: 7 },
+        MidiEvent::ActiveSensing,
+        MidiEvent::SysEx { data: vec![0x7E, 0x7F, 0x09, 0x01, 0xF7] },
+        MidiEvent::SongPosition { position_lsb: 0x10, position_msb: 0x20 },
+        MidiEvent::ControllerChange { channel: 0, controller: 7, value: 0x64 },
+    ];
+
+    for chunk_size in 1..bytes.len()
+    {
+        assert_eq!(decode_in_chunks(&bytes, chunk_size), expected);
+    }
+}
+
+#[test]
+fn test_decode_unterminated_sysex()
+{
+    let bytes = [0xF0, 0x43, 0x12, 0x80, 0x3C, 0x00];
+    assert_eq!(decode_in_chunks(&bytes, 2), vec![
+        MidiEvent::SysEx { data: vec![0x43, 0x12] },
+        MidiEvent::NoteOff { channel: 0, pitch: 0x3C, velocity: 0 },
+    ]);
+}
+
+#[test]
+fn test_encode()
+{
+    assert_eq!(encode_midi_event(&MidiEvent::NoteOn { channel: 3, pitch: 60, velocity: 1 }), vec![0x93, 60, 1]);
+    assert_eq!(encode_midi_event(&MidiEvent::SysEx { data: vec![1, 2, 0xF7] }), vec![0xF0, 1, 2, 0xF7]);
+    assert_eq!(encode_midi_event(&MidiEvent::StopSong), vec![0xFC]);
+    assert_eq!(encode_midi_event(&MidiEvent::EndOfTrack), vec![]);
+
+    let mut encoder = MidiStreamEncoder::new(true);
+    let mut bytes = Vec::new();
+    assert!(encoder.encode(&MidiEvent::NoteOn { channel: 0, pitch: 60, velocity: 1 }, &mut bytes));
+    assert!(encoder.encode(&MidiEvent::TimingTick, &mut bytes));
+    assert!(encoder.encode(&MidiEvent::NoteOn { channel: 0, pitch: 62, velocity: 1 }, &mut bytes));
+    assert!(!encoder.encode(&MidiEvent::Marker { text: "x".into() }, &mut bytes));
+    assert!(encoder.encode(&MidiEvent::TuneRequest, &mut bytes));
+    assert!(encoder.encode(&MidiEvent::NoteOn { channel: 0, pitch: 64, velocity: 1 }, &mut bytes));
+    assert_eq!(bytes, vec![0x90, 60, 1, 0xF8, 62, 1, 0xF6, 0x90, 64, 1]);
+}
+
+#[test]
+fn test_encode_decode_round_trip()
+{
+    let events = vec![
+        MidiEvent::NoteOn { channel: 0, pitch: 60, velocity: 100 },
+        MidiEvent::NoteOn { channel: 0, pitch: 64, velocity: 100 },
+        MidiEvent::PolyPressure { channel: 0, pitch: 64, pressure: 20 },
+        MidiEvent::ChannelPressure { channel: 0, pressure: 30 },
+        MidiEvent::PitchBend { channel: 15, bend_lsb: 0, position_msb: 64 },
+        MidiEvent::SysEx { data: vec![0x41, 0x10, 0xF7] },
+        MidiEvent::SongSelect { song_number: 3 },
+        MidiEvent::StartSong,
+        MidiEvent::ContinueSong,
+        MidiEvent::NoteOff { channel: 0, pitch: 60, velocity: 0 },
+    ];
+
+    let mut encoder = MidiStreamEncoder::new(true);
+    let mut bytes = Vec::new();
+    for event in &events
+    {
+        encoder.encode(event, &mut bytes);
+    }
+    assert_eq!(decode_in_chunks(&bytes, 3), events);
+}
@@ -0,0 +1,71 @@
+use crate::midi::{event::MidiEvent, midi_song::{MidiSong, MidiTrack, TimeDivision}, tempo_map::{BarBeat, TempoMap}};
+
+fn tempo_song() -> MidiSong
+{
+    MidiSong
+    {
+        t: 1,
+        time_division: TimeDivision::Metrical { pulses_per_quarter_note: 100 },
+        tracks: vec![
+            MidiTrack
+            {
+                dts: vec![0, 0, 400, 0, 600],
+                events: vec![
+                    MidiEvent::SetTempo { microseconds_per_quarter_note: 1000000 },
+                    MidiEvent::TimeSignature { nn: 4, dd: 2, cc: 24, bb: 8 },
+                    MidiEvent::SetTempo { microseconds_per_quarter_note: 500000 },
+                    MidiEvent::TimeSignature { nn: 3, dd: 3, cc: 24, bb: 8 },
+                    MidiEvent::EndOfTrack,
+                ],
+            },
+        ],
+    }
+}
+
+#[test]
+fn test_ticks_to_seconds()
+{
+    let map = TempoMap::new(&tempo_song());
+    assert_eq!(map.ticks_to_seconds(0), 0.0);
+    assert_eq!(map.ticks_to_seconds(100), 1.0);
+    assert_eq!(map.ticks_to_seconds(400), 4.0);
+    assert_eq!(map.ticks_to_seconds(500), 4.5);
+    assert_eq!(map.tempo_at(399), 1000000);
+    assert_eq!(map.bpm_at(400), 120.0);
+}
+
+#[test]
+fn test_seconds_to_ticks()
+{
+    let map = TempoMap::new(&tempo_song());
+    for tick in [0, 50, 399, 400, 401, 1000]
+    {
+        let seconds = map.ticks_to_seconds(tick);
+        assert!((map.seconds_to_ticks(seconds) - tick as f64).abs() < 1e-9);
+    }
+}
+
+#[test]
+fn test_default_tempo()
+{
+    let song = MidiSong { t: 0, time_division: TimeDivision::Metrical { pulses_per_quarter_note: 480 }, tracks: Vec::new() };
+    let map = TempoMap::new(&song);
+    assert_eq!(map.ticks_to_seconds(960), 1.0);
+}
+
+#[test]
+fn test_bar_beat()
+{
+    let map = TempoMap::new(&tempo_song());
+    assert_eq!(map.bar_beat(0), BarBeat { bar: 0, beat: 0, ticks: 0 });
+    assert_eq!(map.bar_beat(150), BarBeat { bar: 0, beat: 1, ticks: 50 });
+    assert_eq!(map.bar_beat(399), BarBeat { bar: 0, beat: 3, ticks: 99 });
+    assert_eq!(map.bar_beat(400), BarBeat { bar: 1, beat: 0, ticks: 0 });
+    assert_eq!(map.bar_beat(550), BarBeat { bar: 2, beat: 0, ticks: 0 });
+    assert_eq!(map.bar_beat(600), BarBeat { bar: 2, beat: 1, ticks: 0 });
+
+    for tick in [0, 150, 399, 400, 470, 550, 1000]
+    {
+        assert_eq!(map.bar_beat_to_ticks(map.bar_beat(tick)), tick);
+    }
+}
@@ -0,0 +1,128 @@
+use std::collections::VecDeque;
+
+use crate::midi::{event::MidiEvent, ump::*};
+
+#[test]
+fn test_scaling()
+{
+    assert_eq!(scale_up(0, 7, 16), 0);
+    assert_eq!(scale_up(64, 7, 16), 0x8000);
+    assert_eq!(scale_up(127, 7, 16), 0xFFFF);
+    assert_eq!(scale_up(127, 7, 32), 0xFFFF_FFFF);
+    assert_eq!(scale_up(0x2000, 14, 32), PITCH_BEND_CENTER);
+    assert_eq!(scale_up(0x3FFF, 14, 32), 0xFFFF_FFFF);
+
+    for value in 0..128
+    {
+        assert_eq!(scale_down(scale_up(value, 7, 16), 16, 7), value);
+        assert_eq!(scale_down(scale_up(value, 7, 32), 32, 7), value);
+    }
+    for value in 0..0x4000
+    {
+        assert_eq!(scale_down(scale_up(value, 14, 32), 32, 14), value);
+    }
+}
+
+#[test]
+fn test_packet_words()
+{
+    let note_on = UmpMessage::Midi2ChannelVoice
+    {
+        group: 1,
+        message: Midi2ChannelVoice::NoteOn { channel: 2, pitch: 60, velocity: 0xFFFF, attribute_type: 3, attribute: 0x1234 },
+    };
+    let words = encode_ump(std::slice::from_ref(&note_on));
+    assert_eq!(words, vec![0x4192_3C03, 0xFFFF_1234]);
+    assert_eq!(UmpMessage::decode(&words), Some((note_on, 2)));
+
+    let midi1 = UmpMessage::Midi1ChannelVoice { group: 0, event: MidiEvent::NoteOn { channel: 0, pitch: 60, velocity: 100 } };
+    assert_eq!(encode_ump(std::slice::from_ref(&midi1)), vec![0x2090_3C64]);
+    assert_eq!(decode_ump(&[0x2090_3C64]), vec![midi1]);
+
+    assert_eq!(encode_ump(&[UmpMessage::System { group: 0, event: MidiEvent::TimingTick }]), vec![0x10F8_0000]);
+    assert_eq!(ump_packet_len(0x5000_0000), 4);
+    assert_eq!(ump_packet_len(0xB000_0000), 3);
+
+    // truncated packets stop decoding, unknown message types are kept verbatim
+    assert_eq!(UmpMessage::decode(&[0x4090_3C00]), None);
+    assert_eq!(decode_ump(&[0xD000_0000, 1, 2, 3]), vec![UmpMessage::Unknown { words: vec![0xD000_0000, 1, 2, 3] }]);
+}
+
+#[test]
+fn test_midi2_messages_round_trip()
+{
+    let messages = [
+        Midi2ChannelVoice::PerNotePitchBend { channel: 3, pitch: 64, bend: 0x9000_0000 },
+        Midi2ChannelVoice::RegisteredController { channel: 0, bank: 0, index: 0, data: 0x1800_0000 },
+        Midi2ChannelVoice::RelativeAssignableController { channel: 1, bank: 2, index: 3, data: -5 },
+        Midi2ChannelVoice::ProgramChange { channel: 4, program: 10, bank: Some((1, 2)) },
+        Midi2ChannelVoice::ProgramChange { channel: 4, program: 11, bank: None },
+        Midi2ChannelVoice::PerNoteManagement { channel: 5, pitch: 70, detach: true, reset: false },
+        Midi2ChannelVoice::ControlChange { channel: 15, index: 74, data: 0x7FFF_FFFF },
+    ];
+
+    for message in messages
+    {
+        let ump = UmpMessage::Midi2ChannelVoice { group: 7, message };
+        assert_eq!(decode_ump(&encode_ump(std::slice::from_ref(&ump))), vec![ump]);
+    }
+
+    assert_eq!(Midi2ChannelVoice::PerNotePitchBend { channel: 3, pitch: 64, bend: 0 }.to_midi1(), vec![]);
+    assert_eq!(Midi2ChannelVoice::RegisteredController { channel: 0, bank: 0, index: 0, data: 0x1800_0000 }.to_midi1(), vec![
+        MidiEvent::ControllerChange { channel: 0, controller: 101, value: 0 },
+        MidiEvent::ControllerChange { channel: 0, controller: 100, value: 0 },
+        MidiEvent::ControllerChange { channel: 0, controller: 6, value: 12 },
+        MidiEvent::ControllerChange { channel: 0, controller: 38, value: 0 },
+    ]);
+    assert_eq!(Midi2ChannelVoice::NoteOn { channel: 0, pitch: 60, velocity: 100, attribute_type: 0, attribute: 0 }.to_midi1(), vec![
+        MidiEvent::NoteOn { channel: 0, pitch: 60, velocity: 1 },
+    ]);
+}
+
+fn events() -> Vec<MidiEvent>
+{
+    vec![
+        MidiEvent::NoteOn { channel: 0, pitch: 60, velocity: 127 },
+        MidiEvent::NoteOn { channel: 1, pitch: 61, velocity: 1 },
+        MidiEvent::NoteOff { channel: 2, pitch: 62, velocity: 0 },
+        MidiEvent::PolyPressure { channel: 3, pitch: 63, pressure: 65 },
+        MidiEvent::ControllerChange { channel: 4, controller: 7, value: 100 },
+        MidiEvent::ProgramChange { channel: 5, preset: 42 },
+        MidiEvent::ChannelPressure { channel: 6, pressure: 127 },
+        MidiEvent::PitchBend { channel: 7, bend_lsb: 0x7F, position_msb: 0x7F },
+        MidiEvent::PitchBend { channel: 8, bend_lsb: 0x00, position_msb: 0x40 },
+        MidiEvent::SysEx { data: vec![0x7E, 0x7F, 0x09, 0x01, 0x02, 0x03, 0x04, 0x05, 0x06, 0xF7] },
+        MidiEvent::SysEx { data: vec![0x01, 0xF7] },
+        MidiEvent::SongPosition { position_lsb: 1, position_msb: 2 },
+        MidiEvent::TimingTick,
+        MidiEvent::StopSong,
+    ]
+}
+
+#[test]
+fn test_midi_event_round_trip()
+{
+    for protocol in [UmpProtocol::Midi1, UmpProtocol::Midi2]
+    {
+        let encoder = UmpEncoder::new(3, protocol);
+        let mut messages = VecDeque::new();
+        for event in events()
+        {
+            assert!(encoder.encode(&event, &mut messages));
+        }
+        assert!(!encoder.encode(&MidiEvent::EndOfTrack, &mut messages));
+
+        let words = encode_ump(&Vec::from(messages));
+        let mut decoded = VecDeque::new();
+        UmpDecoder::new().push_words(&words, &mut decoded);
+        assert_eq!(Vec::from(decoded), events());
+    }
+}
+
+#[test]
+fn test_note_on_with_zero_velocity_becomes_note_off()
+{
+    let message = Midi2ChannelVoice::from_midi1(&MidiEvent::NoteOn { channel: 0, pitch: 60, velocity: 0 }).unwrap();
+    assert_eq!(message, Midi2ChannelVoice::NoteOff { channel: 0, pitch: 60, velocity: 0x8000, attribute_type: 0, attribute: 0 });
+    assert_eq!(message.to_midi1(), vec![MidiEvent::NoteOff { channel: 0, pitch: 60, velocity: 64 }]);
+}
@@ -0,0 +1,133 @@
+use std::{borrow::Cow, fmt, hash::{Hash, Hasher}};
+
+use serde::{Serialize, Deserialize};
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, Default, Serialize, Deserialize)]
+#[repr(C)]
+pub enum TextEncoding
+{
+    Utf8Lossy,
+    #[default]
+    Latin1,
+}
+
+// meta event text has no declared encoding, so the bytes are kept as they were in the file
+// and only decoded on request, valid utf-8 wins and anything else goes through the fallback
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[repr(C)]
+pub struct MidiText
+{
+    pub bytes: Vec<u8>,
+    pub fallback: TextEncoding,
+}
+
+impl MidiText
+{
+    pub fn new(bytes: Vec<u8>, fallback: TextEncoding) -> MidiText
+    {
+        MidiText { bytes, fallback }
+    }
+
+    pub fn as_bytes(&self) -> &[u8]
+    {
+        &self.bytes
+    }
+
+    pub fn len(&self) -> usize
+    {
+        self.bytes.len()
+    }
+
+    pub fn is_empty(&self) -> bool
+    {
+        self.bytes.is_empty()
+    }
+
+    pub fn as_utf8(&self) -> Option<&str>
+    {
+        std::str::from_utf8(&self.bytes).ok()
+    }
+
+    pub fn to_utf8_lossy(&self) -> Cow<'_, str>
+    {
+        String::from_utf8_lossy(&self.bytes)
+    }
+
+    pub fn to_latin1(&self) -> String
+    {
+        self.bytes.iter().map(|&b| b as char).collect()
+    }
+
+    pub fn decode_with(&self, fallback: TextEncoding) -> Cow<'_, str>
+    {
+        match (self.as_utf8(), fallback)
+        {
+            (Some(text), _) => Cow::Borrowed(text),
+            (None, TextEncoding::Utf8Lossy) => self.to_utf8_lossy(),
+            (None, TextEncoding::Latin1) => Cow::Owned(self.to_latin1()),
+        }
+    }
+
+    pub fn decode(&self) -> Cow<'_, str>
+    {
+        self.decode_with(self.fallback)
+    }
+}
+
+impl PartialEq for MidiText
+{
+    fn eq(&self, other: &MidiText) -> bool
+    {
+        self.bytes == other.bytes
+    }
+}
+
+impl Eq for MidiText {}
+
+impl Hash for MidiText
+{
+    fn hash<H: Hasher>(&self, state: &mut H)
+    {
+        self.bytes.hash(state);
+    }
+}
+
+impl PartialEq<str> for MidiText
+{
+    fn eq(&self, other: &str) -> bool
+    {
+        self.bytes == other.as_bytes()
+    }
+}
+
+impl PartialEq<&str> for MidiText
+{
+    fn eq(&self, other: &&str) -> bool
+    {
+        self.bytes == other.as_bytes()
+    }
+}
+
+impl From<&str> for MidiText
+{
+    fn from(text: &str) -> MidiText
+    {
+        MidiText { bytes: text.as_bytes().to_owned(), ..Default::default() }
+    }
+}
+
+impl From<String> for MidiText
+{
+    fn from(text: String) -> MidiText
+    {
+        MidiText { bytes: text.into_bytes(), ..Default::default() }
+    }
+}
+
+impl fmt::Display for MidiText
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result
+    {
+        f.write_str(&self.decode())
+    }
+}
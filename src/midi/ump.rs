@@ -0,0 +1,526 @@
+use serde::{Serialize, Deserialize};
+
+use crate::pipe::Pipe;
+
+use super::{event::MidiEvent, midi_parser::read_midi_event, stream::encode_midi_event};
+
+pub const SYSEX_COMPLETE: u8 = 0x0;
+pub const SYSEX_START: u8 = 0x1;
+pub const SYSEX_CONTINUE: u8 = 0x2;
+pub const SYSEX_END: u8 = 0x3;
+
+pub const PITCH_BEND_CENTER: u32 = 0x8000_0000;
+
+// Upscaling follows the MIDI 2.0 min-center-max rule: values up to the center are shifted left,
+// values above it repeat their lower bits into the new low bits so the maximum becomes the new maximum.
+// Downscaling is a plain right shift, so any upscaled value always downscales back to itself.
+pub fn scale_up(value: u32, src_bits: u32, dst_bits: u32) -> u32
+{
+    let scale_bits = dst_bits - src_bits;
+    let shifted = (value as u64) << scale_bits;
+    let center = 1u32 << (src_bits - 1);
+    if value <= center
+    {
+        return shifted as u32;
+    }
+
+    let repeat_bits = src_bits - 1;
+    let mut repeat = (value & ((1u32 << repeat_bits) - 1)) as u64;
+    if scale_bits > repeat_bits
+    {
+        repeat <<= scale_bits - repeat_bits;
+    }
+    else
+    {
+        repeat >>= repeat_bits - scale_bits;
+    }
+
+    let mut result = shifted;
+    while repeat != 0
+    {
+        result |= repeat;
+        repeat >>= repeat_bits;
+    }
+    result as u32
+}
+
+pub fn scale_down(value: u32, src_bits: u32, dst_bits: u32) -> u32
+{
+    value >> (src_bits - dst_bits)
+}
+
+pub fn ump_packet_len(word: u32) -> usize
+{
+    match word >> 28
+    {
+        0x0 | 0x1 | 0x2 | 0x6 | 0x7 => 1,
+        0x3 | 0x4 | 0x8 | 0x9 | 0xA => 2,
+        0xB | 0xC => 3,
+        _ => 4,
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Serialize, Deserialize)]
+#[repr(C)]
+pub enum Midi2ChannelVoice
+{
+    NoteOff { channel: u8, pitch: u8, velocity: u16, attribute_type: u8, attribute: u16, },
+    NoteOn { channel: u8, pitch: u8, velocity: u16, attribute_type: u8, attribute: u16, },
+    PolyPressure { channel: u8, pitch: u8, pressure: u32, },
+    RegisteredPerNoteController { channel: u8, pitch: u8, index: u8, data: u32, },
+    AssignablePerNoteController { channel: u8, pitch: u8, index: u8, data: u32, },
+    PerNoteManagement { channel: u8, pitch: u8, detach: bool, reset: bool, },
+    ControlChange { channel: u8, index: u8, data: u32, },
+    RegisteredController { channel: u8, bank: u8, index: u8, data: u32, },
+    AssignableController { channel: u8, bank: u8, index: u8, data: u32, },
+    RelativeRegisteredController { channel: u8, bank: u8, index: u8, data: i32, },
+    RelativeAssignableController { channel: u8, bank: u8, index: u8, data: i32, },
+    ProgramChange { channel: u8, program: u8, bank: Option<(u8, u8)>, },
+    ChannelPressure { channel: u8, pressure: u32, },
+    PitchBend { channel: u8, bend: u32, },
+    PerNotePitchBend { channel: u8, pitch: u8, bend: u32, },
+}
+
+impl Midi2ChannelVoice
+{
+    pub fn channel(&self) -> u8
+    {
+        match *self
+        {
+            Midi2ChannelVoice::NoteOff { channel, .. }
+            | Midi2ChannelVoice::NoteOn { channel, .. }
+            | Midi2ChannelVoice::PolyPressure { channel, .. }
+            | Midi2ChannelVoice::RegisteredPerNoteController { channel, .. }
+            | Midi2ChannelVoice::AssignablePerNoteController { channel, .. }
+            | Midi2ChannelVoice::PerNoteManagement { channel, .. }
+            | Midi2ChannelVoice::ControlChange { channel, .. }
+            | Midi2ChannelVoice::RegisteredController { channel, .. }
+            | Midi2ChannelVoice::AssignableController { channel, .. }
+            | Midi2ChannelVoice::RelativeRegisteredController { channel, .. }
+            | Midi2ChannelVoice::RelativeAssignableController { channel, .. }
+            | Midi2ChannelVoice::ProgramChange { channel, .. }
+            | Midi2ChannelVoice::ChannelPressure { channel, .. }
+            | Midi2ChannelVoice::PitchBend { channel, .. }
+            | Midi2ChannelVoice::PerNotePitchBend { channel, .. } => channel,
+        }
+    }
+
+    // a MIDI 1.0 note on with velocity 0 is a note off, so it becomes a MIDI 2.0 note off with center velocity
+    pub fn from_midi1(event: &MidiEvent) -> Option<Midi2ChannelVoice>
+    {
+        let up7 = |value: u8| scale_up(value as u32, 7, 32);
+
+        match *event
+        {
+            MidiEvent::NoteOn { channel, pitch, velocity: 0 } =>
+                Some(Midi2ChannelVoice::NoteOff { channel, pitch, velocity: scale_up(64, 7, 16) as u16, attribute_type: 0, attribute: 0 }),
+            MidiEvent::NoteOn { channel, pitch, velocity } =>
+                Some(Midi2ChannelVoice::NoteOn { channel, pitch, velocity: scale_up(velocity as u32, 7, 16) as u16, attribute_type: 0, attribute: 0 }),
+            MidiEvent::NoteOff { channel, pitch, velocity } =>
+                Some(Midi2ChannelVoice::NoteOff { channel, pitch, velocity: scale_up(velocity as u32, 7, 16) as u16, attribute_type: 0, attribute: 0 }),
+            MidiEvent::PolyPressure { channel, pitch, pressure } =>
+                Some(Midi2ChannelVoice::PolyPressure { channel, pitch, pressure: up7(pressure) }),
+            MidiEvent::ControllerChange { channel, controller, value } =>
+                Some(Midi2ChannelVoice::ControlChange { channel, index: controller, data: up7(value) }),
+            MidiEvent::ProgramChange { channel, preset } =>
+                Some(Midi2ChannelVoice::ProgramChange { channel, program: preset, bank: None }),
+            MidiEvent::ChannelPressure { channel, pressure } =>
+                Some(Midi2ChannelVoice::ChannelPressure { channel, pressure: up7(pressure) }),
+            MidiEvent::PitchBend { channel, bend_lsb, position_msb } =>
+            {
+                let bend = (bend_lsb as u32 & 0x7F) | (position_msb as u32 & 0x7F) << 7;
+                Some(Midi2ChannelVoice::PitchBend { channel, bend: scale_up(bend, 14, 32) })
+            }
+            _ => None,
+        }
+    }
+
+    // registered and assignable controllers expand to their RPN/NRPN controller sequence with a 14-bit value,
+    // per-note and relative messages have no MIDI 1.0 equivalent and produce nothing
+    pub fn to_midi1(&self) -> Vec<MidiEvent>
+    {
+        let down7 = |value: u32| scale_down(value, 32, 7) as u8;
+        let cc = |channel: u8, controller: u8, value: u8| MidiEvent::ControllerChange { channel, controller, value };
+
+        match *self
+        {
+            Midi2ChannelVoice::NoteOn { channel, pitch, velocity, .. } =>
+                vec![MidiEvent::NoteOn { channel, pitch, velocity: (scale_down(velocity as u32, 16, 7) as u8).max(1) }],
+            Midi2ChannelVoice::NoteOff { channel, pitch, velocity, .. } =>
+                vec![MidiEvent::NoteOff { channel, pitch, velocity: scale_down(velocity as u32, 16, 7) as u8 }],
+            Midi2ChannelVoice::PolyPressure { channel, pitch, pressure } =>
+                vec![MidiEvent::PolyPressure { channel, pitch, pressure: down7(pressure) }],
+            Midi2ChannelVoice::ControlChange { channel, index, data } =>
+                vec![cc(channel, index & 0x7F, down7(data))],
+            Midi2ChannelVoice::RegisteredController { channel, bank, index, data } =>
+                vec![cc(channel, 101, bank & 0x7F), cc(channel, 100, index & 0x7F), cc(channel, 6, (data >> 25) as u8), cc(channel, 38, (data >> 18) as u8 & 0x7F)],
+            Midi2ChannelVoice::AssignableController { channel, bank, index, data } =>
+                vec![cc(channel, 99, bank & 0x7F), cc(channel, 98, index & 0x7F), cc(channel, 6, (data >> 25) as u8), cc(channel, 38, (data >> 18) as u8 & 0x7F)],
+            Midi2ChannelVoice::ProgramChange { channel, program, bank } =>
+            {
+                let mut events = Vec::new();
+                if let Some((msb, lsb)) = bank
+                {
+                    events.push(cc(channel, 0, msb & 0x7F));
+                    events.push(cc(channel, 32, lsb & 0x7F));
+                }
+                events.push(MidiEvent::ProgramChange { channel, preset: program & 0x7F });
+                events
+            }
+            Midi2ChannelVoice::ChannelPressure { channel, pressure } =>
+                vec![MidiEvent::ChannelPressure { channel, pressure: down7(pressure) }],
+            Midi2ChannelVoice::PitchBend { channel, bend } =>
+            {
+                let bend = scale_down(bend, 32, 14);
+                vec![MidiEvent::PitchBend { channel, bend_lsb: (bend & 0x7F) as u8, position_msb: (bend >> 7) as u8 }]
+            }
+            _ => Vec::new(),
+        }
+    }
+
+    fn to_words(self) -> [u32; 2]
+    {
+        let (status, channel, index1, index2, data) = match self
+        {
+            Midi2ChannelVoice::NoteOff { channel, pitch, velocity, attribute_type, attribute } =>
+                (0x8, channel, pitch, attribute_type, (velocity as u32) << 16 | attribute as u32),
+            Midi2ChannelVoice::NoteOn { channel, pitch, velocity, attribute_type, attribute } =>
+                (0x9, channel, pitch, attribute_type, (velocity as u32) << 16 | attribute as u32),
+            Midi2ChannelVoice::PolyPressure { channel, pitch, pressure } => (0xA, channel, pitch, 0, pressure),
+            Midi2ChannelVoice::RegisteredPerNoteController { channel, pitch, index, data } => (0x0, channel, pitch, index, data),
+            Midi2ChannelVoice::AssignablePerNoteController { channel, pitch, index, data } => (0x1, channel, pitch, index, data),
+            Midi2ChannelVoice::PerNoteManagement { channel, pitch, detach, reset } =>
+                (0xF, channel, pitch, (detach as u8) << 1 | reset as u8, 0),
+            Midi2ChannelVoice::ControlChange { channel, index, data } => (0xB, channel, index, 0, data),
+            Midi2ChannelVoice::RegisteredController { channel, bank, index, data } => (0x2, channel, bank, index, data),
+            Midi2ChannelVoice::AssignableController { channel, bank, index, data } => (0x3, channel, bank, index, data),
+            Midi2ChannelVoice::RelativeRegisteredController { channel, bank, index, data } => (0x4, channel, bank, index, data as u32),
+            Midi2ChannelVoice::RelativeAssignableController { channel, bank, index, data } => (0x5, channel, bank, index, data as u32),
+            Midi2ChannelVoice::ProgramChange { channel, program, bank } =>
+            {
+                let (msb, lsb) = bank.unwrap_or((0, 0));
+                (0xC, channel, 0, bank.is_some() as u8, (program as u32) << 24 | (msb as u32) << 8 | lsb as u32)
+            }
+            Midi2ChannelVoice::ChannelPressure { channel, pressure } => (0xD, channel, 0, 0, pressure),
+            Midi2ChannelVoice::PitchBend { channel, bend } => (0xE, channel, 0, 0, bend),
+            Midi2ChannelVoice::PerNotePitchBend { channel, pitch, bend } => (0x6, channel, pitch, 0, bend),
+        };
+
+        [(status as u32) << 20 | (channel as u32 & 0xF) << 16 | (index1 as u32) << 8 | index2 as u32, data]
+    }
+
+    fn from_words(word: u32, data: u32) -> Option<Midi2ChannelVoice>
+    {
+        let channel = (word >> 16) as u8 & 0xF;
+        let index1 = (word >> 8) as u8;
+        let index2 = word as u8;
+
+        let message = match (word >> 20) & 0xF
+        {
+            0x0 => Midi2ChannelVoice::RegisteredPerNoteController { channel, pitch: index1, index: index2, data },
+            0x1 => Midi2ChannelVoice::AssignablePerNoteController { channel, pitch: index1, index: index2, data },
+            0x2 => Midi2ChannelVoice::RegisteredController { channel, bank: index1, index: index2, data },
+            0x3 => Midi2ChannelVoice::AssignableController { channel, bank: index1, index: index2, data },
+            0x4 => Midi2ChannelVoice::RelativeRegisteredController { channel, bank: index1, index: index2, data: data as i32 },
+            0x5 => Midi2ChannelVoice::RelativeAssignableController { channel, bank: index1, index: index2, data: data as i32 },
+            0x6 => Midi2ChannelVoice::PerNotePitchBend { channel, pitch: index1, bend: data },
+            0x8 => Midi2ChannelVoice::NoteOff { channel, pitch: index1, velocity: (data >> 16) as u16, attribute_type: index2, attribute: data as u16 },
+            0x9 => Midi2ChannelVoice::NoteOn { channel, pitch: index1, velocity: (data >> 16) as u16, attribute_type: index2, attribute: data as u16 },
+            0xA => Midi2ChannelVoice::PolyPressure { channel, pitch: index1, pressure: data },
+            0xB => Midi2ChannelVoice::ControlChange { channel, index: index1, data },
+            0xC =>
+            {
+                let bank = if index2 & 1 != 0 { Some(((data >> 8) as u8 & 0x7F, data as u8 & 0x7F)) } else { None };
+                Midi2ChannelVoice::ProgramChange { channel, program: (data >> 24) as u8, bank }
+            }
+            0xD => Midi2ChannelVoice::ChannelPressure { channel, pressure: data },
+            0xE => Midi2ChannelVoice::PitchBend { channel, bend: data },
+            0xF => Midi2ChannelVoice::PerNoteManagement { channel, pitch: index1, detach: index2 & 2 != 0, reset: index2 & 1 != 0 },
+            _ => return None,
+        };
+        Some(message)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[repr(C)]
+pub enum UmpMessage
+{
+    Utility { status: u8, data: u32, },
+    System { group: u8, event: MidiEvent, },
+    Midi1ChannelVoice { group: u8, event: MidiEvent, },
+    Data64 { group: u8, status: u8, data: Vec<u8>, },
+    Midi2ChannelVoice { group: u8, message: Midi2ChannelVoice, },
+    Data128 { group: u8, status: u8, stream_id: u8, data: Vec<u8>, },
+    Unknown { words: Vec<u32>, },
+}
+
+fn pack_bytes(message_type: u32, group: u8, event: &MidiEvent) -> Option<u32>
+{
+    let bytes = encode_midi_event(event);
+    if bytes.is_empty() || bytes.len() > 3
+    {
+        return None;
+    }
+
+    let byte = |i: usize| bytes.get(i).copied().unwrap_or(0) as u32;
+    Some(message_type << 28 | (group as u32 & 0xF) << 24 | byte(0) << 16 | byte(1) << 8 | byte(2))
+}
+
+fn unpack_bytes(word: u32) -> Option<MidiEvent>
+{
+    let bytes = [(word >> 16) as u8, (word >> 8) as u8 & 0x7F, word as u8 & 0x7F];
+    read_midi_event(&bytes, &mut 0, &mut 0).ok().filter(|event| !event.is_meta())
+}
+
+impl UmpMessage
+{
+    pub fn group(&self) -> Option<u8>
+    {
+        match *self
+        {
+            UmpMessage::System { group, .. }
+            | UmpMessage::Midi1ChannelVoice { group, .. }
+            | UmpMessage::Data64 { group, .. }
+            | UmpMessage::Midi2ChannelVoice { group, .. }
+            | UmpMessage::Data128 { group, .. } => Some(group),
+            UmpMessage::Utility { .. } | UmpMessage::Unknown { .. } => None,
+        }
+    }
+
+    pub fn encode(&self, words: &mut Vec<u32>)
+    {
+        match self
+        {
+            UmpMessage::Utility { status, data } =>
+            {
+                words.push((*status as u32 & 0xF) << 20 | data & 0xF_FFFF);
+            }
+            UmpMessage::System { group, event } => words.extend(pack_bytes(0x1, *group, event)),
+            UmpMessage::Midi1ChannelVoice { group, event } => words.extend(pack_bytes(0x2, *group, event)),
+            UmpMessage::Data64 { group, status, data } =>
+            {
+                let len = data.len().min(6);
+                let mut bytes = [0u32; 6];
+                for (b, &d) in bytes.iter_mut().zip(&data[..len])
+                {
+                    *b = d as u32 & 0x7F;
+                }
+                words.push(0x3 << 28 | (*group as u32 & 0xF) << 24 | (*status as u32 & 0xF) << 20 | (len as u32) << 16 | bytes[0] << 8 | bytes[1]);
+                words.push(bytes[2] << 24 | bytes[3] << 16 | bytes[4] << 8 | bytes[5]);
+            }
+            UmpMessage::Midi2ChannelVoice { group, message } =>
+            {
+                let [word, data] = message.to_words();
+                words.push(0x4 << 28 | (*group as u32 & 0xF) << 24 | word);
+                words.push(data);
+            }
+            UmpMessage::Data128 { group, status, stream_id, data } =>
+            {
+                let len = data.len().min(13);
+                let mut bytes = [0u32; 13];
+                for (b, &d) in bytes.iter_mut().zip(&data[..len])
+                {
+                    *b = d as u32;
+                }
+                words.push(0x5 << 28 | (*group as u32 & 0xF) << 24 | (*status as u32 & 0xF) << 20 | (len as u32 + 1) << 16 | (*stream_id as u32) << 8 | bytes[0]);
+                for chunk in bytes[1..].chunks(4)
+                {
+                    words.push(chunk[0] << 24 | chunk[1] << 16 | chunk[2] << 8 | chunk[3]);
+                }
+            }
+            UmpMessage::Unknown { words: unknown } => words.extend_from_slice(unknown),
+        }
+    }
+
+    pub fn decode(words: &[u32]) -> Option<(UmpMessage, usize)>
+    {
+        let word = *words.first()?;
+        let len = ump_packet_len(word);
+        let words = words.get(..len)?;
+        let group = (word >> 24) as u8 & 0xF;
+        let status = (word >> 20) as u8 & 0xF;
+
+        let message = match word >> 28
+        {
+            0x0 => Some(UmpMessage::Utility { status, data: word & 0xF_FFFF }),
+            0x1 => unpack_bytes(word).map(|event| UmpMessage::System { group, event }),
+            0x2 => unpack_bytes(word).map(|event| UmpMessage::Midi1ChannelVoice { group, event }),
+            0x3 =>
+            {
+                let bytes = [(word >> 8) as u8, word as u8, (words[1] >> 24) as u8, (words[1] >> 16) as u8, (words[1] >> 8) as u8, words[1] as u8];
+                let count = ((word >> 16) as usize & 0xF).min(6);
+                Some(UmpMessage::Data64 { group, status, data: bytes[..count].to_vec() })
+            }
+            0x4 => Midi2ChannelVoice::from_words(word & 0xFF_FFFF, words[1]).map(|message| UmpMessage::Midi2ChannelVoice { group, message }),
+            0x5 =>
+            {
+                let mut bytes = vec![word as u8];
+                for w in &words[1..]
+                {
+                    bytes.extend_from_slice(&w.to_be_bytes());
+                }
+                bytes.truncate(((word >> 16) as usize & 0xF).saturating_sub(1).min(13));
+                Some(UmpMessage::Data128 { group, status, stream_id: (word >> 8) as u8, data: bytes })
+            }
+            _ => None,
+        };
+
+        Some((message.unwrap_or_else(|| UmpMessage::Unknown { words: words.to_vec() }), len))
+    }
+}
+
+pub fn encode_ump(messages: &[UmpMessage]) -> Vec<u32>
+{
+    let mut words = Vec::new();
+    for message in messages
+    {
+        message.encode(&mut words);
+    }
+    words
+}
+
+pub fn decode_ump(words: &[u32]) -> Vec<UmpMessage>
+{
+    let mut messages = Vec::new();
+    let mut cursor = 0;
+    while let Some((message, len)) = UmpMessage::decode(&words[cursor..])
+    {
+        messages.push(message);
+        cursor += len;
+    }
+    messages
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[repr(C)]
+pub enum UmpProtocol
+{
+    #[default]
+    Midi1,
+    Midi2,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[repr(C)]
+pub struct UmpEncoder
+{
+    pub group: u8,
+    pub protocol: UmpProtocol,
+}
+
+impl UmpEncoder
+{
+    pub fn new(group: u8, protocol: UmpProtocol) -> UmpEncoder
+    {
+        UmpEncoder { group, protocol }
+    }
+
+    pub fn encode<P: Pipe<UmpMessage>>(&self, event: &MidiEvent, ump_pipe: &mut P) -> bool
+    {
+        let group = self.group & 0xF;
+        match event
+        {
+            MidiEvent::SysEx { data } =>
+            {
+                let data = data.strip_suffix(&[0xF7]).unwrap_or(data);
+                let chunks: Vec<&[u8]> = if data.is_empty() { vec![&[]] } else { data.chunks(6).collect() };
+                for (i, chunk) in chunks.iter().enumerate()
+                {
+                    let status = match (i == 0, i + 1 == chunks.len())
+                    {
+                        (true, true) => SYSEX_COMPLETE,
+                        (true, false) => SYSEX_START,
+                        (false, false) => SYSEX_CONTINUE,
+                        (false, true) => SYSEX_END,
+                    };
+                    ump_pipe.send(UmpMessage::Data64 { group, status, data: chunk.to_vec() });
+                }
+            }
+            MidiEvent::SysExEscape { .. } => return false,
+            event if event.is_meta() => return false,
+            event if event.channel().is_some() => match self.protocol
+            {
+                UmpProtocol::Midi1 => ump_pipe.send(UmpMessage::Midi1ChannelVoice { group, event: event.clone() }),
+                UmpProtocol::Midi2 => match Midi2ChannelVoice::from_midi1(event)
+                {
+                    Some(message) => ump_pipe.send(UmpMessage::Midi2ChannelVoice { group, message }),
+                    None => return false,
+                },
+            },
+            event => ump_pipe.send(UmpMessage::System { group, event: event.clone() }),
+        }
+        true
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Default, Serialize, Deserialize)]
+#[repr(C)]
+pub struct UmpDecoder
+{
+    sysex: Option<Vec<u8>>,
+}
+
+impl UmpDecoder
+{
+    pub fn new() -> UmpDecoder
+    {
+        UmpDecoder::default()
+    }
+
+    pub fn reset(&mut self)
+    {
+        self.sysex = None;
+    }
+
+    pub fn push_words<P: Pipe<MidiEvent>>(&mut self, words: &[u32], midi_pipe: &mut P)
+    {
+        for message in decode_ump(words)
+        {
+            self.push(&message, midi_pipe);
+        }
+    }
+
+    pub fn push<P: Pipe<MidiEvent>>(&mut self, message: &UmpMessage, midi_pipe: &mut P)
+    {
+        match message
+        {
+            UmpMessage::System { event, .. } | UmpMessage::Midi1ChannelVoice { event, .. } => midi_pipe.send(event.clone()),
+            UmpMessage::Midi2ChannelVoice { message, .. } =>
+            {
+                for event in message.to_midi1()
+                {
+                    midi_pipe.send(event);
+                }
+            }
+            UmpMessage::Data64 { status, data, .. } => match *status
+            {
+                SYSEX_COMPLETE =>
+                {
+                    let mut data = data.clone();
+                    data.push(0xF7);
+                    midi_pipe.send(MidiEvent::SysEx { data });
+                }
+                SYSEX_START => self.sysex = Some(data.clone()),
+                SYSEX_CONTINUE =>
+                {
+                    if let Some(sysex) = &mut self.sysex
+                    {
+                        sysex.extend_from_slice(data);
+                    }
+                }
+                SYSEX_END =>
+                {
+                    if let Some(mut sysex) = self.sysex.take()
+                    {
+                        sysex.extend_from_slice(data);
+                        sysex.push(0xF7);
+                        midi_pipe.send(MidiEvent::SysEx { data: sysex });
+                    }
+                }
+                _ => {}
+            },
+            _ => {}
+        }
+    }
+}
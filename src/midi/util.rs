@@ -1,3 +1,7 @@
 pub fn mtof(m: f32) -> f32 {
     f32::powf(2.0, (m - 69.0) / 12.0) * 440.0
 }
+
+pub fn ftom(f: f32) -> f32 {
+    69.0 + 12.0 * f32::log2(f / 440.0)
+}
@@ -0,0 +1,175 @@
+use serde::{Serialize, Deserialize};
+
+use crate::linalg::prelude::*;
+
+use super::{hash::Hash, rand::{rand_vec2, rand_vec3}};
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, Default, Serialize, Deserialize)]
+#[repr(C)]
+pub enum DistanceMetric
+{
+    #[default]
+    Euclidean,
+    SquaredEuclidean,
+    Manhattan,
+    Chebyshev,
+}
+
+impl DistanceMetric
+{
+    pub fn distance_2d(self, d: Vec2) -> f32
+    {
+        match self
+        {
+            DistanceMetric::Euclidean => d.mag(),
+            DistanceMetric::SquaredEuclidean => d.sqr_mag(),
+            DistanceMetric::Manhattan => d.x.abs() + d.y.abs(),
+            DistanceMetric::Chebyshev => d.x.abs().max(d.y.abs()),
+        }
+    }
+
+    pub fn distance_3d(self, d: Vec3) -> f32
+    {
+        match self
+        {
+            DistanceMetric::Euclidean => d.mag(),
+            DistanceMetric::SquaredEuclidean => d.sqr_mag(),
+            DistanceMetric::Manhattan => d.x.abs() + d.y.abs() + d.z.abs(),
+            DistanceMetric::Chebyshev => d.abs().maxcomp(),
+        }
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Default, Serialize, Deserialize)]
+#[repr(C)]
+pub struct Cellular2d
+{
+    pub f1: f32,
+    pub f2: f32,
+    pub cell_id: u32,
+    pub point: Vec2,
+}
+
+impl Cellular2d
+{
+    pub fn edge(&self) -> f32
+    {
+        self.f2 - self.f1
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Default, Serialize, Deserialize)]
+#[repr(C)]
+pub struct Cellular3d
+{
+    pub f1: f32,
+    pub f2: f32,
+    pub cell_id: u32,
+    pub point: Vec3,
+}
+
+impl Cellular3d
+{
+    pub fn edge(&self) -> f32
+    {
+        self.f2 - self.f1
+    }
+}
+
+// nearest cells first, so the far ring is mostly skipped
+const OFFSETS: [isize; 5] = [0, -1, 1, -2, 2];
+
+// a cell keeps its feature point within jitter / 2 of its center, at full jitter this is the point voronoi uses
+fn jittered(random: f32, jitter: f32) -> f32
+{
+    0.5 + (random - 0.5) * jitter
+}
+
+// per axis gap between p and the region a cell's feature point can lie in, cells farther than f2 are skipped
+fn axis_gap(p: f32, cell: isize, jitter: f32) -> f32
+{
+    let low = cell as f32 + 0.5 - jitter * 0.5;
+    let high = cell as f32 + 0.5 + jitter * 0.5;
+    (low - p).max(p - high).max(0.0)
+}
+
+pub fn cellular_2d(p: Vec2, jitter: f32, metric: DistanceMetric, seed: u32) -> Cellular2d
+{
+    let jitter = jitter.clamp(0.0, 1.0);
+    let base = IVec2::from_vec2(p);
+    let mut result = Cellular2d { f1: f32::MAX, f2: f32::MAX, ..Default::default() };
+
+    for x in OFFSETS
+    {
+        for y in OFFSETS
+        {
+            let cell = base + IVec2::new(x, y);
+            let gap = Vec2::new(axis_gap(p.x, cell.x, jitter), axis_gap(p.y, cell.y, jitter));
+            if metric.distance_2d(gap) >= result.f2
+            {
+                continue;
+            }
+
+            let h = cell.hash() ^ seed;
+            let r = rand_vec2(h);
+            let point = Vec2::from_ivec2(cell) + Vec2::new(jittered(r.x, jitter), jittered(r.y, jitter));
+            let distance = metric.distance_2d(point - p);
+
+            if distance < result.f1
+            {
+                result.f2 = result.f1;
+                result.f1 = distance;
+                result.cell_id = h.hash();
+                result.point = point;
+            }
+            else if distance < result.f2
+            {
+                result.f2 = distance;
+            }
+        }
+    }
+
+    result
+}
+
+pub fn cellular_3d(p: Vec3, jitter: f32, metric: DistanceMetric, seed: u32) -> Cellular3d
+{
+    let jitter = jitter.clamp(0.0, 1.0);
+    let base = IVec3::from_vec3(p);
+    let mut result = Cellular3d { f1: f32::MAX, f2: f32::MAX, ..Default::default() };
+
+    for x in OFFSETS
+    {
+        for y in OFFSETS
+        {
+            for z in OFFSETS
+            {
+                let cell = base + IVec3::new(x, y, z);
+                let gap = Vec3::new(axis_gap(p.x, cell.x, jitter), axis_gap(p.y, cell.y, jitter), axis_gap(p.z, cell.z, jitter));
+                if metric.distance_3d(gap) >= result.f2
+                {
+                    continue;
+                }
+
+                let h = cell.hash() ^ seed;
+                let r = rand_vec3(h);
+                let point = Vec3::from_ivec3(cell) + Vec3::new(jittered(r.x, jitter), jittered(r.y, jitter), jittered(r.z, jitter));
+                let distance = metric.distance_3d(point - p);
+
+                if distance < result.f1
+                {
+                    result.f2 = result.f1;
+                    result.f1 = distance;
+                    result.cell_id = h.hash();
+                    result.point = point;
+                }
+                else if distance < result.f2
+                {
+                    result.f2 = distance;
+                }
+            }
+        }
+    }
+
+    result
+}
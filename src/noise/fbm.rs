@@ -0,0 +1,336 @@
+use std::ops;
+
+use serde::{Serialize, Deserialize};
+
+use crate::linalg::prelude::*;
+
+use super::
+{
+    gradient::
+    {
+        gradient_noise_2d, gradient_noise_2d_derivative, gradient_noise_2d_periodic, gradient_noise_2d_periodic_derivative,
+        gradient_noise_3d, gradient_noise_3d_derivative, gradient_noise_3d_periodic, gradient_noise_3d_periodic_derivative,
+    },
+    simplex::{simplex_2d, simplex_2d_derivative, simplex_3d, simplex_3d_derivative},
+    value::{value_noise, value_noise_derivative, value_noise_periodic, value_noise_periodic_derivative},
+    voronoi::{voronoi, voronoi_periodic},
+};
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, Default, Serialize, Deserialize)]
+#[repr(C)]
+pub enum BaseNoise
+{
+    #[default]
+    Gradient,
+    Value,
+    Voronoi,
+    Simplex,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, Default, Serialize, Deserialize)]
+#[repr(C)]
+pub enum FbmKind
+{
+    #[default]
+    Standard,
+    Ridged,
+    Billow,
+    Turbulence,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Serialize, Deserialize)]
+#[repr(C)]
+pub struct Fbm
+{
+    pub base: BaseNoise,
+    pub kind: FbmKind,
+    pub seed: u32,
+    pub octaves: u32,
+    pub lacunarity: f32,
+    pub gain: f32,
+    pub frequency: f32,
+    pub amplitude: f32,
+    pub offset: f32,
+    pub rotation: f32,
+    pub period: Option<IVec3>,
+}
+
+impl Default for Fbm
+{
+    fn default() -> Self
+    {
+        Fbm
+        {
+            base: BaseNoise::Gradient,
+            kind: FbmKind::Standard,
+            seed: 0,
+            octaves: 6,
+            lacunarity: 2.0,
+            gain: 0.5,
+            frequency: 1.0,
+            amplitude: 0.5,
+            offset: 0.0,
+            rotation: 0.0,
+            period: None,
+        }
+    }
+}
+
+// the 3d octave rotation turns around a diagonal so no lattice axis stays aligned between octaves
+const ROTATION_AXIS: Vec3 = Vec3 { x: 1.0, y: 1.0, z: 1.0 };
+
+impl BaseNoise
+{
+    pub fn sample_2d(self, p: Vec2, seed: u32) -> f32
+    {
+        self.sample_2d_wrapped(p, None, seed)
+    }
+
+    // every base is brought to a roughly signed range so the fbm variants treat them alike
+    pub fn sample_3d(self, p: Vec3, seed: u32) -> f32
+    {
+        self.sample_3d_wrapped(p, None, seed)
+    }
+
+    pub fn sample_2d_derivative(self, p: Vec2, seed: u32) -> (f32, Vec2)
+    {
+        self.sample_2d_derivative_wrapped(p, None, seed)
+    }
+
+    pub fn sample_3d_derivative(self, p: Vec3, seed: u32) -> (f32, Vec3)
+    {
+        self.sample_3d_derivative_wrapped(p, None, seed)
+    }
+
+    // simplex has no square lattice to wrap, so it ignores the period
+    fn sample_2d_wrapped(self, p: Vec2, period: Option<IVec3>, seed: u32) -> f32
+    {
+        match (self, period)
+        {
+            (BaseNoise::Gradient, None) => gradient_noise_2d(p, seed),
+            (BaseNoise::Gradient, Some(period)) => gradient_noise_2d_periodic(p, IVec2::new(period.x, period.y), seed),
+            (BaseNoise::Simplex, _) => simplex_2d(p, seed),
+            _ => self.sample_3d_wrapped(Vec3::from(p), period, seed),
+        }
+    }
+
+    fn sample_3d_wrapped(self, p: Vec3, period: Option<IVec3>, seed: u32) -> f32
+    {
+        match (self, period)
+        {
+            (BaseNoise::Gradient, None) => gradient_noise_3d(p, seed),
+            (BaseNoise::Gradient, Some(period)) => gradient_noise_3d_periodic(p, period, seed),
+            (BaseNoise::Simplex, _) => simplex_3d(p, seed),
+            (BaseNoise::Value, None) => value_noise(p, seed) * 2.0 - 1.0,
+            (BaseNoise::Value, Some(period)) => value_noise_periodic(p, period, seed) * 2.0 - 1.0,
+            (BaseNoise::Voronoi, None) => (p - voronoi(p, seed)).mag() * 2.0 - 1.0,
+            (BaseNoise::Voronoi, Some(period)) => (p - voronoi_periodic(p, period, seed)).mag() * 2.0 - 1.0,
+        }
+    }
+
+    fn sample_2d_derivative_wrapped(self, p: Vec2, period: Option<IVec3>, seed: u32) -> (f32, Vec2)
+    {
+        match (self, period)
+        {
+            (BaseNoise::Gradient, None) => gradient_noise_2d_derivative(p, seed),
+            (BaseNoise::Gradient, Some(period)) => gradient_noise_2d_periodic_derivative(p, IVec2::new(period.x, period.y), seed),
+            (BaseNoise::Simplex, _) => simplex_2d_derivative(p, seed),
+            _ =>
+            {
+                let (n, d) = self.sample_3d_derivative_wrapped(Vec3::from(p), period, seed);
+                (n, d.vec2())
+            }
+        }
+    }
+
+    fn sample_3d_derivative_wrapped(self, p: Vec3, period: Option<IVec3>, seed: u32) -> (f32, Vec3)
+    {
+        match self
+        {
+            BaseNoise::Gradient => match period
+            {
+                Some(period) => gradient_noise_3d_periodic_derivative(p, period, seed),
+                None => gradient_noise_3d_derivative(p, seed),
+            },
+            BaseNoise::Simplex => simplex_3d_derivative(p, seed),
+            BaseNoise::Value =>
+            {
+                let (n, d) = match period
+                {
+                    Some(period) => value_noise_periodic_derivative(p, period, seed),
+                    None => value_noise_derivative(p, seed),
+                };
+                (n * 2.0 - 1.0, d * 2.0)
+            }
+            BaseNoise::Voronoi =>
+            {
+                let point = match period
+                {
+                    Some(period) => voronoi_periodic(p, period, seed),
+                    None => voronoi(p, seed),
+                };
+                let offset = p - point;
+                let distance = offset.mag();
+                let d = if distance > 0.0 { offset * (2.0 / distance) } else { Vec3::ZERO };
+                (distance * 2.0 - 1.0, d)
+            }
+        }
+    }
+}
+
+impl Fbm
+{
+    pub fn new(base: BaseNoise, seed: u32) -> Fbm
+    {
+        Fbm { base, seed, ..Default::default() }
+    }
+
+    pub fn ridged(base: BaseNoise, seed: u32) -> Fbm
+    {
+        Fbm { base, seed, kind: FbmKind::Ridged, offset: 1.0, ..Default::default() }
+    }
+
+    pub fn billow(base: BaseNoise, seed: u32) -> Fbm
+    {
+        Fbm { base, seed, kind: FbmKind::Billow, ..Default::default() }
+    }
+
+    pub fn turbulence(base: BaseNoise, seed: u32) -> Fbm
+    {
+        Fbm { base, seed, kind: FbmKind::Turbulence, ..Default::default() }
+    }
+
+    pub fn sample_2d(&self, p: Vec2) -> f32
+    {
+        let rotation = Complex::phase(self.rotation);
+        let mut scale = self.frequency;
+        let mut p = p * self.frequency;
+        let (acc, _) = self.accumulate(||
+        {
+            let n = self.base.sample_2d_wrapped(p, self.octave_period(scale), self.seed);
+            if self.rotation != 0.0
+            {
+                p = rotation * p;
+            }
+            p *= self.lacunarity;
+            scale *= self.lacunarity;
+            (n, 0.0)
+        });
+        acc
+    }
+
+    pub fn sample_3d(&self, p: Vec3) -> f32
+    {
+        let rotation = Mat3::angle_axis(self.rotation, ROTATION_AXIS);
+        let mut scale = self.frequency;
+        let mut p = p * self.frequency;
+        let (acc, _) = self.accumulate(||
+        {
+            let n = self.base.sample_3d_wrapped(p, self.octave_period(scale), self.seed);
+            if self.rotation != 0.0
+            {
+                p = rotation * p;
+            }
+            p *= self.lacunarity;
+            scale *= self.lacunarity;
+            (n, 0.0)
+        });
+        acc
+    }
+
+    // every octave samples at scale * turn * p, so its gradient is taken back through the inverse rotation
+    pub fn sample_2d_derivative(&self, p: Vec2) -> (f32, Vec2)
+    {
+        let rotation = Complex::phase(self.rotation);
+        let mut turn = Complex::ONE;
+        let mut scale = self.frequency;
+        let mut p = p * self.frequency;
+        self.accumulate(||
+        {
+            let (n, d) = self.base.sample_2d_derivative_wrapped(p, self.octave_period(scale), self.seed);
+            let d = turn.i_inv() * d * scale;
+            if self.rotation != 0.0
+            {
+                p = rotation * p;
+                turn = rotation * turn;
+            }
+            p *= self.lacunarity;
+            scale *= self.lacunarity;
+            (n, d)
+        })
+    }
+
+    pub fn sample_3d_derivative(&self, p: Vec3) -> (f32, Vec3)
+    {
+        let rotation = Mat3::angle_axis(self.rotation, ROTATION_AXIS);
+        let mut turn = Mat3::IDENTITY;
+        let mut scale = self.frequency;
+        let mut p = p * self.frequency;
+        self.accumulate(||
+        {
+            let (n, d) = self.base.sample_3d_derivative_wrapped(p, self.octave_period(scale), self.seed);
+            let d = turn.transposed() * d * scale;
+            if self.rotation != 0.0
+            {
+                p = rotation * p;
+                turn = rotation * turn;
+            }
+            p *= self.lacunarity;
+            scale *= self.lacunarity;
+            (n, d)
+        })
+    }
+
+    // an octave sampled at scale * p repeats every period * scale lattice cells, rounded since the lattice only wraps
+    // on whole cells, so the tiling is exact for integer frequency and lacunarity and without rotation
+    fn octave_period(&self, scale: f32) -> Option<IVec3>
+    {
+        let wrap = |period: isize| ((period as f32 * scale).round() as isize).max(1);
+        self.period.map(|period| IVec3::new(wrap(period.x), wrap(period.y), wrap(period.z)))
+    }
+
+    // the scalar samplers pass a plain f32 as their derivative and ignore it
+    fn accumulate<D>(&self, mut octave: impl FnMut() -> (f32, D)) -> (f32, D)
+    where
+        D: Copy + Default + ops::Add<Output = D> + ops::Mul<f32, Output = D>,
+    {
+        let mut acc = 0.0;
+        let mut derivative = D::default();
+        let mut amplitude = self.amplitude;
+        // ridged noise lets each octave scale the detail of the next one so valleys stay smooth
+        let mut weight = 1.0;
+        let mut weight_derivative = D::default();
+
+        for _ in 0..self.octaves
+        {
+            let (n, d) = octave();
+            let (signal, signal_derivative) = match self.kind
+            {
+                FbmKind::Standard => (n + self.offset, d),
+                FbmKind::Billow => (n.abs() * 2.0 - 1.0 + self.offset, d * (n.signum() * 2.0)),
+                FbmKind::Turbulence => (n.abs() + self.offset, d * n.signum()),
+                FbmKind::Ridged =>
+                {
+                    let ridge = self.offset - n.abs();
+                    let signal = ridge * ridge * weight;
+                    let signal_derivative = d * (-2.0 * ridge * n.signum() * weight) + weight_derivative * (ridge * ridge);
+                    (weight, weight_derivative) = if signal * 2.0 > 0.0 && signal * 2.0 < 1.0
+                    {
+                        (signal * 2.0, signal_derivative * 2.0)
+                    }
+                    else
+                    {
+                        ((signal * 2.0).clamp(0.0, 1.0), D::default())
+                    };
+                    (signal, signal_derivative)
+                }
+            };
+
+            acc += signal * amplitude;
+            derivative = derivative + signal_derivative * amplitude;
+            amplitude *= self.gain;
+        }
+
+        (acc, derivative)
+    }
+}
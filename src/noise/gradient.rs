@@ -1,18 +1,18 @@
 use crate::linalg::prelude::*;
 
-use super::{hash::Hash, rand::{rand_vec2, rand_vec3}};
+use super::{hash::Hash, periodic::{wrap_ivec2, wrap_ivec3}, rand::{rand_vec2, rand_vec3}};
 
-pub fn gradient_noise_2d(p: Vec2, seed: u32) -> f32
+fn gradient_2d(p: Vec2, seed: u32, wrap: impl Fn(IVec2) -> IVec2) -> f32
 {
     let i = IVec2::from_vec2(p);
     let f = p - Vec2::from_ivec2(i);
 
     let u = f*f*(Vec2::ONE * 3.0 - f * 2.0);
     
-    let r00 = rand_vec2((i + IVec2::new(0,0)).hash() ^ seed) * 2.0 - Vec2::new(1.0, 1.0);
-    let r01 = rand_vec2((i + IVec2::new(1,0)).hash() ^ seed) * 2.0 - Vec2::new(1.0, 1.0);
-    let r10 = rand_vec2((i + IVec2::new(0,1)).hash() ^ seed) * 2.0 - Vec2::new(1.0, 1.0);
-    let r11 = rand_vec2((i + IVec2::new(1,1)).hash() ^ seed) * 2.0 - Vec2::new(1.0, 1.0);
+    let r00 = rand_vec2(wrap(i + IVec2::new(0,0)).hash() ^ seed) * 2.0 - Vec2::new(1.0, 1.0);
+    let r01 = rand_vec2(wrap(i + IVec2::new(1,0)).hash() ^ seed) * 2.0 - Vec2::new(1.0, 1.0);
+    let r10 = rand_vec2(wrap(i + IVec2::new(0,1)).hash() ^ seed) * 2.0 - Vec2::new(1.0, 1.0);
+    let r11 = rand_vec2(wrap(i + IVec2::new(1,1)).hash() ^ seed) * 2.0 - Vec2::new(1.0, 1.0);
     
     let x00 = Vec2::dot(r00, f - Vec2::new(0.0,0.0));
     let x01 = Vec2::dot(r01, f - Vec2::new(1.0,0.0));
@@ -25,21 +25,21 @@ pub fn gradient_noise_2d(p: Vec2, seed: u32) -> f32
     lerp(x0, x1, u.y)
 }
 
-pub fn gradient_noise_3d(p: Vec3, seed: u32) -> f32
+fn gradient_3d(p: Vec3, seed: u32, wrap: impl Fn(IVec3) -> IVec3) -> f32
 {
     let i = IVec3::from_vec3(p);
     let f = p - Vec3::from_ivec3(i);
 
     let u = f*f*(Vec3::ONE * 3.0 - f * 2.0);
     
-    let r000 = rand_vec3((i + IVec3::new(0,0,0)).hash() ^ seed) * 2.0 - 1.0;
-    let r010 = rand_vec3((i + IVec3::new(1,0,0)).hash() ^ seed) * 2.0 - 1.0;
-    let r100 = rand_vec3((i + IVec3::new(0,1,0)).hash() ^ seed) * 2.0 - 1.0;
-    let r110 = rand_vec3((i + IVec3::new(1,1,0)).hash() ^ seed) * 2.0 - 1.0;
-    let r001 = rand_vec3((i + IVec3::new(0,0,1)).hash() ^ seed) * 2.0 - 1.0;
-    let r011 = rand_vec3((i + IVec3::new(1,0,1)).hash() ^ seed) * 2.0 - 1.0;
-    let r101 = rand_vec3((i + IVec3::new(0,1,1)).hash() ^ seed) * 2.0 - 1.0;
-    let r111 = rand_vec3((i + IVec3::new(1,1,1)).hash() ^ seed) * 2.0 - 1.0;
+    let r000 = rand_vec3(wrap(i + IVec3::new(0,0,0)).hash() ^ seed) * 2.0 - 1.0;
+    let r010 = rand_vec3(wrap(i + IVec3::new(1,0,0)).hash() ^ seed) * 2.0 - 1.0;
+    let r100 = rand_vec3(wrap(i + IVec3::new(0,1,0)).hash() ^ seed) * 2.0 - 1.0;
+    let r110 = rand_vec3(wrap(i + IVec3::new(1,1,0)).hash() ^ seed) * 2.0 - 1.0;
+    let r001 = rand_vec3(wrap(i + IVec3::new(0,0,1)).hash() ^ seed) * 2.0 - 1.0;
+    let r011 = rand_vec3(wrap(i + IVec3::new(1,0,1)).hash() ^ seed) * 2.0 - 1.0;
+    let r101 = rand_vec3(wrap(i + IVec3::new(0,1,1)).hash() ^ seed) * 2.0 - 1.0;
+    let r111 = rand_vec3(wrap(i + IVec3::new(1,1,1)).hash() ^ seed) * 2.0 - 1.0;
     
     let x000 = Vec3::dot(r000, f - Vec3::new(0.0,0.0,0.0));
     let x010 = Vec3::dot(r010, f - Vec3::new(1.0,0.0,0.0));
@@ -60,3 +60,120 @@ pub fn gradient_noise_3d(p: Vec3, seed: u32) -> f32
 
     lerp(x0, x1, u.y)
 }
+
+// the derivative adds the interpolated corner gradients to the slope of the interpolation weights
+fn gradient_2d_derivative(p: Vec2, seed: u32, wrap: impl Fn(IVec2) -> IVec2) -> (f32, Vec2)
+{
+    let i = IVec2::from_vec2(p);
+    let f = p - Vec2::from_ivec2(i);
+
+    let u = f*f*(Vec2::ONE * 3.0 - f * 2.0);
+    let du = f*(Vec2::ONE - f) * 6.0;
+
+    let r00 = rand_vec2(wrap(i + IVec2::new(0,0)).hash() ^ seed) * 2.0 - Vec2::new(1.0, 1.0);
+    let r01 = rand_vec2(wrap(i + IVec2::new(1,0)).hash() ^ seed) * 2.0 - Vec2::new(1.0, 1.0);
+    let r10 = rand_vec2(wrap(i + IVec2::new(0,1)).hash() ^ seed) * 2.0 - Vec2::new(1.0, 1.0);
+    let r11 = rand_vec2(wrap(i + IVec2::new(1,1)).hash() ^ seed) * 2.0 - Vec2::new(1.0, 1.0);
+
+    let x00 = Vec2::dot(r00, f - Vec2::new(0.0,0.0));
+    let x01 = Vec2::dot(r01, f - Vec2::new(1.0,0.0));
+    let x10 = Vec2::dot(r10, f - Vec2::new(0.0,1.0));
+    let x11 = Vec2::dot(r11, f - Vec2::new(1.0,1.0));
+
+    let x0 = lerp(x00, x01, u.x);
+    let x1 = lerp(x10, x11, u.x);
+
+    let gradient = Vec2::lerp(Vec2::lerp(r00, r01, u.x), Vec2::lerp(r10, r11, u.x), u.y);
+    let slope = Vec2::new(lerp(x01 - x00, x11 - x10, u.y), x1 - x0);
+
+    (lerp(x0, x1, u.y), gradient + du * slope)
+}
+
+fn gradient_3d_derivative(p: Vec3, seed: u32, wrap: impl Fn(IVec3) -> IVec3) -> (f32, Vec3)
+{
+    let i = IVec3::from_vec3(p);
+    let f = p - Vec3::from_ivec3(i);
+
+    let u = f*f*(Vec3::ONE * 3.0 - f * 2.0);
+    let du = f*(Vec3::ONE - f) * 6.0;
+
+    let r000 = rand_vec3(wrap(i + IVec3::new(0,0,0)).hash() ^ seed) * 2.0 - 1.0;
+    let r010 = rand_vec3(wrap(i + IVec3::new(1,0,0)).hash() ^ seed) * 2.0 - 1.0;
+    let r100 = rand_vec3(wrap(i + IVec3::new(0,1,0)).hash() ^ seed) * 2.0 - 1.0;
+    let r110 = rand_vec3(wrap(i + IVec3::new(1,1,0)).hash() ^ seed) * 2.0 - 1.0;
+    let r001 = rand_vec3(wrap(i + IVec3::new(0,0,1)).hash() ^ seed) * 2.0 - 1.0;
+    let r011 = rand_vec3(wrap(i + IVec3::new(1,0,1)).hash() ^ seed) * 2.0 - 1.0;
+    let r101 = rand_vec3(wrap(i + IVec3::new(0,1,1)).hash() ^ seed) * 2.0 - 1.0;
+    let r111 = rand_vec3(wrap(i + IVec3::new(1,1,1)).hash() ^ seed) * 2.0 - 1.0;
+
+    let x000 = Vec3::dot(r000, f - Vec3::new(0.0,0.0,0.0));
+    let x010 = Vec3::dot(r010, f - Vec3::new(1.0,0.0,0.0));
+    let x100 = Vec3::dot(r100, f - Vec3::new(0.0,1.0,0.0));
+    let x110 = Vec3::dot(r110, f - Vec3::new(1.0,1.0,0.0));
+    let x001 = Vec3::dot(r001, f - Vec3::new(0.0,0.0,1.0));
+    let x011 = Vec3::dot(r011, f - Vec3::new(1.0,0.0,1.0));
+    let x101 = Vec3::dot(r101, f - Vec3::new(0.0,1.0,1.0));
+    let x111 = Vec3::dot(r111, f - Vec3::new(1.0,1.0,1.0));
+
+    let x00 = lerp(x000, x001, u.z);
+    let x01 = lerp(x010, x011, u.z);
+    let x10 = lerp(x100, x101, u.z);
+    let x11 = lerp(x110, x111, u.z);
+
+    let x0 = lerp(x00, x01, u.x);
+    let x1 = lerp(x10, x11, u.x);
+
+    let g00 = Vec3::lerp(r000, r001, u.z);
+    let g01 = Vec3::lerp(r010, r011, u.z);
+    let g10 = Vec3::lerp(r100, r101, u.z);
+    let g11 = Vec3::lerp(r110, r111, u.z);
+    let gradient = Vec3::lerp(Vec3::lerp(g00, g01, u.x), Vec3::lerp(g10, g11, u.x), u.y);
+
+    let slope = Vec3::new(
+        lerp(x01 - x00, x11 - x10, u.y),
+        x1 - x0,
+        lerp(lerp(x001 - x000, x011 - x010, u.x), lerp(x101 - x100, x111 - x110, u.x), u.y),
+    );
+
+    (lerp(x0, x1, u.y), gradient + du * slope)
+}
+
+pub fn gradient_noise_2d(p: Vec2, seed: u32) -> f32
+{
+    gradient_2d(p, seed, |c| c)
+}
+
+pub fn gradient_noise_3d(p: Vec3, seed: u32) -> f32
+{
+    gradient_3d(p, seed, |c| c)
+}
+
+pub fn gradient_noise_2d_derivative(p: Vec2, seed: u32) -> (f32, Vec2)
+{
+    gradient_2d_derivative(p, seed, |c| c)
+}
+
+pub fn gradient_noise_3d_derivative(p: Vec3, seed: u32) -> (f32, Vec3)
+{
+    gradient_3d_derivative(p, seed, |c| c)
+}
+
+pub fn gradient_noise_2d_periodic(p: Vec2, period: IVec2, seed: u32) -> f32
+{
+    gradient_2d(p, seed, |c| wrap_ivec2(c, period))
+}
+
+pub fn gradient_noise_3d_periodic(p: Vec3, period: IVec3, seed: u32) -> f32
+{
+    gradient_3d(p, seed, |c| wrap_ivec3(c, period))
+}
+
+pub fn gradient_noise_2d_periodic_derivative(p: Vec2, period: IVec2, seed: u32) -> (f32, Vec2)
+{
+    gradient_2d_derivative(p, seed, |c| wrap_ivec2(c, period))
+}
+
+pub fn gradient_noise_3d_periodic_derivative(p: Vec3, period: IVec3, seed: u32) -> (f32, Vec3)
+{
+    gradient_3d_derivative(p, seed, |c| wrap_ivec3(c, period))
+}
@@ -0,0 +1,255 @@
+use std::ops;
+
+use serde::{Serialize, Deserialize};
+
+use crate::linalg::prelude::*;
+
+use super::
+{
+    fbm::Fbm,
+    noise_fn::{CellularNoise, GradientNoise, NoiseFn, PerlinNoise, SimplexNoise, ValueNoise, VoronoiNoise},
+};
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[repr(C)]
+pub enum NoiseGraph
+{
+    Constant(f32),
+    Gradient(GradientNoise),
+    Value(ValueNoise),
+    Voronoi(VoronoiNoise),
+    Simplex(SimplexNoise),
+    Perlin(PerlinNoise),
+    Cellular(CellularNoise),
+    Fbm(Fbm),
+    Add(Box<NoiseGraph>, Box<NoiseGraph>),
+    Mul(Box<NoiseGraph>, Box<NoiseGraph>),
+    Min(Box<NoiseGraph>, Box<NoiseGraph>),
+    Max(Box<NoiseGraph>, Box<NoiseGraph>),
+    Clamp { source: Box<NoiseGraph>, min: f32, max: f32 },
+    Remap { source: Box<NoiseGraph>, old_min: f32, old_max: f32, new_min: f32, new_max: f32 },
+    Scale { source: Box<NoiseGraph>, scale: Vec3 },
+    Translate { source: Box<NoiseGraph>, offset: Vec3 },
+    Rotate { source: Box<NoiseGraph>, rotation: Quat },
+    Transform { source: Box<NoiseGraph>, matrix: Mat3 },
+    Warp { source: Box<NoiseGraph>, warp: Box<NoiseGraph>, strength: f32 },
+}
+
+impl Default for NoiseGraph
+{
+    fn default() -> Self
+    {
+        NoiseGraph::Constant(0.0)
+    }
+}
+
+// the warp noise is read at a few far apart offsets so every axis gets an unrelated displacement
+const WARP_OFFSETS: [Vec3; 3] =
+[
+    Vec3 { x: 0.0, y: 0.0, z: 0.0 },
+    Vec3 { x: 31.7, y: -17.3, z: 5.2 },
+    Vec3 { x: -11.9, y: 43.1, z: -27.6 },
+];
+
+impl NoiseGraph
+{
+    pub fn min(self, other: impl Into<NoiseGraph>) -> NoiseGraph
+    {
+        NoiseGraph::Min(Box::new(self), Box::new(other.into()))
+    }
+
+    pub fn max(self, other: impl Into<NoiseGraph>) -> NoiseGraph
+    {
+        NoiseGraph::Max(Box::new(self), Box::new(other.into()))
+    }
+
+    pub fn clamp(self, min: f32, max: f32) -> NoiseGraph
+    {
+        NoiseGraph::Clamp { source: Box::new(self), min, max }
+    }
+
+    pub fn remap(self, old_min: f32, old_max: f32, new_min: f32, new_max: f32) -> NoiseGraph
+    {
+        NoiseGraph::Remap { source: Box::new(self), old_min, old_max, new_min, new_max }
+    }
+
+    pub fn scale(self, scale: Vec3) -> NoiseGraph
+    {
+        NoiseGraph::Scale { source: Box::new(self), scale }
+    }
+
+    pub fn translate(self, offset: Vec3) -> NoiseGraph
+    {
+        NoiseGraph::Translate { source: Box::new(self), offset }
+    }
+
+    pub fn rotate(self, rotation: Quat) -> NoiseGraph
+    {
+        NoiseGraph::Rotate { source: Box::new(self), rotation }
+    }
+
+    pub fn transform(self, matrix: Mat3) -> NoiseGraph
+    {
+        NoiseGraph::Transform { source: Box::new(self), matrix }
+    }
+
+    pub fn warp(self, warp: impl Into<NoiseGraph>, strength: f32) -> NoiseGraph
+    {
+        NoiseGraph::Warp { source: Box::new(self), warp: Box::new(warp.into()), strength }
+    }
+}
+
+// domain nodes move the sample position before it reaches their source, a 2d sample stays in 2d all the way down:
+// rotations and matrices act on (x, y, 0) and keep x and y, and the warp only displaces x and y
+impl NoiseFn for NoiseGraph
+{
+    fn sample_2d(&self, p: Vec2) -> f32
+    {
+        match self
+        {
+            NoiseGraph::Constant(value) => *value,
+            NoiseGraph::Gradient(noise) => noise.sample_2d(p),
+            NoiseGraph::Value(noise) => noise.sample_2d(p),
+            NoiseGraph::Voronoi(noise) => noise.sample_2d(p),
+            NoiseGraph::Simplex(noise) => noise.sample_2d(p),
+            NoiseGraph::Perlin(noise) => noise.sample_2d(p),
+            NoiseGraph::Cellular(noise) => noise.sample_2d(p),
+            NoiseGraph::Fbm(noise) => noise.sample_2d(p),
+            NoiseGraph::Add(a, b) => a.sample_2d(p) + b.sample_2d(p),
+            NoiseGraph::Mul(a, b) => a.sample_2d(p) * b.sample_2d(p),
+            NoiseGraph::Min(a, b) => a.sample_2d(p).min(b.sample_2d(p)),
+            NoiseGraph::Max(a, b) => a.sample_2d(p).max(b.sample_2d(p)),
+            NoiseGraph::Clamp { source, min, max } => source.sample_2d(p).max(*min).min(*max),
+            NoiseGraph::Remap { source, old_min, old_max, new_min, new_max } =>
+                fit(source.sample_2d(p), *old_min, *old_max, *new_min, *new_max),
+            NoiseGraph::Scale { source, scale } => source.sample_2d(p * scale.vec2()),
+            NoiseGraph::Translate { source, offset } => source.sample_2d(p + offset.vec2()),
+            NoiseGraph::Rotate { source, rotation } => source.sample_2d((*rotation * Vec3::from(p)).vec2()),
+            NoiseGraph::Transform { source, matrix } => source.sample_2d((*matrix * Vec3::from(p)).vec2()),
+            NoiseGraph::Warp { source, warp, strength } =>
+            {
+                let offset = Vec2::new(warp.sample_2d(p + WARP_OFFSETS[0].vec2()), warp.sample_2d(p + WARP_OFFSETS[1].vec2()));
+                source.sample_2d(p + offset * *strength)
+            }
+        }
+    }
+
+    fn sample_3d(&self, p: Vec3) -> f32
+    {
+        match self
+        {
+            NoiseGraph::Constant(value) => *value,
+            NoiseGraph::Gradient(noise) => noise.sample_3d(p),
+            NoiseGraph::Value(noise) => noise.sample_3d(p),
+            NoiseGraph::Voronoi(noise) => noise.sample_3d(p),
+            NoiseGraph::Simplex(noise) => noise.sample_3d(p),
+            NoiseGraph::Perlin(noise) => noise.sample_3d(p),
+            NoiseGraph::Cellular(noise) => noise.sample_3d(p),
+            NoiseGraph::Fbm(noise) => noise.sample_3d(p),
+            NoiseGraph::Add(a, b) => a.sample_3d(p) + b.sample_3d(p),
+            NoiseGraph::Mul(a, b) => a.sample_3d(p) * b.sample_3d(p),
+            NoiseGraph::Min(a, b) => a.sample_3d(p).min(b.sample_3d(p)),
+            NoiseGraph::Max(a, b) => a.sample_3d(p).max(b.sample_3d(p)),
+            NoiseGraph::Clamp { source, min, max } => source.sample_3d(p).max(*min).min(*max),
+            NoiseGraph::Remap { source, old_min, old_max, new_min, new_max } =>
+                fit(source.sample_3d(p), *old_min, *old_max, *new_min, *new_max),
+            NoiseGraph::Scale { source, scale } => source.sample_3d(p.mul_comp(*scale)),
+            NoiseGraph::Translate { source, offset } => source.sample_3d(p + *offset),
+            NoiseGraph::Rotate { source, rotation } => source.sample_3d(*rotation * p),
+            NoiseGraph::Transform { source, matrix } => source.sample_3d(*matrix * p),
+            NoiseGraph::Warp { source, warp, strength } =>
+            {
+                let offset = Vec3::new(
+                    warp.sample_3d(p + WARP_OFFSETS[0]),
+                    warp.sample_3d(p + WARP_OFFSETS[1]),
+                    warp.sample_3d(p + WARP_OFFSETS[2]),
+                );
+                source.sample_3d(p + offset * *strength)
+            }
+        }
+    }
+}
+
+impl ops::Add<NoiseGraph> for NoiseGraph
+{
+    type Output = NoiseGraph;
+
+    fn add(self, rhs: NoiseGraph) -> NoiseGraph
+    {
+        NoiseGraph::Add(Box::new(self), Box::new(rhs))
+    }
+}
+
+impl ops::Mul<NoiseGraph> for NoiseGraph
+{
+    type Output = NoiseGraph;
+
+    fn mul(self, rhs: NoiseGraph) -> NoiseGraph
+    {
+        NoiseGraph::Mul(Box::new(self), Box::new(rhs))
+    }
+}
+
+impl From<f32> for NoiseGraph
+{
+    fn from(value: f32) -> NoiseGraph
+    {
+        NoiseGraph::Constant(value)
+    }
+}
+
+impl From<GradientNoise> for NoiseGraph
+{
+    fn from(noise: GradientNoise) -> NoiseGraph
+    {
+        NoiseGraph::Gradient(noise)
+    }
+}
+
+impl From<ValueNoise> for NoiseGraph
+{
+    fn from(noise: ValueNoise) -> NoiseGraph
+    {
+        NoiseGraph::Value(noise)
+    }
+}
+
+impl From<VoronoiNoise> for NoiseGraph
+{
+    fn from(noise: VoronoiNoise) -> NoiseGraph
+    {
+        NoiseGraph::Voronoi(noise)
+    }
+}
+
+impl From<SimplexNoise> for NoiseGraph
+{
+    fn from(noise: SimplexNoise) -> NoiseGraph
+    {
+        NoiseGraph::Simplex(noise)
+    }
+}
+
+impl From<PerlinNoise> for NoiseGraph
+{
+    fn from(noise: PerlinNoise) -> NoiseGraph
+    {
+        NoiseGraph::Perlin(noise)
+    }
+}
+
+impl From<CellularNoise> for NoiseGraph
+{
+    fn from(noise: CellularNoise) -> NoiseGraph
+    {
+        NoiseGraph::Cellular(noise)
+    }
+}
+
+impl From<Fbm> for NoiseGraph
+{
+    fn from(noise: Fbm) -> NoiseGraph
+    {
+        NoiseGraph::Fbm(noise)
+    }
+}
@@ -59,3 +59,11 @@ impl Hash for Vec3
         (f32_bits_to_u32(self.x) ^ self.y.hash() ^ self.z.hash()).hash()
     }
 }
+
+impl Hash for [i32; 4]
+{
+    fn hash(self) -> u32
+    {
+        (i32_bits_to_u32(self[0]) ^ self[1].hash() ^ self[2].hash() ^ self[3].hash()).hash()
+    }
+}
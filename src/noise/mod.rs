@@ -1,9 +1,17 @@
+pub mod cellular;
 pub mod convert;
+pub mod fbm;
 pub mod gradient;
+pub mod graph;
 pub mod hash;
+pub mod noise_fn;
+pub mod periodic;
 pub mod perlin;
 pub mod prelude;
 pub mod rand;
+pub mod simplex;
 pub mod smoothstep;
 pub mod value;
 pub mod voronoi;
+
+#[cfg(test)] mod tests;
\ No newline at end of file
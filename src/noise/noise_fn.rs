@@ -0,0 +1,222 @@
+use serde::{Serialize, Deserialize};
+
+use crate::linalg::prelude::*;
+
+use super::
+{
+    cellular::{cellular_2d, cellular_3d, DistanceMetric},
+    convert::float_construct,
+    fbm::Fbm,
+    gradient::{gradient_noise_2d, gradient_noise_2d_periodic, gradient_noise_3d, gradient_noise_3d_periodic},
+    perlin::{perlin_2d, perlin_3d},
+    simplex::{simplex_2d, simplex_3d},
+    value::{value_noise, value_noise_periodic},
+    voronoi::{voronoi, voronoi_periodic},
+};
+
+pub trait NoiseFn
+{
+    fn sample_2d(&self, p: Vec2) -> f32;
+    fn sample_3d(&self, p: Vec3) -> f32;
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Default, Serialize, Deserialize)]
+#[repr(C)]
+pub struct GradientNoise
+{
+    pub seed: u32,
+    pub period: Option<IVec3>,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Default, Serialize, Deserialize)]
+#[repr(C)]
+pub struct ValueNoise
+{
+    pub seed: u32,
+    pub period: Option<IVec3>,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Default, Serialize, Deserialize)]
+#[repr(C)]
+pub struct VoronoiNoise
+{
+    pub seed: u32,
+    pub period: Option<IVec3>,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Default, Serialize, Deserialize)]
+#[repr(C)]
+pub struct SimplexNoise
+{
+    pub seed: u32,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Serialize, Deserialize)]
+#[repr(C)]
+pub struct PerlinNoise
+{
+    pub octaves: u32,
+    pub seed: u32,
+}
+
+impl Default for PerlinNoise
+{
+    fn default() -> Self
+    {
+        PerlinNoise { octaves: 6, seed: 0 }
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, Default, Serialize, Deserialize)]
+#[repr(C)]
+pub enum CellularOutput
+{
+    #[default]
+    F1,
+    F2,
+    Edge,
+    CellId,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Serialize, Deserialize)]
+#[repr(C)]
+pub struct CellularNoise
+{
+    pub jitter: f32,
+    pub metric: DistanceMetric,
+    pub output: CellularOutput,
+    pub seed: u32,
+}
+
+impl Default for CellularNoise
+{
+    fn default() -> Self
+    {
+        CellularNoise { jitter: 1.0, metric: DistanceMetric::Euclidean, output: CellularOutput::F1, seed: 0 }
+    }
+}
+
+// noises that only exist in 3d sample the z = 0 plane for 2d input
+impl NoiseFn for GradientNoise
+{
+    fn sample_2d(&self, p: Vec2) -> f32
+    {
+        match self.period
+        {
+            Some(period) => gradient_noise_2d_periodic(p, IVec2::new(period.x, period.y), self.seed),
+            None => gradient_noise_2d(p, self.seed),
+        }
+    }
+
+    fn sample_3d(&self, p: Vec3) -> f32
+    {
+        match self.period
+        {
+            Some(period) => gradient_noise_3d_periodic(p, period, self.seed),
+            None => gradient_noise_3d(p, self.seed),
+        }
+    }
+}
+
+impl NoiseFn for ValueNoise
+{
+    fn sample_2d(&self, p: Vec2) -> f32
+    {
+        self.sample_3d(Vec3::from(p))
+    }
+
+    fn sample_3d(&self, p: Vec3) -> f32
+    {
+        match self.period
+        {
+            Some(period) => value_noise_periodic(p, period, self.seed),
+            None => value_noise(p, self.seed),
+        }
+    }
+}
+
+impl NoiseFn for VoronoiNoise
+{
+    fn sample_2d(&self, p: Vec2) -> f32
+    {
+        self.sample_3d(Vec3::from(p))
+    }
+
+    fn sample_3d(&self, p: Vec3) -> f32
+    {
+        let point = match self.period
+        {
+            Some(period) => voronoi_periodic(p, period, self.seed),
+            None => voronoi(p, self.seed),
+        };
+        (p - point).mag()
+    }
+}
+
+impl NoiseFn for SimplexNoise
+{
+    fn sample_2d(&self, p: Vec2) -> f32
+    {
+        simplex_2d(p, self.seed)
+    }
+
+    fn sample_3d(&self, p: Vec3) -> f32
+    {
+        simplex_3d(p, self.seed)
+    }
+}
+
+impl NoiseFn for PerlinNoise
+{
+    fn sample_2d(&self, p: Vec2) -> f32
+    {
+        perlin_2d(p, self.octaves, self.seed)
+    }
+
+    fn sample_3d(&self, p: Vec3) -> f32
+    {
+        perlin_3d(p, self.octaves, self.seed)
+    }
+}
+
+impl CellularOutput
+{
+    fn select(self, f1: f32, f2: f32, cell_id: u32) -> f32
+    {
+        match self
+        {
+            CellularOutput::F1 => f1,
+            CellularOutput::F2 => f2,
+            CellularOutput::Edge => f2 - f1,
+            CellularOutput::CellId => float_construct(cell_id),
+        }
+    }
+}
+
+impl NoiseFn for CellularNoise
+{
+    fn sample_2d(&self, p: Vec2) -> f32
+    {
+        let c = cellular_2d(p, self.jitter, self.metric, self.seed);
+        self.output.select(c.f1, c.f2, c.cell_id)
+    }
+
+    fn sample_3d(&self, p: Vec3) -> f32
+    {
+        let c = cellular_3d(p, self.jitter, self.metric, self.seed);
+        self.output.select(c.f1, c.f2, c.cell_id)
+    }
+}
+
+impl NoiseFn for Fbm
+{
+    fn sample_2d(&self, p: Vec2) -> f32
+    {
+        Fbm::sample_2d(self, p)
+    }
+
+    fn sample_3d(&self, p: Vec3) -> f32
+    {
+        Fbm::sample_3d(self, p)
+    }
+}
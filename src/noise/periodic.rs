@@ -0,0 +1,23 @@
+use std::f32::consts::TAU;
+
+use crate::linalg::prelude::*;
+
+pub fn wrap_ivec2(cell: IVec2, period: IVec2) -> IVec2
+{
+    IVec2::new(cell.x.rem_euclid(period.x.max(1)), cell.y.rem_euclid(period.y.max(1)))
+}
+
+pub fn wrap_ivec3(cell: IVec3, period: IVec3) -> IVec3
+{
+    IVec3::new(cell.x.rem_euclid(period.x.max(1)), cell.y.rem_euclid(period.y.max(1)), cell.z.rem_euclid(period.z.max(1)))
+}
+
+// wraps both axes of a tile onto two circles, so any 4d noise sampled at the result loops seamlessly in uv
+// with radius chosen so one turn covers the given number of noise cells
+pub fn torus_4d(uv: Vec2, cells: f32) -> Vec4
+{
+    let radius = cells / TAU;
+    let (su, cu) = (uv.x * TAU).sin_cos();
+    let (sv, cv) = (uv.y * TAU).sin_cos();
+    Vec4 { x: cu * radius, y: su * radius, z: cv * radius, w: sv * radius }
+}
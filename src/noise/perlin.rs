@@ -1,35 +1,33 @@
 use crate::linalg::prelude::*;
 
-use super::gradient::{gradient_noise_3d, gradient_noise_2d};
+use super::fbm::Fbm;
 
-pub fn perlin_2d(mut p: Vec2, mut octaves: u32, s: u32) -> f32
+pub fn perlin_2d(p: Vec2, octaves: u32, s: u32) -> f32
 {
-    let mut acc = 0.0;
-    let mut contrib = 0.5;
-    
-    while octaves > 0
-    {
-        acc += gradient_noise_2d(p, s) * contrib;
-        contrib *= 0.5;
-        p *= 2.0;
-        octaves -= 1;
-    }
-    
-    return acc;
+    Fbm { octaves, seed: s, ..Default::default() }.sample_2d(p)
 }
 
-pub fn perlin_3d(mut p: Vec3, mut octaves: u32, s: u32) -> f32
+pub fn perlin_3d(p: Vec3, octaves: u32, s: u32) -> f32
 {
-    let mut acc = 0.0;
-    let mut contrib = 0.5;
-    
-    while octaves > 0
-    {
-        acc += gradient_noise_3d(p, s) * contrib;
-        contrib *= 0.5;
-        p *= 2.0;
-        octaves -= 1;
-    }
-    
-    return acc;
+    Fbm { octaves, seed: s, ..Default::default() }.sample_3d(p)
+}
+
+pub fn perlin_2d_derivative(p: Vec2, octaves: u32, s: u32) -> (f32, Vec2)
+{
+    Fbm { octaves, seed: s, ..Default::default() }.sample_2d_derivative(p)
+}
+
+pub fn perlin_3d_derivative(p: Vec3, octaves: u32, s: u32) -> (f32, Vec3)
+{
+    Fbm { octaves, seed: s, ..Default::default() }.sample_3d_derivative(p)
+}
+
+pub fn perlin_2d_periodic(p: Vec2, period: IVec2, octaves: u32, s: u32) -> f32
+{
+    Fbm { octaves, seed: s, period: Some(IVec3::new(period.x, period.y, 1)), ..Default::default() }.sample_2d(p)
+}
+
+pub fn perlin_3d_periodic(p: Vec3, period: IVec3, octaves: u32, s: u32) -> f32
+{
+    Fbm { octaves, seed: s, period: Some(period), ..Default::default() }.sample_3d(p)
 }
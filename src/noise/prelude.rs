@@ -1,7 +1,17 @@
 pub use super::
 {
-    gradient::{gradient_noise_2d, gradient_noise_3d},
-    perlin::{perlin_2d, perlin_3d},
-    value::value_noise,
-    voronoi::voronoi,
+    cellular::{cellular_2d, cellular_3d, Cellular2d, Cellular3d, DistanceMetric},
+    fbm::{BaseNoise, Fbm, FbmKind},
+    gradient::
+    {
+        gradient_noise_2d, gradient_noise_2d_derivative, gradient_noise_2d_periodic, gradient_noise_2d_periodic_derivative,
+        gradient_noise_3d, gradient_noise_3d_derivative, gradient_noise_3d_periodic, gradient_noise_3d_periodic_derivative,
+    },
+    graph::NoiseGraph,
+    noise_fn::{CellularNoise, CellularOutput, GradientNoise, NoiseFn, PerlinNoise, SimplexNoise, ValueNoise, VoronoiNoise},
+    periodic::torus_4d,
+    perlin::{perlin_2d, perlin_2d_derivative, perlin_2d_periodic, perlin_3d, perlin_3d_derivative, perlin_3d_periodic},
+    simplex::{simplex_2d, simplex_2d_derivative, simplex_3d, simplex_3d_derivative, simplex_4d},
+    value::{value_noise, value_noise_derivative, value_noise_periodic, value_noise_periodic_derivative},
+    voronoi::{voronoi, voronoi_periodic},
 };
@@ -0,0 +1,178 @@
+use std::f32::consts::FRAC_1_SQRT_2;
+
+use crate::linalg::prelude::*;
+
+use super::hash::Hash;
+
+const F2: f32 = 0.366_025_4;
+const G2: f32 = 0.211_324_87;
+const F3: f32 = 1.0 / 3.0;
+const G3: f32 = 1.0 / 6.0;
+const F4: f32 = 0.309_017;
+const G4: f32 = 0.138_196_6;
+
+const GRAD_3D: [[f32; 3]; 12] =
+[
+    [1.0, 1.0, 0.0], [-1.0, 1.0, 0.0], [1.0, -1.0, 0.0], [-1.0, -1.0, 0.0],
+    [1.0, 0.0, 1.0], [-1.0, 0.0, 1.0], [1.0, 0.0, -1.0], [-1.0, 0.0, -1.0],
+    [0.0, 1.0, 1.0], [0.0, -1.0, 1.0], [0.0, 1.0, -1.0], [0.0, -1.0, -1.0],
+];
+
+const GRAD_2D: [[f32; 2]; 8] =
+[
+    [1.0, 0.0], [-1.0, 0.0], [0.0, 1.0], [0.0, -1.0],
+    [FRAC_1_SQRT_2, FRAC_1_SQRT_2], [-FRAC_1_SQRT_2, FRAC_1_SQRT_2],
+    [FRAC_1_SQRT_2, -FRAC_1_SQRT_2], [-FRAC_1_SQRT_2, -FRAC_1_SQRT_2],
+];
+
+fn gradient_2d(corner: IVec2, seed: u32) -> Vec2
+{
+    let [x, y] = GRAD_2D[((corner.hash() ^ seed).hash() % 8) as usize];
+    Vec2::new(x, y)
+}
+
+fn gradient_3d(corner: IVec3, seed: u32) -> Vec3
+{
+    let [x, y, z] = GRAD_3D[((corner.hash() ^ seed).hash() % 12) as usize];
+    Vec3::new(x, y, z)
+}
+
+// the 32 edge midpoints of the tesseract, every permutation of (0, +-1, +-1, +-1)
+fn gradient_4d(corner: [i32; 4], seed: u32) -> [f32; 4]
+{
+    let h = (corner.hash() ^ seed).hash() % 32;
+    let zero = (h >> 3) as usize;
+    let mut gradient = [0.0; 4];
+    let mut bit = 0;
+    for (axis, g) in gradient.iter_mut().enumerate()
+    {
+        if axis != zero
+        {
+            *g = if h >> bit & 1 == 0 { 1.0 } else { -1.0 };
+            bit += 1;
+        }
+    }
+    gradient
+}
+
+// every corner adds (r^2 - d^2)^4 * dot(g, d), the derivative of that falls out of the same terms
+pub fn simplex_2d_derivative(p: Vec2, seed: u32) -> (f32, Vec2)
+{
+    let s = (p.x + p.y) * F2;
+    let i = IVec2::from_vec2(p + s);
+    let t = (i.x + i.y) as f32 * G2;
+    let d0 = p - Vec2::from_ivec2(i) + t;
+
+    let i1 = if d0.x > d0.y { IVec2::new(1, 0) } else { IVec2::new(0, 1) };
+    let corners = [
+        (i, d0),
+        (i + i1, d0 - Vec2::from_ivec2(i1) + G2),
+        (i + IVec2::new(1, 1), d0 + (2.0 * G2 - 1.0)),
+    ];
+
+    let mut value = 0.0;
+    let mut derivative = Vec2::ZERO;
+    for (corner, d) in corners
+    {
+        let t = 0.5 - Vec2::dot(d, d);
+        if t > 0.0
+        {
+            let g = gradient_2d(corner, seed);
+            let n = Vec2::dot(g, d);
+            value += t * t * t * t * n;
+            derivative += g * (t * t * t * t) - d * (8.0 * t * t * t * n);
+        }
+    }
+
+    // scaled to keep the sum of the kernels inside [-1, 1]
+    (value * 95.0, derivative * 95.0)
+}
+
+pub fn simplex_2d(p: Vec2, seed: u32) -> f32
+{
+    simplex_2d_derivative(p, seed).0
+}
+
+pub fn simplex_3d_derivative(p: Vec3, seed: u32) -> (f32, Vec3)
+{
+    let s = (p.x + p.y + p.z) * F3;
+    let i = IVec3::from_vec3(p + s);
+    let t = (i.x + i.y + i.z) as f32 * G3;
+    let d0 = p - Vec3::from_ivec3(i) + t;
+
+    // walk from the origin corner to the far corner along the axes ordered by distance
+    let (i1, i2) = if d0.x >= d0.y
+    {
+        if d0.y >= d0.z { (IVec3::new(1, 0, 0), IVec3::new(1, 1, 0)) }
+        else if d0.x >= d0.z { (IVec3::new(1, 0, 0), IVec3::new(1, 0, 1)) }
+        else { (IVec3::new(0, 0, 1), IVec3::new(1, 0, 1)) }
+    }
+    else if d0.y < d0.z { (IVec3::new(0, 0, 1), IVec3::new(0, 1, 1)) }
+    else if d0.x < d0.z { (IVec3::new(0, 1, 0), IVec3::new(0, 1, 1)) }
+    else { (IVec3::new(0, 1, 0), IVec3::new(1, 1, 0)) };
+
+    let corners = [
+        (i, d0),
+        (i + i1, d0 - Vec3::from_ivec3(i1) + G3),
+        (i + i2, d0 - Vec3::from_ivec3(i2) + 2.0 * G3),
+        (i + IVec3::new(1, 1, 1), d0 - 1.0 + 3.0 * G3),
+    ];
+
+    let mut value = 0.0;
+    let mut derivative = Vec3::ZERO;
+    for (corner, d) in corners
+    {
+        let t = 0.6 - d.dot(d);
+        if t > 0.0
+        {
+            let g = gradient_3d(corner, seed);
+            let n = g.dot(d);
+            value += t * t * t * t * n;
+            derivative += g * (t * t * t * t) - d * (8.0 * t * t * t * n);
+        }
+    }
+
+    (value * 31.0, derivative * 31.0)
+}
+
+pub fn simplex_3d(p: Vec3, seed: u32) -> f32
+{
+    simplex_3d_derivative(p, seed).0
+}
+
+pub fn simplex_4d(p: Vec4, seed: u32) -> f32
+{
+    let p = [p.x, p.y, p.z, p.w];
+    let s = p.iter().sum::<f32>() * F4;
+    let i = p.map(|x| (x + s).floor() as i32);
+    let t = i.iter().sum::<i32>() as f32 * G4;
+    let d0: [f32; 4] = std::array::from_fn(|a| p[a] - (i[a] as f32 - t));
+
+    // an axis steps in once for every other axis it is larger than, which orders the five corners
+    let mut rank = [0; 4];
+    for a in 0..4
+    {
+        for b in a + 1..4
+        {
+            if d0[a] > d0[b] { rank[a] += 1 } else { rank[b] += 1 }
+        }
+    }
+
+    let mut value = 0.0;
+    for k in 0..5
+    {
+        let step: [i32; 4] = rank.map(|r| (r + k >= 4) as i32);
+        let corner: [i32; 4] = std::array::from_fn(|a| i[a] + step[a]);
+        let d: [f32; 4] = std::array::from_fn(|a| d0[a] - step[a] as f32 + k as f32 * G4);
+
+        let t = 0.6 - d.iter().map(|x| x * x).sum::<f32>();
+        if t > 0.0
+        {
+            let g = gradient_4d(corner, seed);
+            let n: f32 = (0..4).map(|a| g[a] * d[a]).sum();
+            value += t * t * t * t * n;
+        }
+    }
+
+    value * 26.0
+}
@@ -0,0 +1,6 @@
+#[cfg(test)] mod test_cellular;
+#[cfg(test)] mod test_derivative;
+#[cfg(test)] mod test_fbm;
+#[cfg(test)] mod test_graph;
+#[cfg(test)] mod test_periodic;
+#[cfg(test)] mod test_simplex;
\ No newline at end of file
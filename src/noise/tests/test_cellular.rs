@@ -0,0 +1,75 @@
+use crate::linalg::prelude::*;
+use crate::noise::{hash::Hash, prelude::*, rand::rand_vec3};
+
+const METRICS: [DistanceMetric; 4] = [DistanceMetric::Euclidean, DistanceMetric::SquaredEuclidean, DistanceMetric::Manhattan, DistanceMetric::Chebyshev];
+
+fn points() -> impl Iterator<Item = Vec3>
+{
+    (0..300).map(|i| rand_vec3(i) * 20.0 - 10.0)
+}
+
+// every feature point in a wide block, sorted by distance
+fn brute_force_3d(p: Vec3, jitter: f32, metric: DistanceMetric, seed: u32) -> Vec<f32>
+{
+    let base = IVec3::from_vec3(p);
+    let mut distances = Vec::new();
+    for x in -3..=3
+    {
+        for y in -3..=3
+        {
+            for z in -3..=3
+            {
+                let cell = base + IVec3::new(x, y, z);
+                let r = rand_vec3(cell.hash() ^ seed);
+                let point = Vec3::from_ivec3(cell) + (r - 0.5) * jitter + 0.5;
+                distances.push(metric.distance_3d(point - p));
+            }
+        }
+    }
+    distances.sort_by(f32::total_cmp);
+    distances
+}
+
+#[test]
+fn test_matches_brute_force()
+{
+    for metric in METRICS
+    {
+        for jitter in [0.3, 1.0]
+        {
+            for p in points()
+            {
+                let cellular = cellular_3d(p, jitter, metric, 9);
+                let expected = brute_force_3d(p, jitter, metric, 9);
+                assert!((cellular.f1 - expected[0]).abs() < 1e-5, "{:?} {:?}", metric, p);
+                assert!((cellular.f2 - expected[1]).abs() < 1e-5, "{:?} {:?}", metric, p);
+                assert!(cellular.edge() >= 0.0);
+                assert!((metric.distance_3d(cellular.point - p) - cellular.f1).abs() < 1e-5);
+
+                let flat = cellular_2d(p.vec2(), jitter, metric, 9);
+                assert!(flat.f1 <= flat.f2);
+                assert!((metric.distance_2d(flat.point - p.vec2()) - flat.f1).abs() < 1e-5);
+            }
+        }
+    }
+}
+
+#[test]
+fn test_cells()
+{
+    // without jitter the feature points sit on the cell centers
+    let center = cellular_2d(Vec2::new(3.5, -1.5), 0.0, DistanceMetric::Euclidean, 4);
+    assert_eq!(center.f1, 0.0);
+    assert_eq!(center.f2, 1.0);
+    assert_eq!(center.point, Vec2::new(3.5, -1.5));
+
+    for p in points()
+    {
+        let cellular = cellular_3d(p, 1.0, DistanceMetric::Euclidean, 4);
+        assert!((cellular.point - voronoi(p, 4)).mag() < 1e-5);
+
+        let nudged = cellular_3d(p + (cellular.point - p) * 0.01, 1.0, DistanceMetric::Euclidean, 4);
+        assert_eq!(nudged.cell_id, cellular.cell_id);
+        assert_ne!(cellular_3d(p, 1.0, DistanceMetric::Euclidean, 5).cell_id, cellular.cell_id);
+    }
+}
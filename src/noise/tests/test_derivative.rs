@@ -0,0 +1,71 @@
+use crate::linalg::prelude::*;
+use crate::noise::prelude::*;
+
+const H: f32 = 1e-3;
+
+fn points() -> impl Iterator<Item = Vec3>
+{
+    (0..100).map(|i| Vec3::new(i as f32 * 0.137 + 0.01, i as f32 * -0.219 + 0.02, i as f32 * 0.071 + 0.03))
+}
+
+fn check_2d(f: impl Fn(Vec2) -> (f32, Vec2), tolerance: f32)
+{
+    for p in points().map(|p| p.vec2())
+    {
+        let (_, d) = f(p);
+        let dx = (f(p + Vec2::X * H).0 - f(p - Vec2::X * H).0) / (2.0 * H);
+        let dy = (f(p + Vec2::Y * H).0 - f(p - Vec2::Y * H).0) / (2.0 * H);
+        assert!((d.x - dx).abs() < tolerance && (d.y - dy).abs() < tolerance, "{:?} {:?} {} {}", p, d, dx, dy);
+    }
+}
+
+fn check_3d(f: impl Fn(Vec3) -> (f32, Vec3), tolerance: f32)
+{
+    for p in points()
+    {
+        let (_, d) = f(p);
+        let axes = [Vec3::new(1.0, 0.0, 0.0), Vec3::new(0.0, 1.0, 0.0), Vec3::new(0.0, 0.0, 1.0)];
+        let numeric: Vec<f32> = axes.iter().map(|&a| (f(p + a * H).0 - f(p - a * H).0) / (2.0 * H)).collect();
+        assert!((d.x - numeric[0]).abs() < tolerance, "{:?} {:?} {:?}", p, d, numeric);
+        assert!((d.y - numeric[1]).abs() < tolerance, "{:?} {:?} {:?}", p, d, numeric);
+        assert!((d.z - numeric[2]).abs() < tolerance, "{:?} {:?} {:?}", p, d, numeric);
+    }
+}
+
+#[test]
+fn test_base_derivatives()
+{
+    check_2d(|p| gradient_noise_2d_derivative(p, 5), 0.02);
+    check_3d(|p| gradient_noise_3d_derivative(p, 5), 0.02);
+    check_3d(|p| value_noise_derivative(p, 5), 0.02);
+    check_2d(|p| simplex_2d_derivative(p, 5), 0.05);
+    check_3d(|p| simplex_3d_derivative(p, 5), 0.05);
+
+    for p in points()
+    {
+        assert_eq!(gradient_noise_2d_derivative(p.vec2(), 9).0, gradient_noise_2d(p.vec2(), 9));
+        assert_eq!(gradient_noise_3d_derivative(p, 9).0, gradient_noise_3d(p, 9));
+        assert_eq!(value_noise_derivative(p, 9).0, value_noise(p, 9));
+    }
+}
+
+#[test]
+fn test_fbm_derivatives()
+{
+    check_2d(|p| perlin_2d_derivative(p, 4, 3), 0.05);
+    check_3d(|p| perlin_3d_derivative(p, 4, 3), 0.05);
+
+    let rotated = Fbm { octaves: 4, rotation: 0.7, lacunarity: 2.1, frequency: 0.8, ..Fbm::new(BaseNoise::Gradient, 3) };
+    check_2d(|p| rotated.sample_2d_derivative(p), 0.05);
+    check_3d(|p| rotated.sample_3d_derivative(p), 0.05);
+
+    let value = Fbm { octaves: 3, ..Fbm::new(BaseNoise::Value, 3) };
+    // value noise is only continuous for positive coordinates
+    check_3d(|p| value.sample_3d_derivative(p + Vec3::all(40.0)), 0.05);
+
+    for p in points()
+    {
+        assert_eq!(perlin_3d_derivative(p, 4, 3).0, perlin_3d(p, 4, 3));
+        assert_eq!(rotated.sample_2d_derivative(p.vec2()).0, rotated.sample_2d(p.vec2()));
+    }
+}
@@ -0,0 +1,60 @@
+use crate::linalg::prelude::*;
+use crate::noise::prelude::*;
+
+fn samples() -> impl Iterator<Item = Vec3>
+{
+    (0..200).map(|i| Vec3::new(i as f32 * 0.173, i as f32 * 0.291 - 7.0, i as f32 * 0.057 + 3.0))
+}
+
+#[test]
+fn test_perlin_matches_fbm_loop()
+{
+    for p in samples()
+    {
+        let mut acc = 0.0;
+        let mut contrib = 0.5;
+        let mut q = p;
+        for _ in 0..5
+        {
+            acc += gradient_noise_3d(q, 7) * contrib;
+            contrib *= 0.5;
+            q *= 2.0;
+        }
+        assert_eq!(perlin_3d(p, 5, 7), acc);
+    }
+}
+
+#[test]
+fn test_variants_and_ranges()
+{
+    for base in [BaseNoise::Gradient, BaseNoise::Value, BaseNoise::Voronoi, BaseNoise::Simplex]
+    {
+        let standard = Fbm { rotation: 0.6, ..Fbm::new(base, 3) };
+        let ridged = Fbm::ridged(base, 3);
+        let turbulence = Fbm::turbulence(base, 3);
+        let billow = Fbm::billow(base, 3);
+
+        for p in samples()
+        {
+            assert!(standard.sample_3d(p).abs() <= 1.0);
+            assert!(standard.sample_2d(p.vec2()).abs() <= 1.0);
+            assert!(ridged.sample_3d(p) >= 0.0);
+            assert!(turbulence.sample_3d(p) >= 0.0);
+            assert!(billow.sample_2d(p.vec2()).abs() <= 1.0);
+        }
+    }
+}
+
+#[test]
+fn test_parameters()
+{
+    let p = Vec2::new(1.3, -2.7);
+    let fbm = Fbm::new(BaseNoise::Gradient, 11);
+
+    assert_eq!(Fbm { octaves: 0, ..fbm }.sample_2d(p), 0.0);
+    assert_eq!(Fbm { octaves: 1, amplitude: 1.0, ..fbm }.sample_2d(p), gradient_noise_2d(p, 11));
+    assert_eq!(Fbm { octaves: 1, frequency: 3.0, ..fbm }.sample_2d(p), gradient_noise_2d(p * 3.0, 11) * 0.5);
+    assert_eq!(Fbm { amplitude: 2.0, ..fbm }.sample_2d(p), fbm.sample_2d(p) * 4.0);
+    assert_ne!(Fbm { seed: 12, ..fbm }.sample_2d(p), fbm.sample_2d(p));
+    assert_ne!(Fbm { rotation: 0.5, ..fbm }.sample_2d(p), fbm.sample_2d(p));
+}
@@ -0,0 +1,159 @@
+use serde::{Serialize, Deserialize};
+
+use crate::linalg::prelude::*;
+use crate::noise::prelude::*;
+
+fn samples() -> impl Iterator<Item = Vec3>
+{
+    (0..100).map(|i| Vec3::new(i as f32 * 0.173 + 0.5, i as f32 * 0.291 + 2.0, i as f32 * 0.057 + 3.0))
+}
+
+#[test]
+fn test_leaves_match_functions()
+{
+    let gradient = NoiseGraph::from(GradientNoise { seed: 3, period: None });
+    let periodic = NoiseGraph::from(GradientNoise { seed: 3, period: Some(IVec3::new(4, 4, 4)) });
+    let value = NoiseGraph::from(ValueNoise { seed: 3, period: None });
+    let simplex = NoiseGraph::from(SimplexNoise { seed: 3 });
+    let perlin = NoiseGraph::from(PerlinNoise { octaves: 4, seed: 3 });
+    let fbm = NoiseGraph::from(Fbm::new(BaseNoise::Simplex, 3));
+    let cellular = CellularNoise { seed: 3, ..Default::default() };
+
+    for p in samples()
+    {
+        assert_eq!(gradient.sample_3d(p), gradient_noise_3d(p, 3));
+        assert_eq!(gradient.sample_2d(p.vec2()), gradient_noise_2d(p.vec2(), 3));
+        assert_eq!(periodic.sample_3d(p), gradient_noise_3d_periodic(p, IVec3::new(4, 4, 4), 3));
+        assert_eq!(value.sample_3d(p), value_noise(p, 3));
+        assert_eq!(simplex.sample_2d(p.vec2()), simplex_2d(p.vec2(), 3));
+        assert_eq!(perlin.sample_3d(p), perlin_3d(p, 4, 3));
+        assert_eq!(fbm.sample_3d(p), Fbm::new(BaseNoise::Simplex, 3).sample_3d(p));
+
+        let c = cellular_3d(p, 1.0, DistanceMetric::Euclidean, 3);
+        assert_eq!(NoiseGraph::from(cellular).sample_3d(p), c.f1);
+        assert_eq!(NoiseGraph::from(CellularNoise { output: CellularOutput::Edge, ..cellular }).sample_3d(p), c.edge());
+    }
+}
+
+#[test]
+fn test_combinators()
+{
+    let a = NoiseGraph::from(GradientNoise { seed: 1, period: None });
+    let b = NoiseGraph::from(SimplexNoise { seed: 2 });
+
+    for p in samples()
+    {
+        let (x, y) = (a.sample_3d(p), b.sample_3d(p));
+        assert_eq!((a.clone() + b.clone()).sample_3d(p), x + y);
+        assert_eq!((a.clone() * b.clone()).sample_3d(p), x * y);
+        assert_eq!(a.clone().min(b.clone()).sample_3d(p), x.min(y));
+        assert_eq!(a.clone().max(0.25).sample_3d(p), x.max(0.25));
+        assert_eq!(a.clone().clamp(-0.1, 0.1).sample_2d(p.vec2()), a.sample_2d(p.vec2()).clamp(-0.1, 0.1));
+        assert_eq!(a.clone().remap(-1.0, 1.0, 0.0, 1.0).sample_3d(p), fit(x, -1.0, 1.0, 0.0, 1.0));
+    }
+}
+
+#[test]
+fn test_domain()
+{
+    let source = NoiseGraph::from(GradientNoise { seed: 5, period: None });
+    let rotation = Quat::angle_axis(0.7, Vec3::new(0.0, 0.0, 1.0));
+    let scale = Vec3::new(2.0, 3.0, 0.5);
+    let offset = Vec3::new(1.5, -0.5, 4.0);
+
+    for p in samples()
+    {
+        assert_eq!(source.clone().scale(scale).sample_3d(p), source.sample_3d(p.mul_comp(scale)));
+        assert_eq!(source.clone().translate(offset).sample_3d(p), source.sample_3d(p + offset));
+        assert_eq!(source.clone().translate(offset * Vec3::new(1.0, 1.0, 0.0)).sample_2d(p.vec2()), source.sample_2d(p.vec2() + offset.vec2()));
+        assert_eq!(source.clone().rotate(rotation).sample_3d(p), source.sample_3d(rotation * p));
+        assert_eq!(source.clone().transform(Mat3::IDENTITY).sample_3d(p), source.sample_3d(p));
+
+        let warp = NoiseGraph::from(SimplexNoise { seed: 9 });
+        assert_eq!(source.clone().warp(warp.clone(), 0.0).sample_3d(p), source.sample_3d(p));
+        assert_eq!(source.clone().warp(warp.clone(), 0.0).sample_2d(p.vec2()), source.sample_2d(p.vec2()));
+        assert_eq!(source.clone().warp(0.5, 2.0).sample_3d(p), source.sample_3d(p + 1.0));
+    }
+}
+
+fn serde<T: Serialize + for<'de> Deserialize<'de>>() {}
+
+#[test]
+fn test_serializable()
+{
+    serde::<NoiseGraph>();
+    serde::<CellularNoise>();
+    serde::<Fbm>();
+}
+
+fn leaves() -> Vec<NoiseGraph>
+{
+    vec![
+        NoiseGraph::Constant(0.25),
+        GradientNoise { seed: 4, period: None }.into(),
+        ValueNoise { seed: 4, period: None }.into(),
+        VoronoiNoise { seed: 4, period: None }.into(),
+        SimplexNoise { seed: 4 }.into(),
+        PerlinNoise { octaves: 3, seed: 4 }.into(),
+        CellularNoise { seed: 4, ..Default::default() }.into(),
+        Fbm { rotation: 0.5, ..Fbm::new(BaseNoise::Simplex, 4) }.into(),
+    ]
+}
+
+#[test]
+fn test_identity_domain_keeps_2d()
+{
+    for leaf in leaves()
+    {
+        let graphs = [
+            leaf.clone().rotate(Quat::IDENTITY),
+            leaf.clone().transform(Mat3::IDENTITY),
+            leaf.clone().translate(Vec3::ZERO),
+            leaf.clone().scale(Vec3::ONE),
+            leaf.clone().warp(SimplexNoise { seed: 2 }, 0.0),
+        ];
+
+        for p in samples()
+        {
+            for graph in &graphs
+            {
+                assert_eq!(graph.sample_2d(p.vec2()), leaf.sample_2d(p.vec2()));
+                assert_eq!(graph.sample_3d(p), leaf.sample_3d(p));
+            }
+
+            // z offsets only move 3d samples
+            assert_eq!(leaf.clone().translate(Vec3::new(0.0, 0.0, 1e-6)).sample_2d(p.vec2()), leaf.sample_2d(p.vec2()));
+        }
+    }
+}
+
+#[test]
+fn test_2d_domain_projection()
+{
+    let source = NoiseGraph::from(SimplexNoise { seed: 4 });
+    let warp = NoiseGraph::from(GradientNoise { seed: 2, period: None });
+    let rotation = Quat::angle_axis(0.9, Vec3::new(1.0, 2.0, 0.5));
+
+    for p in samples()
+    {
+        let p = p.vec2();
+        let rotated = (rotation * Vec3::from(p)).vec2();
+        assert_eq!(source.clone().rotate(rotation).sample_2d(p), source.sample_2d(rotated));
+        assert_eq!(source.clone().translate(Vec3::new(0.5, 1.5, 2.5)).sample_2d(p), source.sample_2d(p + Vec2::new(0.5, 1.5)));
+
+        let offset = Vec2::new(warp.sample_2d(p), warp.sample_2d(p + Vec2::new(31.7, -17.3)));
+        assert_eq!(source.clone().warp(warp.clone(), 0.75).sample_2d(p), source.sample_2d(p + offset * 0.75));
+    }
+}
+
+#[test]
+fn test_bad_presets_do_not_panic()
+{
+    let source = NoiseGraph::from(GradientNoise { seed: 1, period: None });
+    let p = Vec3::new(0.3, 0.6, 0.9);
+
+    assert_eq!(source.clone().clamp(1.0, -1.0).sample_3d(p), -1.0);
+    assert_eq!(source.clone().clamp(f32::NAN, f32::NAN).sample_3d(p), source.sample_3d(p));
+    assert_eq!(source.clone().clamp(f32::NAN, -0.5).sample_2d(p.vec2()), source.sample_2d(p.vec2()).min(-0.5));
+    assert_ne!(NoiseGraph::from(PerlinNoise::default()).sample_3d(p), 0.0);
+}
@@ -0,0 +1,84 @@
+use crate::linalg::prelude::*;
+use crate::noise::prelude::*;
+
+fn points() -> impl Iterator<Item = Vec3>
+{
+    (0..100).map(|i| Vec3::new(i as f32 * 0.137 % 5.0, i as f32 * 0.219 % 3.0, i as f32 * 0.071 % 4.0))
+}
+
+#[test]
+fn test_periodic_noise_tiles()
+{
+    let period = IVec3::new(5, 3, 4);
+    let shift = Vec3::new(5.0, -3.0, 8.0);
+
+    for p in points()
+    {
+        let q = p + shift;
+        assert!((gradient_noise_3d_periodic(p, period, 7) - gradient_noise_3d_periodic(q, period, 7)).abs() < 1e-4);
+        assert!((gradient_noise_2d_periodic(p.vec2(), IVec2::new(5, 3), 7) - gradient_noise_2d_periodic(q.vec2(), IVec2::new(5, 3), 7)).abs() < 1e-4);
+        assert!((value_noise_periodic(p, period, 7) - value_noise_periodic(p + Vec3::new(10.0, 6.0, 4.0), period, 7)).abs() < 1e-4);
+        assert!((perlin_2d_periodic(p.vec2(), IVec2::new(5, 3), 4, 7) - perlin_2d_periodic(q.vec2(), IVec2::new(5, 3), 4, 7)).abs() < 1e-4);
+
+        let near = voronoi_periodic(p, period, 7);
+        let far = voronoi_periodic(q, period, 7);
+        assert!((near + shift - far).mag() < 1e-4);
+    }
+}
+
+#[test]
+fn test_periodic_fbm_tiles()
+{
+    // value noise is only continuous for positive coordinates, so the shift stays positive
+    let period = IVec3::new(5, 3, 4);
+    let shift = Vec3::new(5.0, 6.0, 4.0);
+
+    for base in [BaseNoise::Gradient, BaseNoise::Value, BaseNoise::Voronoi]
+    {
+        for kind in [FbmKind::Standard, FbmKind::Ridged]
+        {
+            let fbm = Fbm { kind, octaves: 4, frequency: 2.0, lacunarity: 3.0, period: Some(period), ..Fbm::new(base, 7) };
+            for p in points()
+            {
+                let q = p + shift;
+                assert!((fbm.sample_3d(p) - fbm.sample_3d(q)).abs() < 1e-3);
+                assert!((fbm.sample_2d(p.vec2()) - fbm.sample_2d(q.vec2())).abs() < 1e-3);
+                let (dp, dq) = (fbm.sample_3d_derivative(p).1, fbm.sample_3d_derivative(q).1);
+                assert!((dp - dq).mag() <= 1e-3 * (1.0 + dp.mag()));
+            }
+        }
+    }
+
+    for p in points()
+    {
+        assert!((perlin_3d_periodic(p, period, 4, 7) - perlin_3d_periodic(p + shift, period, 4, 7)).abs() < 1e-4);
+    }
+    assert!(perlin_2d_periodic(Vec2::new(0.3, 0.7), IVec2::new(5, 3), 40, 7).is_finite());
+}
+
+#[test]
+fn test_periodic_noise_matches_inside_period()
+{
+    // inside the first period the lattice is not wrapped yet, away from the far border
+    let p = Vec3::new(1.3, 0.4, 2.2);
+    let period = IVec3::new(8, 8, 8);
+    assert_eq!(gradient_noise_3d_periodic(p, period, 3), gradient_noise_3d(p, 3));
+    assert_eq!(value_noise_periodic(p, period, 3), value_noise(p, 3));
+    assert_eq!(voronoi_periodic(p, period, 3), voronoi(p, 3));
+}
+
+#[test]
+fn test_torus_loops()
+{
+    for i in 0..50
+    {
+        let uv = Vec2::new(i as f32 * 0.0173, i as f32 * 0.0291);
+        let a = simplex_4d(torus_4d(uv, 6.0), 2);
+        let b = simplex_4d(torus_4d(uv + Vec2::new(1.0, -2.0), 6.0), 2);
+        assert!((a - b).abs() < 1e-4);
+    }
+
+    let edge = simplex_4d(torus_4d(Vec2::new(0.9999, 0.5), 6.0), 2);
+    let start = simplex_4d(torus_4d(Vec2::new(0.0, 0.5), 6.0), 2);
+    assert!((edge - start).abs() < 0.01);
+}
@@ -0,0 +1,68 @@
+use crate::linalg::prelude::*;
+use crate::noise::{prelude::*, rand::rand};
+
+fn coordinate(i: u32, axis: u32) -> f32
+{
+    rand(i * 4 + axis) * 200.0 - 100.0
+}
+
+#[test]
+fn test_output_range()
+{
+    let mut extremes = [0.0f32; 3];
+    for i in 0..20000
+    {
+        let v = Vec4 { x: coordinate(i, 0), y: coordinate(i, 1), z: coordinate(i, 2), w: coordinate(i, 3) };
+        let values = [
+            simplex_2d(Vec2::new(v.x, v.y), i % 5),
+            simplex_3d(Vec3::new(v.x, v.y, v.z), i % 5),
+            simplex_4d(v, i % 5),
+        ];
+        for (extreme, value) in extremes.iter_mut().zip(values)
+        {
+            assert!((-1.0..=1.0).contains(&value));
+            *extreme = extreme.max(value.abs());
+        }
+    }
+
+    // the scaling should use most of the range, not just stay inside it
+    for extreme in extremes
+    {
+        assert!(extreme > 0.6, "{}", extreme);
+    }
+}
+
+#[test]
+fn test_seeds()
+{
+    let mut differing = [0; 3];
+    for i in 0..200
+    {
+        let v = Vec4 { x: coordinate(i, 0), y: coordinate(i, 1), z: coordinate(i, 2), w: coordinate(i, 3) };
+        let p2 = Vec2::new(v.x, v.y);
+        let p3 = Vec3::new(v.x, v.y, v.z);
+
+        assert_eq!(simplex_2d(p2, 17), simplex_2d(p2, 17));
+        assert_eq!(simplex_3d(p3, 17), simplex_3d(p3, 17));
+        assert_eq!(simplex_4d(v, 17), simplex_4d(v, 17));
+
+        differing[0] += (simplex_2d(p2, 17) != simplex_2d(p2, 18)) as usize;
+        differing[1] += (simplex_3d(p3, 17) != simplex_3d(p3, 18)) as usize;
+        differing[2] += (simplex_4d(v, 17) != simplex_4d(v, 18)) as usize;
+    }
+    assert!(differing.iter().all(|&n| n > 190), "{:?}", differing);
+}
+
+#[test]
+fn test_continuity()
+{
+    // the kernels fall to zero at their radius so small steps never jump
+    for i in 0..2000
+    {
+        let v = Vec4 { x: coordinate(i, 0), y: coordinate(i, 1), z: coordinate(i, 2), w: coordinate(i, 3) };
+        let step = Vec4 { x: 1e-3, y: 1e-3, z: 1e-3, w: 1e-3 };
+        let moved = Vec4 { x: v.x + step.x, y: v.y + step.y, z: v.z + step.z, w: v.w + step.w };
+        assert!((simplex_4d(v, 3) - simplex_4d(moved, 3)).abs() < 0.05);
+        assert!((simplex_3d(Vec3::new(v.x, v.y, v.z), 3) - simplex_3d(Vec3::new(moved.x, moved.y, moved.z), 3)).abs() < 0.05);
+    }
+}
@@ -1,22 +1,22 @@
 use crate::linalg::prelude::*;
 
-use super::{convert::float_construct, hash::Hash, smoothstep::smoothstep};
+use super::{convert::float_construct, hash::Hash, periodic::wrap_ivec3, smoothstep::smoothstep};
 
-pub fn value_noise(p: Vec3, seed: u32) -> f32
+fn value_3d(p: Vec3, seed: u32, wrap: impl Fn(IVec3) -> IVec3) -> f32
 {
     let cell = IVec3::from_vec3(p);
     let w = p.fract_comp();
 
     let su = (seed ^ 73145542u32).hash();
     
-    let x000 = float_construct((cell + IVec3::new(0,0,0)).hash() ^ su);
-    let x001 = float_construct((cell + IVec3::new(0,0,1)).hash() ^ su);
-    let x010 = float_construct((cell + IVec3::new(0,1,0)).hash() ^ su);
-    let x011 = float_construct((cell + IVec3::new(0,1,1)).hash() ^ su);
-    let x100 = float_construct((cell + IVec3::new(1,0,0)).hash() ^ su);
-    let x101 = float_construct((cell + IVec3::new(1,0,1)).hash() ^ su);
-    let x110 = float_construct((cell + IVec3::new(1,1,0)).hash() ^ su);
-    let x111 = float_construct((cell + IVec3::new(1,1,1)).hash() ^ su);
+    let x000 = float_construct(wrap(cell + IVec3::new(0,0,0)).hash() ^ su);
+    let x001 = float_construct(wrap(cell + IVec3::new(0,0,1)).hash() ^ su);
+    let x010 = float_construct(wrap(cell + IVec3::new(0,1,0)).hash() ^ su);
+    let x011 = float_construct(wrap(cell + IVec3::new(0,1,1)).hash() ^ su);
+    let x100 = float_construct(wrap(cell + IVec3::new(1,0,0)).hash() ^ su);
+    let x101 = float_construct(wrap(cell + IVec3::new(1,0,1)).hash() ^ su);
+    let x110 = float_construct(wrap(cell + IVec3::new(1,1,0)).hash() ^ su);
+    let x111 = float_construct(wrap(cell + IVec3::new(1,1,1)).hash() ^ su);
 
     let wx = smoothstep(0.0, 1.0, w.x);
     let wy = smoothstep(0.0, 1.0, w.y);
@@ -34,3 +34,63 @@ pub fn value_noise(p: Vec3, seed: u32) -> f32
     
     return x;
 }
+
+fn value_3d_derivative(p: Vec3, seed: u32, wrap: impl Fn(IVec3) -> IVec3) -> (f32, Vec3)
+{
+    let cell = IVec3::from_vec3(p);
+    let w = p.fract_comp();
+
+    let su = (seed ^ 73145542u32).hash();
+
+    let x000 = float_construct(wrap(cell + IVec3::new(0,0,0)).hash() ^ su);
+    let x001 = float_construct(wrap(cell + IVec3::new(0,0,1)).hash() ^ su);
+    let x010 = float_construct(wrap(cell + IVec3::new(0,1,0)).hash() ^ su);
+    let x011 = float_construct(wrap(cell + IVec3::new(0,1,1)).hash() ^ su);
+    let x100 = float_construct(wrap(cell + IVec3::new(1,0,0)).hash() ^ su);
+    let x101 = float_construct(wrap(cell + IVec3::new(1,0,1)).hash() ^ su);
+    let x110 = float_construct(wrap(cell + IVec3::new(1,1,0)).hash() ^ su);
+    let x111 = float_construct(wrap(cell + IVec3::new(1,1,1)).hash() ^ su);
+
+    let wx = smoothstep(0.0, 1.0, w.x);
+    let wy = smoothstep(0.0, 1.0, w.y);
+    let wz = smoothstep(0.0, 1.0, w.z);
+    // fract keeps the sign of negative coordinates, the smoothstep clamp then flattens those axes
+    let t = w.max(Vec3::ZERO).min(Vec3::ONE);
+    let dw = t * (Vec3::ONE - t) * 6.0;
+
+    let x00 = lerp(x000, x001, wz);
+    let x01 = lerp(x010, x011, wz);
+    let x10 = lerp(x100, x101, wz);
+    let x11 = lerp(x110, x111, wz);
+
+    let x0 = lerp(x00, x01, wy);
+    let x1 = lerp(x10, x11, wy);
+
+    let slope = Vec3::new(
+        x1 - x0,
+        lerp(x01 - x00, x11 - x10, wx),
+        lerp(lerp(x001 - x000, x011 - x010, wy), lerp(x101 - x100, x111 - x110, wy), wx),
+    );
+
+    (lerp(x0, x1, wx), dw * slope)
+}
+
+pub fn value_noise(p: Vec3, seed: u32) -> f32
+{
+    value_3d(p, seed, |c| c)
+}
+
+pub fn value_noise_derivative(p: Vec3, seed: u32) -> (f32, Vec3)
+{
+    value_3d_derivative(p, seed, |c| c)
+}
+
+pub fn value_noise_periodic(p: Vec3, period: IVec3, seed: u32) -> f32
+{
+    value_3d(p, seed, |c| wrap_ivec3(c, period))
+}
+
+pub fn value_noise_periodic_derivative(p: Vec3, period: IVec3, seed: u32) -> (f32, Vec3)
+{
+    value_3d_derivative(p, seed, |c| wrap_ivec3(c, period))
+}
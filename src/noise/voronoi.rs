@@ -1,8 +1,8 @@
 use crate::linalg::prelude::*;
 
-use super::{rand::rand_vec3, hash::Hash};
+use super::{rand::rand_vec3, hash::Hash, periodic::wrap_ivec3};
 
-pub fn voronoi(p: Vec3, seed: u32) -> Vec3
+fn voronoi_3d(p: Vec3, seed: u32, wrap: impl Fn(IVec3) -> IVec3) -> Vec3
 {
     let base = IVec3::from_vec3(p);
     
@@ -25,7 +25,7 @@ pub fn voronoi(p: Vec3, seed: u32) -> Vec3
     
     for i in 0..27
     {
-        r[i] = Vec3::from_ivec3(b[i]) + rand_vec3(b[i].hash() ^ seed);
+        r[i] = Vec3::from_ivec3(b[i]) + rand_vec3(wrap(b[i]).hash() ^ seed);
     }
     
     let mut closesed_point = r[0];
@@ -43,3 +43,14 @@ pub fn voronoi(p: Vec3, seed: u32) -> Vec3
     
     return closesed_point;
 }
+
+pub fn voronoi(p: Vec3, seed: u32) -> Vec3
+{
+    voronoi_3d(p, seed, |c| c)
+}
+
+// the feature point of a wrapped cell is placed in the unwrapped cell so distances stay continuous across the seam
+pub fn voronoi_periodic(p: Vec3, period: IVec3, seed: u32) -> Vec3
+{
+    voronoi_3d(p, seed, |c| wrap_ivec3(c, period))
+}
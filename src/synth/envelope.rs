@@ -0,0 +1,101 @@
+use serde::{Serialize, Deserialize};
+
+#[derive(Debug, Copy, Clone, PartialEq, Default, Serialize, Deserialize)]
+#[repr(C)]
+pub struct Adsr
+{
+    pub attack: f32,
+    pub decay: f32,
+    pub sustain: f32,
+    pub release: f32,
+}
+
+impl Adsr
+{
+    pub fn new(attack: f32, decay: f32, sustain: f32, release: f32) -> Adsr
+    {
+        Adsr { attack, decay, sustain, release }
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, Default, Serialize, Deserialize)]
+#[repr(C)]
+pub enum EnvelopeStage
+{
+    #[default]
+    Attack,
+    Decay,
+    Sustain,
+    Release,
+    Finished,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Default, Serialize, Deserialize)]
+#[repr(C)]
+pub struct Envelope
+{
+    pub adsr: Adsr,
+    pub stage: EnvelopeStage,
+    pub level: f32,
+    release_rate: f32,
+}
+
+impl Envelope
+{
+    pub fn new(adsr: Adsr) -> Envelope
+    {
+        Envelope { adsr, stage: EnvelopeStage::Attack, level: 0.0, release_rate: 0.0 }
+    }
+
+    pub fn release(&mut self)
+    {
+        if self.stage != EnvelopeStage::Finished
+        {
+            self.stage = EnvelopeStage::Release;
+            self.release_rate = if self.adsr.release > 0.0 { self.level / self.adsr.release } else { f32::INFINITY };
+        }
+    }
+
+    pub fn is_finished(&self) -> bool
+    {
+        self.stage == EnvelopeStage::Finished
+    }
+
+    pub fn next(&mut self, dt: f32) -> f32
+    {
+        let sustain = self.adsr.sustain.clamp(0.0, 1.0);
+        match self.stage
+        {
+            EnvelopeStage::Attack =>
+            {
+                self.level = if self.adsr.attack > 0.0 { self.level + dt / self.adsr.attack } else { 1.0 };
+                if self.level >= 1.0
+                {
+                    self.level = 1.0;
+                    self.stage = EnvelopeStage::Decay;
+                }
+            }
+            EnvelopeStage::Decay =>
+            {
+                self.level = if self.adsr.decay > 0.0 { self.level - dt * (1.0 - sustain) / self.adsr.decay } else { sustain };
+                if self.level <= sustain
+                {
+                    self.level = sustain;
+                    self.stage = if sustain > 0.0 { EnvelopeStage::Sustain } else { EnvelopeStage::Finished };
+                }
+            }
+            EnvelopeStage::Sustain => self.level = sustain,
+            EnvelopeStage::Release =>
+            {
+                self.level -= dt * self.release_rate;
+                if self.level <= 0.0
+                {
+                    self.level = 0.0;
+                    self.stage = EnvelopeStage::Finished;
+                }
+            }
+            EnvelopeStage::Finished => self.level = 0.0,
+        }
+        self.level
+    }
+}
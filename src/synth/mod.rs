@@ -0,0 +1,11 @@
+pub mod envelope;
+pub mod oscillator;
+pub mod patch;
+pub mod prelude;
+pub mod render;
+pub mod synthesizer;
+pub mod voice;
+pub mod wav;
+pub mod wav_error;
+
+#[cfg(test)] mod tests;
\ No newline at end of file
@@ -0,0 +1,54 @@
+use std::f32::consts::TAU;
+
+use serde::{Serialize, Deserialize};
+
+use crate::noise::rand::rand;
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, Default, Serialize, Deserialize)]
+#[repr(C)]
+pub enum Waveform
+{
+    #[default]
+    Sine,
+    Saw,
+    Square,
+    Triangle,
+    Noise,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Default, Serialize, Deserialize)]
+#[repr(C)]
+pub struct Oscillator
+{
+    pub waveform: Waveform,
+    pub phase: f32,
+    pub seed: u32,
+}
+
+impl Oscillator
+{
+    pub fn new(waveform: Waveform, seed: u32) -> Oscillator
+    {
+        Oscillator { waveform, phase: 0.0, seed }
+    }
+
+    pub fn sample(&self) -> f32
+    {
+        match self.waveform
+        {
+            Waveform::Sine => (self.phase * TAU).sin(),
+            Waveform::Saw => 2.0 * self.phase - 1.0,
+            Waveform::Square => if self.phase < 0.5 { 1.0 } else { -1.0 },
+            Waveform::Triangle => 1.0 - 4.0 * (self.phase - 0.5).abs(),
+            Waveform::Noise => rand(self.seed) * 2.0 - 1.0,
+        }
+    }
+
+    pub fn next(&mut self, frequency: f32, sample_rate: f32) -> f32
+    {
+        let sample = self.sample();
+        self.phase = (self.phase + frequency / sample_rate).fract();
+        self.seed = self.seed.wrapping_add(1);
+        sample
+    }
+}
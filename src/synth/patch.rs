@@ -0,0 +1,47 @@
+use serde::{Serialize, Deserialize};
+
+use super::{envelope::Adsr, oscillator::Waveform};
+
+#[derive(Debug, Copy, Clone, PartialEq, Default, Serialize, Deserialize)]
+#[repr(C)]
+pub struct Patch
+{
+    pub waveform: Waveform,
+    pub envelope: Adsr,
+    pub gain: f32,
+}
+
+impl Patch
+{
+    pub fn new(waveform: Waveform, envelope: Adsr, gain: f32) -> Patch
+    {
+        Patch { waveform, envelope, gain }
+    }
+
+    // one rough timbre per General MIDI instrument family
+    pub fn from_program(program: u8) -> Patch
+    {
+        match program & 0x7F
+        {
+            0..=7 => Patch::new(Waveform::Triangle, Adsr::new(0.005, 1.5, 0.0, 0.3), 1.0),
+            8..=15 => Patch::new(Waveform::Sine, Adsr::new(0.001, 0.6, 0.0, 0.2), 1.0),
+            16..=23 => Patch::new(Waveform::Square, Adsr::new(0.01, 0.0, 1.0, 0.05), 0.4),
+            24..=31 => Patch::new(Waveform::Saw, Adsr::new(0.002, 0.8, 0.0, 0.15), 0.6),
+            32..=39 => Patch::new(Waveform::Triangle, Adsr::new(0.005, 0.4, 0.6, 0.1), 1.0),
+            40..=55 => Patch::new(Waveform::Saw, Adsr::new(0.15, 0.2, 0.8, 0.4), 0.5),
+            56..=63 => Patch::new(Waveform::Saw, Adsr::new(0.04, 0.1, 0.8, 0.15), 0.5),
+            64..=79 => Patch::new(Waveform::Sine, Adsr::new(0.03, 0.1, 0.9, 0.1), 0.9),
+            80..=87 => Patch::new(Waveform::Square, Adsr::new(0.01, 0.1, 0.8, 0.1), 0.4),
+            88..=95 => Patch::new(Waveform::Saw, Adsr::new(0.5, 0.5, 0.7, 1.0), 0.4),
+            96..=103 => Patch::new(Waveform::Sine, Adsr::new(0.2, 0.5, 0.6, 0.8), 0.8),
+            104..=111 => Patch::new(Waveform::Saw, Adsr::new(0.002, 0.5, 0.0, 0.2), 0.6),
+            112..=119 => Patch::new(Waveform::Sine, Adsr::new(0.001, 0.2, 0.0, 0.1), 1.0),
+            _ => Patch::new(Waveform::Noise, Adsr::new(0.05, 0.3, 0.5, 0.5), 0.3),
+        }
+    }
+
+    pub fn percussion() -> Patch
+    {
+        Patch::new(Waveform::Noise, Adsr::new(0.001, 0.15, 0.0, 0.05), 0.5)
+    }
+}
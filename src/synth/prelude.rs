@@ -0,0 +1,11 @@
+pub use super::
+{
+    envelope::{Adsr, Envelope, EnvelopeStage},
+    oscillator::{Oscillator, Waveform},
+    patch::Patch,
+    render::render_song,
+    synthesizer::Synth,
+    voice::SynthVoice,
+    wav::{load_wav_file, read_wav, save_wav_file, write_wav, SampleFormat, Wav, WavSpec},
+    wav_error::WavError,
+};
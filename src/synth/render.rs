@@ -0,0 +1,42 @@
+use crate::midi::{midi_song::MidiSong, play::MidiPlayer};
+
+use super::synthesizer::Synth;
+
+fn render_frames(synth: &mut Synth, buffer: &mut Vec<f32>, frames: usize, channels: usize)
+{
+    let start = buffer.len();
+    buffer.resize(start + frames * channels, 0.0);
+    synth.render(&mut buffer[start..], channels);
+}
+
+// events land on the sample they fall on, the release tails are rendered for at most tail_seconds after the song ends
+pub fn render_song(song: &MidiSong, synth: &mut Synth, channels: usize, tail_seconds: f32) -> Vec<f32>
+{
+    let channels = channels.max(1);
+    let mut buffer = Vec::new();
+    let mut player = MidiPlayer::new(song);
+    let mut frame = 0;
+
+    while let Some(seconds) = player.next_event_seconds(song)
+    {
+        let event_frame = (seconds * synth.sample_rate as f64).round() as usize;
+        if event_frame > frame
+        {
+            render_frames(synth, &mut buffer, event_frame - frame, channels);
+            frame = event_frame;
+        }
+        player.advance_to_seconds(seconds, true, song, synth);
+    }
+
+    let block = (synth.sample_rate as usize / 100).max(1);
+    let tail_frames = (tail_seconds.max(0.0) * synth.sample_rate) as usize;
+    let mut rendered = 0;
+    while !synth.is_silent() && rendered < tail_frames
+    {
+        let frames = block.min(tail_frames - rendered);
+        render_frames(synth, &mut buffer, frames, channels);
+        rendered += frames;
+    }
+
+    buffer
+}
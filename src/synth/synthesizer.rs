@@ -0,0 +1,191 @@
+use std::f32::consts::FRAC_PI_2;
+
+use serde::{Serialize, Deserialize};
+
+use crate::{midi::{controllers::ChannelControllers, event::{MidiEvent, PERCUSSION_CHANNEL}, util::mtof}, pipe::Pipe};
+
+use super::{patch::Patch, voice::SynthVoice};
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[repr(C)]
+pub struct Synth
+{
+    pub sample_rate: f32,
+    pub max_voices: usize,
+    pub gain: f32,
+    pub patches: Vec<Patch>,
+    pub percussion_patch: Patch,
+    controllers: ChannelControllers,
+    programs: [u8; 16],
+    voices: Vec<SynthVoice>,
+    seed: u32,
+}
+
+impl Synth
+{
+    pub fn new(sample_rate: f32) -> Synth
+    {
+        Synth
+        {
+            sample_rate,
+            max_voices: 64,
+            gain: 0.25,
+            patches: (0..128).map(Patch::from_program).collect(),
+            percussion_patch: Patch::percussion(),
+            controllers: ChannelControllers::new(),
+            programs: [0; 16],
+            voices: Vec::new(),
+            seed: 0,
+        }
+    }
+
+    pub fn voices(&self) -> &[SynthVoice]
+    {
+        &self.voices
+    }
+
+    pub fn is_silent(&self) -> bool
+    {
+        self.voices.is_empty()
+    }
+
+    pub fn controllers(&self) -> &ChannelControllers
+    {
+        &self.controllers
+    }
+
+    pub fn program(&self, channel: u8) -> u8
+    {
+        self.programs[channel as usize & 0xF]
+    }
+
+    pub fn reset(&mut self)
+    {
+        self.controllers.reset();
+        self.programs = [0; 16];
+        self.voices.clear();
+    }
+
+    fn patch(&self, channel: u8) -> Patch
+    {
+        if channel == PERCUSSION_CHANNEL
+        {
+            return self.percussion_patch;
+        }
+        self.patches.get(self.program(channel) as usize).copied().unwrap_or_default()
+    }
+
+    fn note_on(&mut self, channel: u8, pitch: u8, velocity: u8)
+    {
+        for voice in self.voices.iter_mut().filter(|v| v.channel == channel && v.pitch == pitch && !v.released)
+        {
+            voice.release();
+        }
+
+        // steal released voices first, then the oldest one
+        if self.max_voices > 0 && self.voices.len() >= self.max_voices
+        {
+            let i = self.voices.iter().position(|v| v.released).unwrap_or(0);
+            self.voices.remove(i);
+        }
+
+        self.seed = self.seed.wrapping_add(0x9E37_79B9);
+        let patch = self.patch(channel);
+        self.voices.push(SynthVoice::new(channel, pitch, velocity, &patch, self.seed));
+    }
+
+    fn note_off(&mut self, channel: u8, pitch: u8)
+    {
+        let sustain = self.controllers.channel(channel).value(64) >= 64;
+        for voice in self.voices.iter_mut().filter(|v| v.channel == channel && v.pitch == pitch && !v.released)
+        {
+            if sustain
+            {
+                voice.sustained = true;
+            }
+            else
+            {
+                voice.release();
+            }
+        }
+    }
+
+    pub fn process(&mut self, event: &MidiEvent)
+    {
+        match *event
+        {
+            MidiEvent::NoteOn { channel, pitch, velocity } if velocity > 0 => self.note_on(channel & 0xF, pitch, velocity),
+            MidiEvent::NoteOn { channel, pitch, .. } | MidiEvent::NoteOff { channel, pitch, .. } => self.note_off(channel & 0xF, pitch),
+            MidiEvent::ProgramChange { channel, preset } => self.programs[channel as usize & 0xF] = preset & 0x7F,
+            MidiEvent::ControllerChange { channel, controller: 120, .. } => self.voices.retain(|v| v.channel != channel & 0xF),
+            MidiEvent::ControllerChange { channel, controller: 123, .. } =>
+            {
+                for voice in self.voices.iter_mut().filter(|v| v.channel == channel & 0xF)
+                {
+                    voice.release();
+                }
+            }
+            MidiEvent::ControllerChange { channel, controller, .. } =>
+            {
+                self.controllers.process(event);
+                if (controller == 64 || controller == 121) && self.controllers.channel(channel).value(64) < 64
+                {
+                    for voice in self.voices.iter_mut().filter(|v| v.channel == channel & 0xF && v.sustained)
+                    {
+                        voice.release();
+                    }
+                }
+            }
+            MidiEvent::PitchBend { .. } =>
+            {
+                self.controllers.process(event);
+            }
+            _ => {}
+        }
+    }
+
+    pub fn render(&mut self, buffer: &mut [f32], channels: usize)
+    {
+        buffer.fill(0.0);
+        let channels = channels.max(1);
+
+        for voice in &mut self.voices
+        {
+            let controllers = self.controllers.channel(voice.channel);
+            let frequency = mtof(voice.pitch as f32 + controllers.pitch_bend_semitones() + controllers.tuning_semitones());
+            let gain = self.gain * controllers.value(7) as f32 / 127.0 * controllers.value(11) as f32 / 127.0;
+
+            let pan = controllers.value(10) as f32 / 127.0 * FRAC_PI_2;
+            let pans = [pan.cos(), pan.sin()];
+
+            for frame in buffer.chunks_mut(channels)
+            {
+                let sample = voice.next(frequency, self.sample_rate) * gain;
+                if frame.len() == 1
+                {
+                    frame[0] += sample;
+                }
+                else
+                {
+                    frame[0] += sample * pans[0];
+                    frame[1] += sample * pans[1];
+                }
+
+                if voice.is_finished()
+                {
+                    break;
+                }
+            }
+        }
+
+        self.voices.retain(|v| !v.is_finished());
+    }
+}
+
+impl Pipe<MidiEvent> for Synth
+{
+    fn send(&mut self, event: MidiEvent)
+    {
+        self.process(&event);
+    }
+}
@@ -0,0 +1,4 @@
+#[cfg(test)] mod test_envelope;
+#[cfg(test)] mod test_render;
+#[cfg(test)] mod test_synth;
+#[cfg(test)] mod test_wav;
\ No newline at end of file
@@ -0,0 +1,63 @@
+use crate::synth::prelude::*;
+
+#[test]
+fn test_adsr_stages()
+{
+    let mut envelope = Envelope::new(Adsr::new(0.1, 0.1, 0.5, 0.2));
+    let dt = 0.01;
+
+    let levels: Vec<f32> = (0..30).map(|_| envelope.next(dt)).collect();
+    assert!((levels[4] - 0.5).abs() < 1e-4);
+    assert_eq!(levels[9], 1.0);
+    assert!((levels[14] - 0.75).abs() < 1e-4);
+    assert_eq!(levels[29], 0.5);
+    assert_eq!(envelope.stage, EnvelopeStage::Sustain);
+
+    envelope.release();
+    for _ in 0..10
+    {
+        envelope.next(dt);
+    }
+    assert!((envelope.level - 0.25).abs() < 1e-4);
+    for _ in 0..11
+    {
+        envelope.next(dt);
+    }
+    assert!(envelope.is_finished());
+    assert_eq!(envelope.next(dt), 0.0);
+}
+
+#[test]
+fn test_percussive_envelope_finishes_without_release()
+{
+    let mut envelope = Envelope::new(Adsr::new(0.0, 0.05, 0.0, 1.0));
+    assert_eq!(envelope.next(0.01), 1.0);
+    for _ in 0..6
+    {
+        envelope.next(0.01);
+    }
+    assert!(envelope.is_finished());
+}
+
+#[test]
+fn test_oscillators()
+{
+    let mut oscillator = Oscillator::new(Waveform::Saw, 0);
+    let samples: Vec<f32> = (0..4).map(|_| oscillator.next(1.0, 4.0)).collect();
+    assert_eq!(samples, vec![-1.0, -0.5, 0.0, 0.5]);
+
+    let mut oscillator = Oscillator::new(Waveform::Square, 0);
+    let samples: Vec<f32> = (0..4).map(|_| oscillator.next(1.0, 4.0)).collect();
+    assert_eq!(samples, vec![1.0, 1.0, -1.0, -1.0]);
+
+    let mut oscillator = Oscillator::new(Waveform::Triangle, 0);
+    let samples: Vec<f32> = (0..4).map(|_| oscillator.next(1.0, 4.0)).collect();
+    assert_eq!(samples, vec![-1.0, 0.0, 1.0, 0.0]);
+
+    let mut a = Oscillator::new(Waveform::Noise, 7);
+    let mut b = Oscillator::new(Waveform::Noise, 7);
+    let noise: Vec<f32> = (0..64).map(|_| a.next(440.0, 48000.0)).collect();
+    assert!(noise.iter().all(|s| (-1.0..=1.0).contains(s)));
+    assert!(noise.iter().any(|&s| s != noise[0]));
+    assert!(noise.iter().all(|&s| s == b.next(440.0, 48000.0)));
+}
@@ -0,0 +1,49 @@
+use crate::{midi::{event::MidiEvent, midi_song::{MidiSong, MidiTrack, TimeDivision}}, synth::prelude::*};
+
+fn song() -> MidiSong
+{
+    MidiSong
+    {
+        t: 0,
+        time_division: TimeDivision::Metrical { pulses_per_quarter_note: 100 },
+        tracks: vec![
+            MidiTrack
+            {
+                dts: vec![0, 0, 50, 100, 0],
+                events: vec![
+                    MidiEvent::SetTempo { microseconds_per_quarter_note: 1000000 },
+                    MidiEvent::ProgramChange { channel: 0, preset: 0 },
+                    MidiEvent::NoteOn { channel: 0, pitch: 69, velocity: 127 },
+                    MidiEvent::NoteOff { channel: 0, pitch: 69, velocity: 0 },
+                    MidiEvent::EndOfTrack,
+                ],
+            },
+        ],
+    }
+}
+
+#[test]
+fn test_render_song_is_sample_accurate()
+{
+    let mut synth = Synth::new(1000.0);
+    synth.patches[0] = Patch::new(Waveform::Square, Adsr::new(0.0, 0.0, 1.0, 0.1), 1.0);
+
+    let buffer = render_song(&song(), &mut synth, 1, 5.0);
+
+    // the note starts at 0.5s, ends at 1.5s and releases over 0.1s
+    assert_eq!(buffer.iter().position(|&s| s != 0.0), Some(500));
+    assert!(buffer[500..1500].iter().all(|&s| s.abs() > 0.1));
+    assert!(buffer.len() >= 1600 && buffer.len() < 1700);
+    assert!(synth.is_silent());
+}
+
+#[test]
+fn test_render_song_stereo_tail_limit()
+{
+    let mut synth = Synth::new(1000.0);
+    synth.patches[0] = Patch::new(Waveform::Sine, Adsr::new(0.0, 0.0, 1.0, 10.0), 1.0);
+
+    let buffer = render_song(&song(), &mut synth, 2, 0.25);
+    assert_eq!(buffer.len(), 2 * 1750);
+    assert!(!synth.is_silent());
+}
@@ -0,0 +1,108 @@
+use crate::{midi::event::MidiEvent, pipe::Pipe, synth::prelude::*};
+
+fn sine_synth() -> Synth
+{
+    let mut synth = Synth::new(48000.0);
+    synth.patches[0] = Patch::new(Waveform::Sine, Adsr::new(0.0, 0.0, 1.0, 0.01), 1.0);
+    synth
+}
+
+fn rising_zero_crossings(samples: &[f32]) -> usize
+{
+    samples.windows(2).filter(|w| w[0] < 0.0 && w[1] >= 0.0).count()
+}
+
+#[test]
+fn test_note_frequency_and_pitch_bend()
+{
+    let mut synth = sine_synth();
+    let mut buffer = vec![0.0; 48000];
+
+    synth.send(MidiEvent::NoteOn { channel: 0, pitch: 69, velocity: 127 });
+    synth.render(&mut buffer, 1);
+    assert!((rising_zero_crossings(&buffer) as i32 - 440).abs() <= 1);
+    assert!(buffer.iter().all(|s| s.abs() <= 0.25));
+
+    synth.send(MidiEvent::PitchBend { channel: 0, bend_lsb: 0x7F, position_msb: 0x7F });
+    synth.render(&mut buffer, 1);
+    assert!((rising_zero_crossings(&buffer) as i32 - 494).abs() <= 1);
+}
+
+#[test]
+fn test_note_off_and_sustain_pedal()
+{
+    let mut synth = sine_synth();
+    let mut buffer = vec![0.0; 4800];
+
+    synth.send(MidiEvent::ControllerChange { channel: 0, controller: 64, value: 127 });
+    synth.send(MidiEvent::NoteOn { channel: 0, pitch: 60, velocity: 100 });
+    synth.send(MidiEvent::NoteOff { channel: 0, pitch: 60, velocity: 0 });
+    synth.render(&mut buffer, 1);
+    assert_eq!(synth.voices().len(), 1);
+    assert!(synth.voices()[0].sustained);
+
+    synth.send(MidiEvent::ControllerChange { channel: 0, controller: 64, value: 0 });
+    synth.render(&mut buffer, 1);
+    assert!(synth.is_silent());
+    assert!(buffer[4000..].iter().all(|&s| s == 0.0));
+}
+
+#[test]
+fn test_programs_and_polyphony()
+{
+    let mut synth = sine_synth();
+    synth.max_voices = 2;
+
+    synth.send(MidiEvent::ProgramChange { channel: 1, preset: 16 });
+    assert_eq!(synth.program(1), 16);
+    synth.send(MidiEvent::NoteOn { channel: 1, pitch: 60, velocity: 100 });
+    assert_eq!(synth.voices()[0].oscillator.waveform, Waveform::Square);
+
+    synth.send(MidiEvent::NoteOn { channel: 9, pitch: 36, velocity: 100 });
+    assert_eq!(synth.voices()[1].oscillator.waveform, Waveform::Noise);
+
+    // the oldest voice is stolen once the polyphony limit is hit
+    synth.send(MidiEvent::NoteOn { channel: 0, pitch: 64, velocity: 100 });
+    let pitches: Vec<u8> = synth.voices().iter().map(|v| v.pitch).collect();
+    assert_eq!(pitches, vec![36, 64]);
+
+    synth.send(MidiEvent::ControllerChange { channel: 0, controller: 120, value: 0 });
+    assert_eq!(synth.voices().len(), 1);
+}
+
+#[test]
+fn test_channel_mode_messages_mask_channel()
+{
+    let mut synth = sine_synth();
+    let mut buffer = vec![0.0; 4800];
+
+    synth.send(MidiEvent::NoteOn { channel: 0x12, pitch: 60, velocity: 100 });
+    synth.send(MidiEvent::NoteOn { channel: 3, pitch: 62, velocity: 100 });
+    synth.send(MidiEvent::ControllerChange { channel: 0x22, controller: 120, value: 0 });
+    assert_eq!(synth.voices().iter().map(|v| v.pitch).collect::<Vec<_>>(), vec![62]);
+
+    synth.send(MidiEvent::ControllerChange { channel: 0x13, controller: 123, value: 0 });
+    synth.render(&mut buffer, 1);
+    assert!(synth.is_silent());
+
+    synth.send(MidiEvent::ControllerChange { channel: 0x14, controller: 64, value: 127 });
+    synth.send(MidiEvent::NoteOn { channel: 4, pitch: 60, velocity: 100 });
+    synth.send(MidiEvent::NoteOff { channel: 4, pitch: 60, velocity: 0 });
+    synth.send(MidiEvent::ControllerChange { channel: 0x14, controller: 64, value: 0 });
+    synth.render(&mut buffer, 1);
+    assert!(synth.is_silent());
+}
+
+#[test]
+fn test_stereo_pan()
+{
+    let mut synth = sine_synth();
+    let mut buffer = vec![0.0; 2 * 1000];
+
+    synth.send(MidiEvent::ControllerChange { channel: 0, controller: 10, value: 0 });
+    synth.send(MidiEvent::NoteOn { channel: 0, pitch: 69, velocity: 127 });
+    synth.render(&mut buffer, 2);
+
+    assert!(buffer.iter().step_by(2).any(|&s| s != 0.0));
+    assert!(buffer.iter().skip(1).step_by(2).all(|&s| s.abs() < 1e-6));
+}
@@ -0,0 +1,140 @@
+use crate::synth::{prelude::*, wav::{read_sample, write_sample}};
+
+fn wav(channels: u16, format: SampleFormat) -> Wav
+{
+    let samples = (0..64).map(|i| ((i as f32) * 0.37).sin() * 0.9).collect();
+    Wav::new(WavSpec { channels, sample_rate: 22050, format }, samples)
+}
+
+#[test]
+fn test_header()
+{
+    let bytes = write_wav(&wav(2, SampleFormat::Pcm16)).unwrap();
+    assert_eq!(bytes.len(), 44 + 64 * 2);
+    assert_eq!(&bytes[0..4], b"RIFF");
+    assert_eq!(u32::from_le_bytes(bytes[4..8].try_into().unwrap()), 36 + 128);
+    assert_eq!(&bytes[8..16], b"WAVEfmt ");
+    assert_eq!(u16::from_le_bytes([bytes[20], bytes[21]]), 1);
+    assert_eq!(u16::from_le_bytes([bytes[22], bytes[23]]), 2);
+    assert_eq!(u32::from_le_bytes(bytes[24..28].try_into().unwrap()), 22050);
+    assert_eq!(u32::from_le_bytes(bytes[28..32].try_into().unwrap()), 22050 * 4);
+    assert_eq!(u16::from_le_bytes([bytes[32], bytes[33]]), 4);
+    assert_eq!(u16::from_le_bytes([bytes[34], bytes[35]]), 16);
+    assert_eq!(&bytes[36..40], b"data");
+
+    let bytes = write_wav(&wav(1, SampleFormat::Float32)).unwrap();
+    assert_eq!(u16::from_le_bytes([bytes[20], bytes[21]]), 3);
+
+    // odd sized data chunks get a pad byte
+    let bytes = write_wav(&Wav::new(WavSpec { channels: 1, sample_rate: 8000, format: SampleFormat::Pcm24 }, vec![0.5])).unwrap();
+    assert_eq!(bytes.len(), 44 + 4);
+    assert_eq!(u32::from_le_bytes(bytes[40..44].try_into().unwrap()), 3);
+}
+
+#[test]
+fn test_round_trip()
+{
+    for (format, tolerance) in [(SampleFormat::Pcm16, 1.0 / 32767.0), (SampleFormat::Pcm24, 1.0 / 8388607.0), (SampleFormat::Float32, 0.0)]
+    {
+        for channels in [1, 2]
+        {
+            let original = wav(channels, format);
+            let decoded = read_wav(&write_wav(&original).unwrap()).unwrap();
+            assert_eq!(decoded.spec, original.spec);
+            assert_eq!(decoded.samples.len(), original.samples.len());
+            assert!(decoded.samples.iter().zip(&original.samples).all(|(a, b)| (a - b).abs() <= tolerance));
+        }
+    }
+
+    let stereo = wav(2, SampleFormat::Float32);
+    assert_eq!(stereo.frames(), 32);
+    assert_eq!(stereo.duration_seconds(), 32.0 / 22050.0);
+    assert_eq!(stereo.channel(1).next(), Some(stereo.samples[1]));
+}
+
+#[test]
+fn test_sample_conversion()
+{
+    let mut bytes = Vec::new();
+    write_sample(&mut bytes, -1.0, SampleFormat::Pcm24);
+    write_sample(&mut bytes, 2.0, SampleFormat::Pcm16);
+    assert_eq!(bytes, vec![0x01, 0x00, 0x80, 0xFF, 0x7F]);
+    assert_eq!(read_sample(&bytes[0..3], SampleFormat::Pcm24), -1.0);
+    assert_eq!(read_sample(&bytes[3..5], SampleFormat::Pcm16), 1.0);
+}
+
+#[test]
+fn test_reader_skips_unknown_chunks_and_reads_extensible()
+{
+    let bytes = write_wav(&wav(1, SampleFormat::Pcm16)).unwrap();
+    let mut extended = bytes[..12].to_vec();
+    extended.extend_from_slice(b"LIST");
+    extended.extend_from_slice(&3u32.to_le_bytes());
+    extended.extend_from_slice(&[1, 2, 3, 0]);
+
+    let mut fmt = vec![0u8; 40];
+    fmt[..16].copy_from_slice(&bytes[20..36]);
+    fmt[0..2].copy_from_slice(&0xFFFEu16.to_le_bytes());
+    fmt[16..18].copy_from_slice(&22u16.to_le_bytes());
+    fmt[24..26].copy_from_slice(&1u16.to_le_bytes());
+    extended.extend_from_slice(b"fmt ");
+    extended.extend_from_slice(&40u32.to_le_bytes());
+    extended.extend_from_slice(&fmt);
+    extended.extend_from_slice(&bytes[36..]);
+
+    assert_eq!(read_wav(&extended).unwrap(), read_wav(&bytes).unwrap());
+}
+
+#[test]
+fn test_errors()
+{
+    let bytes = write_wav(&wav(1, SampleFormat::Pcm16)).unwrap();
+
+    let mut bad = bytes.clone();
+    bad[8..12].copy_from_slice(b"AVI ");
+    assert_eq!(read_wav(&bad), Err(WavError::BadMagic { found: *b"AVI ", offset: 8 }));
+
+    assert_eq!(read_wav(&bytes[..50]), Err(WavError::TruncatedChunk { offset: 36 }));
+    assert_eq!(read_wav(&bytes[..36]), Err(WavError::MissingChunk { id: *b"data" }));
+    assert_eq!(read_wav(&bytes[..4]), Err(WavError::TruncatedChunk { offset: 0 }));
+
+    let mut eight_bit = bytes.clone();
+    eight_bit[34] = 8;
+    assert_eq!(read_wav(&eight_bit), Err(WavError::UnsupportedFormat { format_tag: 1, bits_per_sample: 8 }));
+    assert_eq!(read_wav(&eight_bit).unwrap_err().to_string(), "unsupported sample format 0x0001 with 8 bits per sample");
+}
+
+#[test]
+fn test_save_and_load_file()
+{
+    let path = std::env::temp_dir().join(format!("dol_test_wav_{}.wav", std::process::id()));
+    let original = wav(2, SampleFormat::Pcm24);
+    save_wav_file(&path, &original).unwrap();
+    let loaded = load_wav_file(&path);
+    let _ = std::fs::remove_file(&path);
+
+    assert_eq!(loaded.unwrap().spec, original.spec);
+    assert!(matches!(load_wav_file(&path), Err(WavError::Io { .. })));
+}
+
+#[test]
+fn test_write_rejects_oversized_spec()
+{
+    let oversized = |channels, sample_rate, format| Wav::new(WavSpec { channels, sample_rate, format }, vec![0.0; 4]);
+
+    // 40000 channels of 16 bit samples overflow the block alignment
+    let wav = oversized(40000, 44100, SampleFormat::Pcm16);
+    assert_eq!(write_wav(&wav), Err(WavError::InvalidSpec { channels: 40000, sample_rate: 44100, format: SampleFormat::Pcm16 }));
+
+    // the byte rate overflows even though the block alignment fits
+    let wav = oversized(8, u32::MAX / 4, SampleFormat::Float32);
+    assert!(matches!(write_wav(&wav), Err(WavError::InvalidSpec { .. })));
+    assert_eq!(write_wav(&wav).unwrap_err().to_string(), format!("8 channels of Float32 at {} Hz do not fit a wav header", u32::MAX / 4));
+
+    let wav = oversized(u16::MAX / 4, 44100, SampleFormat::Float32);
+    assert!(write_wav(&wav).is_ok());
+
+    let path = std::env::temp_dir().join(format!("dol_test_oversized_wav_{}.wav", std::process::id()));
+    assert!(matches!(save_wav_file(&path, &oversized(40000, 44100, SampleFormat::Pcm16)), Err(WavError::InvalidSpec { .. })));
+    assert!(!path.exists());
+}
\ No newline at end of file
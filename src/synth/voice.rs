@@ -0,0 +1,53 @@
+use serde::{Serialize, Deserialize};
+
+use super::{envelope::Envelope, oscillator::Oscillator, patch::Patch};
+
+#[derive(Debug, Copy, Clone, PartialEq, Default, Serialize, Deserialize)]
+#[repr(C)]
+pub struct SynthVoice
+{
+    pub channel: u8,
+    pub pitch: u8,
+    pub velocity: u8,
+    pub oscillator: Oscillator,
+    pub envelope: Envelope,
+    pub gain: f32,
+    pub released: bool,
+    pub sustained: bool,
+}
+
+impl SynthVoice
+{
+    pub fn new(channel: u8, pitch: u8, velocity: u8, patch: &Patch, seed: u32) -> SynthVoice
+    {
+        SynthVoice
+        {
+            channel,
+            pitch,
+            velocity,
+            oscillator: Oscillator::new(patch.waveform, seed),
+            envelope: Envelope::new(patch.envelope),
+            gain: patch.gain,
+            released: false,
+            sustained: false,
+        }
+    }
+
+    pub fn release(&mut self)
+    {
+        self.released = true;
+        self.sustained = false;
+        self.envelope.release();
+    }
+
+    pub fn is_finished(&self) -> bool
+    {
+        self.envelope.is_finished()
+    }
+
+    pub fn next(&mut self, frequency: f32, sample_rate: f32) -> f32
+    {
+        let level = self.envelope.next(1.0 / sample_rate);
+        self.oscillator.next(frequency, sample_rate) * level * self.gain * self.velocity as f32 / 127.0
+    }
+}
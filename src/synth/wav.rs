@@ -0,0 +1,246 @@
+use std::path::Path;
+
+use serde::{Serialize, Deserialize};
+
+use super::wav_error::WavError;
+
+const WAVE_FORMAT_PCM: u16 = 0x0001;
+const WAVE_FORMAT_IEEE_FLOAT: u16 = 0x0003;
+const WAVE_FORMAT_EXTENSIBLE: u16 = 0xFFFE;
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, Default, Serialize, Deserialize)]
+#[repr(C)]
+pub enum SampleFormat
+{
+    #[default]
+    Pcm16,
+    Pcm24,
+    Float32,
+}
+
+impl SampleFormat
+{
+    pub fn bits_per_sample(self) -> u16
+    {
+        match self
+        {
+            SampleFormat::Pcm16 => 16,
+            SampleFormat::Pcm24 => 24,
+            SampleFormat::Float32 => 32,
+        }
+    }
+
+    pub fn bytes_per_sample(self) -> usize
+    {
+        self.bits_per_sample() as usize / 8
+    }
+
+    fn format_tag(self) -> u16
+    {
+        match self
+        {
+            SampleFormat::Pcm16 | SampleFormat::Pcm24 => WAVE_FORMAT_PCM,
+            SampleFormat::Float32 => WAVE_FORMAT_IEEE_FLOAT,
+        }
+    }
+
+    fn from_format_tag(format_tag: u16, bits_per_sample: u16) -> Option<SampleFormat>
+    {
+        match (format_tag, bits_per_sample)
+        {
+            (WAVE_FORMAT_PCM, 16) => Some(SampleFormat::Pcm16),
+            (WAVE_FORMAT_PCM, 24) => Some(SampleFormat::Pcm24),
+            (WAVE_FORMAT_IEEE_FLOAT, 32) => Some(SampleFormat::Float32),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, Default, Serialize, Deserialize)]
+#[repr(C)]
+pub struct WavSpec
+{
+    pub channels: u16,
+    pub sample_rate: u32,
+    pub format: SampleFormat,
+}
+
+#[derive(Debug, Clone, PartialEq, Default, Serialize, Deserialize)]
+#[repr(C)]
+pub struct Wav
+{
+    pub spec: WavSpec,
+    pub samples: Vec<f32>,
+}
+
+impl Wav
+{
+    pub fn new(spec: WavSpec, samples: Vec<f32>) -> Wav
+    {
+        Wav { spec, samples }
+    }
+
+    pub fn frames(&self) -> usize
+    {
+        self.samples.len() / self.spec.channels.max(1) as usize
+    }
+
+    pub fn duration_seconds(&self) -> f64
+    {
+        if self.spec.sample_rate == 0 { 0.0 } else { self.frames() as f64 / self.spec.sample_rate as f64 }
+    }
+
+    pub fn channel(&self, channel: usize) -> impl Iterator<Item = f32> + '_
+    {
+        self.samples.iter().skip(channel).step_by(self.spec.channels.max(1) as usize).copied()
+    }
+}
+
+pub fn save_wav_file<P: AsRef<Path>>(path: P, wav: &Wav) -> Result<(), WavError>
+{
+    let bytes = write_wav(wav)?;
+    std::fs::write(path, bytes).map_err(|e| WavError::Io { kind: e.kind() })
+}
+
+pub fn load_wav_file<P: AsRef<Path>>(path: P) -> Result<Wav, WavError>
+{
+    let bytes = std::fs::read(path).map_err(|e| WavError::Io { kind: e.kind() })?;
+    read_wav(&bytes)
+}
+
+pub fn write_wav(wav: &Wav) -> Result<Vec<u8>, WavError>
+{
+    let spec = &wav.spec;
+    let frames = wav.frames();
+
+    // header fields are fixed width, sizes are computed wide so a spec that does not fit is an error instead of a
+    // panic or a silently truncated header
+    let invalid_spec = WavError::InvalidSpec { channels: spec.channels, sample_rate: spec.sample_rate, format: spec.format };
+    let block_align = u16::try_from(spec.channels as u64 * spec.format.bytes_per_sample() as u64).map_err(|_| invalid_spec.clone())?;
+    let byte_rate = u32::try_from(spec.sample_rate as u64 * block_align as u64).map_err(|_| invalid_spec)?;
+    let data_len = (frames as u64).checked_mul(block_align as u64).ok_or(WavError::DataTooLarge { len: u64::MAX })?;
+    let riff_len = u32::try_from(36 + data_len + data_len % 2).map_err(|_| WavError::DataTooLarge { len: data_len })?;
+
+    let mut bytes = Vec::with_capacity(8 + riff_len as usize);
+    bytes.extend_from_slice(b"RIFF");
+    bytes.extend_from_slice(&riff_len.to_le_bytes());
+    bytes.extend_from_slice(b"WAVE");
+
+    bytes.extend_from_slice(b"fmt ");
+    bytes.extend_from_slice(&16u32.to_le_bytes());
+    bytes.extend_from_slice(&spec.format.format_tag().to_le_bytes());
+    bytes.extend_from_slice(&spec.channels.to_le_bytes());
+    bytes.extend_from_slice(&spec.sample_rate.to_le_bytes());
+    bytes.extend_from_slice(&byte_rate.to_le_bytes());
+    bytes.extend_from_slice(&block_align.to_le_bytes());
+    bytes.extend_from_slice(&spec.format.bits_per_sample().to_le_bytes());
+
+    bytes.extend_from_slice(b"data");
+    bytes.extend_from_slice(&(data_len as u32).to_le_bytes());
+    for &sample in &wav.samples[..frames * spec.channels as usize]
+    {
+        write_sample(&mut bytes, sample, spec.format);
+    }
+    if data_len % 2 == 1
+    {
+        bytes.push(0);
+    }
+
+    Ok(bytes)
+}
+
+pub fn write_sample(bytes: &mut Vec<u8>, sample: f32, format: SampleFormat)
+{
+    match format
+    {
+        SampleFormat::Pcm16 => bytes.extend_from_slice(&((sample.clamp(-1.0, 1.0) * 32767.0).round() as i16).to_le_bytes()),
+        SampleFormat::Pcm24 => bytes.extend_from_slice(&((sample.clamp(-1.0, 1.0) * 8388607.0).round() as i32).to_le_bytes()[..3]),
+        SampleFormat::Float32 => bytes.extend_from_slice(&sample.to_le_bytes()),
+    }
+}
+
+pub fn read_sample(bytes: &[u8], format: SampleFormat) -> f32
+{
+    match format
+    {
+        SampleFormat::Pcm16 => i16::from_le_bytes([bytes[0], bytes[1]]) as f32 / 32767.0,
+        SampleFormat::Pcm24 => (i32::from_le_bytes([0, bytes[0], bytes[1], bytes[2]]) >> 8) as f32 / 8388607.0,
+        SampleFormat::Float32 => f32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]),
+    }
+}
+
+fn read_chunk<'a>(bytes: &'a [u8], cursor: &mut usize) -> Result<([u8; 4], &'a [u8]), WavError>
+{
+    let offset = *cursor;
+    let header = bytes.get(offset..offset + 8).ok_or(WavError::TruncatedChunk { offset })?;
+    let id = [header[0], header[1], header[2], header[3]];
+    let len = u32::from_le_bytes([header[4], header[5], header[6], header[7]]) as usize;
+
+    let body = bytes.get(offset + 8..offset + 8 + len).ok_or(WavError::TruncatedChunk { offset })?;
+    *cursor = offset + 8 + len + len % 2;
+    Ok((id, body))
+}
+
+fn read_fmt_chunk(body: &[u8]) -> Result<WavSpec, WavError>
+{
+    let u16_at = |i: usize| u16::from_le_bytes([body[i], body[i + 1]]);
+    if body.len() < 16
+    {
+        return Err(WavError::MissingChunk { id: *b"fmt " });
+    }
+
+    let mut format_tag = u16_at(0);
+    let bits_per_sample = u16_at(14);
+    if format_tag == WAVE_FORMAT_EXTENSIBLE && body.len() >= 26
+    {
+        // the sub format guid starts with the plain format tag
+        format_tag = u16_at(24);
+    }
+
+    let format = SampleFormat::from_format_tag(format_tag, bits_per_sample)
+        .ok_or(WavError::UnsupportedFormat { format_tag, bits_per_sample })?;
+
+    Ok(WavSpec
+    {
+        channels: u16_at(2),
+        sample_rate: u32::from_le_bytes([body[4], body[5], body[6], body[7]]),
+        format,
+    })
+}
+
+pub fn read_wav(bytes: &[u8]) -> Result<Wav, WavError>
+{
+    let riff = bytes.get(0..12).ok_or(WavError::TruncatedChunk { offset: 0 })?;
+    for (magic, offset) in [(b"RIFF", 0), (b"WAVE", 8)]
+    {
+        if &riff[offset..offset + 4] != magic
+        {
+            return Err(WavError::BadMagic { found: [riff[offset], riff[offset + 1], riff[offset + 2], riff[offset + 3]], offset });
+        }
+    }
+
+    let mut spec = None;
+    let mut cursor = 12;
+    while cursor < bytes.len()
+    {
+        let (id, body) = read_chunk(bytes, &mut cursor)?;
+        match &id
+        {
+            b"fmt " => spec = Some(read_fmt_chunk(body)?),
+            b"data" =>
+            {
+                let spec = spec.ok_or(WavError::MissingChunk { id: *b"fmt " })?;
+                let frame_len = spec.channels as usize * spec.format.bytes_per_sample();
+                let usable = body.len().checked_div(frame_len).unwrap_or(0) * frame_len;
+                let samples = body[..usable]
+                    .chunks_exact(spec.format.bytes_per_sample())
+                    .map(|sample| read_sample(sample, spec.format))
+                    .collect();
+                return Ok(Wav { spec, samples });
+            }
+            _ => {}
+        }
+    }
+
+    Err(WavError::MissingChunk { id: *b"data" })
+}
@@ -0,0 +1,59 @@
+use std::fmt;
+
+use super::wav::SampleFormat;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum WavError
+{
+    Io { kind: std::io::ErrorKind },
+    TruncatedChunk { offset: usize },
+    BadMagic { found: [u8; 4], offset: usize },
+    MissingChunk { id: [u8; 4] },
+    UnsupportedFormat { format_tag: u16, bits_per_sample: u16 },
+    InvalidSpec { channels: u16, sample_rate: u32, format: SampleFormat },
+    DataTooLarge { len: u64 },
+}
+
+impl WavError
+{
+    pub fn offset(&self) -> Option<usize>
+    {
+        match self
+        {
+            WavError::TruncatedChunk { offset } | WavError::BadMagic { offset, .. } => Some(*offset),
+            WavError::Io { .. }
+            | WavError::MissingChunk { .. }
+            | WavError::UnsupportedFormat { .. }
+            | WavError::InvalidSpec { .. }
+            | WavError::DataTooLarge { .. } => None,
+        }
+    }
+}
+
+impl fmt::Display for WavError
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result
+    {
+        match self
+        {
+            WavError::Io { kind } => return write!(f, "wav file io failed: {}", kind),
+            WavError::TruncatedChunk { .. } => write!(f, "truncated chunk")?,
+            WavError::BadMagic { found, .. } => write!(f, "bad chunk magic {:?}", found)?,
+            WavError::MissingChunk { id } => write!(f, "missing {} chunk", String::from_utf8_lossy(id))?,
+            WavError::UnsupportedFormat { format_tag, bits_per_sample } =>
+                write!(f, "unsupported sample format {:#06X} with {} bits per sample", format_tag, bits_per_sample)?,
+            WavError::InvalidSpec { channels, sample_rate, format } =>
+                write!(f, "{} channels of {:?} at {} Hz do not fit a wav header", channels, format, sample_rate)?,
+            WavError::DataTooLarge { len } => write!(f, "{} bytes of sample data do not fit a wav file", len)?,
+        }
+
+        if let Some(offset) = self.offset()
+        {
+            write!(f, " at byte {}", offset)?;
+        }
+
+        Ok(())
+    }
+}
+
+impl std::error::Error for WavError {}
@@ -0,0 +1,170 @@
+use std::fmt;
+
+use serde::{Serialize, Deserialize};
+
+use super::note_name::{NoteName, OctaveConvention, Spelling};
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, Default, Serialize, Deserialize)]
+#[repr(C)]
+pub enum ChordQuality
+{
+    #[default]
+    Major,
+    Minor,
+    Diminished,
+    Augmented,
+    Sus2,
+    Sus4,
+    Dominant7,
+    Major7,
+    Minor7,
+    HalfDiminished7,
+    Diminished7,
+}
+
+impl ChordQuality
+{
+    pub const ALL: [ChordQuality; 11] =
+    [
+        ChordQuality::Major,
+        ChordQuality::Minor,
+        ChordQuality::Diminished,
+        ChordQuality::Augmented,
+        ChordQuality::Sus2,
+        ChordQuality::Sus4,
+        ChordQuality::Dominant7,
+        ChordQuality::Major7,
+        ChordQuality::Minor7,
+        ChordQuality::HalfDiminished7,
+        ChordQuality::Diminished7,
+    ];
+
+    pub fn intervals(self) -> &'static [u8]
+    {
+        match self
+        {
+            ChordQuality::Major => &[0, 4, 7],
+            ChordQuality::Minor => &[0, 3, 7],
+            ChordQuality::Diminished => &[0, 3, 6],
+            ChordQuality::Augmented => &[0, 4, 8],
+            ChordQuality::Sus2 => &[0, 2, 7],
+            ChordQuality::Sus4 => &[0, 5, 7],
+            ChordQuality::Dominant7 => &[0, 4, 7, 10],
+            ChordQuality::Major7 => &[0, 4, 7, 11],
+            ChordQuality::Minor7 => &[0, 3, 7, 10],
+            ChordQuality::HalfDiminished7 => &[0, 3, 6, 10],
+            ChordQuality::Diminished7 => &[0, 3, 6, 9],
+        }
+    }
+
+    pub fn suffix(self) -> &'static str
+    {
+        match self
+        {
+            ChordQuality::Major => "",
+            ChordQuality::Minor => "m",
+            ChordQuality::Diminished => "dim",
+            ChordQuality::Augmented => "aug",
+            ChordQuality::Sus2 => "sus2",
+            ChordQuality::Sus4 => "sus4",
+            ChordQuality::Dominant7 => "7",
+            ChordQuality::Major7 => "maj7",
+            ChordQuality::Minor7 => "m7",
+            ChordQuality::HalfDiminished7 => "m7b5",
+            ChordQuality::Diminished7 => "dim7",
+        }
+    }
+
+    pub fn from_intervals(intervals: &[u8]) -> Option<ChordQuality>
+    {
+        ChordQuality::ALL.into_iter().find(|q| q.intervals() == intervals)
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, Default, Serialize, Deserialize)]
+#[repr(C)]
+pub struct Chord
+{
+    pub root: u8,
+    pub quality: ChordQuality,
+}
+
+impl Chord
+{
+    pub fn new(root: u8, quality: ChordQuality) -> Chord
+    {
+        Chord { root: root % 12, quality }
+    }
+
+    pub fn pitch_classes(&self) -> impl Iterator<Item = u8> + '_
+    {
+        self.quality.intervals().iter().map(move |i| (self.root + i) % 12)
+    }
+
+    pub fn tones(&self, octave_root: i32) -> Vec<i32>
+    {
+        let root = octave_root + (self.root as i32 - octave_root).rem_euclid(12);
+        self.quality.intervals().iter().map(|&i| root + i as i32).collect()
+    }
+
+    pub fn contains(&self, pitch_class: u8) -> bool
+    {
+        self.pitch_classes().any(|pc| pc == pitch_class % 12)
+    }
+
+    pub fn detect(pitch_class_weights: &[f32; 12]) -> Option<Chord>
+    {
+        let total: f32 = pitch_class_weights.iter().sum();
+        if total <= 0.0 || pitch_class_weights.iter().filter(|&&w| w > 0.0).count() < 2
+        {
+            return None;
+        }
+
+        // every chord tone that does not sound costs a little, so triads win over sevenths with a missing seventh
+        let mut best = None;
+        let mut best_score = f32::MIN;
+        for root in 0..12
+        {
+            for quality in ChordQuality::ALL
+            {
+                let chord = Chord { root, quality };
+                let mut score = 0.0;
+                for (pitch_class, &weight) in pitch_class_weights.iter().enumerate()
+                {
+                    score += if chord.contains(pitch_class as u8) { weight / total } else { -weight / total };
+                }
+                score -= 0.1 * chord.pitch_classes().filter(|&pc| pitch_class_weights[pc as usize] <= 0.0).count() as f32;
+
+                if score > best_score
+                {
+                    best_score = score;
+                    best = Some(chord);
+                }
+            }
+        }
+
+        best
+    }
+
+    pub fn to_string_with(&self, spelling: Spelling) -> String
+    {
+        let name = NoteName::from_midi(self.root as i32, OctaveConvention::default(), spelling);
+        let mut s = String::from(name.letter.as_char());
+        match name.accidental
+        {
+            1 => s.push('#'),
+            -1 => s.push('b'),
+            _ => {}
+        }
+        s.push_str(self.quality.suffix());
+        s
+    }
+}
+
+impl fmt::Display for Chord
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result
+    {
+        write!(f, "{}", self.to_string_with(Spelling::default()))
+    }
+}
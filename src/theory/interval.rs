@@ -0,0 +1,91 @@
+use std::ops;
+
+use serde::{Serialize, Deserialize};
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, PartialOrd, Ord, Default, Serialize, Deserialize)]
+#[repr(C)]
+pub struct Interval
+{
+    pub semitones: i32,
+}
+
+impl Interval
+{
+    pub const UNISON: Interval = Interval { semitones: 0 };
+    pub const MINOR_SECOND: Interval = Interval { semitones: 1 };
+    pub const MAJOR_SECOND: Interval = Interval { semitones: 2 };
+    pub const MINOR_THIRD: Interval = Interval { semitones: 3 };
+    pub const MAJOR_THIRD: Interval = Interval { semitones: 4 };
+    pub const PERFECT_FOURTH: Interval = Interval { semitones: 5 };
+    pub const TRITONE: Interval = Interval { semitones: 6 };
+    pub const PERFECT_FIFTH: Interval = Interval { semitones: 7 };
+    pub const MINOR_SIXTH: Interval = Interval { semitones: 8 };
+    pub const MAJOR_SIXTH: Interval = Interval { semitones: 9 };
+    pub const MINOR_SEVENTH: Interval = Interval { semitones: 10 };
+    pub const MAJOR_SEVENTH: Interval = Interval { semitones: 11 };
+    pub const OCTAVE: Interval = Interval { semitones: 12 };
+
+    pub fn new(semitones: i32) -> Interval
+    {
+        Interval { semitones }
+    }
+
+    pub fn between(from: i32, to: i32) -> Interval
+    {
+        Interval { semitones: to - from }
+    }
+
+    pub fn simple(self) -> Interval
+    {
+        Interval { semitones: self.semitones.rem_euclid(12) }
+    }
+
+    pub fn invert(self) -> Interval
+    {
+        Interval { semitones: (12 - self.semitones.rem_euclid(12)) % 12 }
+    }
+
+    pub fn ratio(self) -> f32
+    {
+        f32::powf(2.0, self.semitones as f32 / 12.0)
+    }
+
+    pub fn short_name(self) -> &'static str
+    {
+        match self.semitones.rem_euclid(12)
+        {
+            0 => if self.semitones == 0 { "P1" } else { "P8" },
+            1 => "m2",
+            2 => "M2",
+            3 => "m3",
+            4 => "M3",
+            5 => "P4",
+            6 => "TT",
+            7 => "P5",
+            8 => "m6",
+            9 => "M6",
+            10 => "m7",
+            _ => "M7",
+        }
+    }
+}
+
+impl ops::Add<Interval> for Interval
+{
+    type Output = Interval;
+
+    fn add(self, rhs: Interval) -> Interval
+    {
+        Interval { semitones: self.semitones + rhs.semitones }
+    }
+}
+
+impl ops::Neg for Interval
+{
+    type Output = Interval;
+
+    fn neg(self) -> Interval
+    {
+        Interval { semitones: -self.semitones }
+    }
+}
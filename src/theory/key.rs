@@ -0,0 +1,152 @@
+use std::fmt;
+
+use serde::{Serialize, Deserialize};
+
+use super::{note_name::{NoteName, OctaveConvention, Spelling}, scale::{Scale, ScaleKind}};
+
+// Krumhansl-Kessler probe tone profiles
+const MAJOR_PROFILE: [f32; 12] = [6.35, 2.23, 3.48, 2.33, 4.38, 4.09, 2.52, 5.19, 2.39, 3.66, 2.29, 2.88];
+const MINOR_PROFILE: [f32; 12] = [6.33, 2.68, 3.52, 5.38, 2.60, 3.53, 2.54, 4.75, 3.98, 2.69, 3.34, 3.17];
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, Default, Serialize, Deserialize)]
+#[repr(C)]
+pub enum Mode
+{
+    #[default]
+    Major,
+    Minor,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, Default, Serialize, Deserialize)]
+#[repr(C)]
+pub struct Key
+{
+    pub tonic: u8,
+    pub mode: Mode,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Default, Serialize, Deserialize)]
+#[repr(C)]
+pub struct KeyEstimate
+{
+    pub key: Key,
+    pub correlation: f32,
+}
+
+fn correlation(xs: &[f32; 12], ys: &[f32; 12]) -> f32
+{
+    let mean_x = xs.iter().sum::<f32>() / 12.0;
+    let mean_y = ys.iter().sum::<f32>() / 12.0;
+
+    let mut covariance = 0.0;
+    let mut variance_x = 0.0;
+    let mut variance_y = 0.0;
+    for (x, y) in xs.iter().zip(ys)
+    {
+        covariance += (x - mean_x) * (y - mean_y);
+        variance_x += (x - mean_x) * (x - mean_x);
+        variance_y += (y - mean_y) * (y - mean_y);
+    }
+
+    let denominator = (variance_x * variance_y).sqrt();
+    if denominator > 0.0 { covariance / denominator } else { 0.0 }
+}
+
+impl Key
+{
+    pub fn new(tonic: u8, mode: Mode) -> Key
+    {
+        Key { tonic: tonic % 12, mode }
+    }
+
+    pub fn from_key_signature(sharps_or_flats: i8, minor: bool) -> Key
+    {
+        let major_tonic = (sharps_or_flats as i32 * 7).rem_euclid(12) as u8;
+        if minor
+        {
+            Key { tonic: (major_tonic + 9) % 12, mode: Mode::Minor }
+        }
+        else
+        {
+            Key { tonic: major_tonic, mode: Mode::Major }
+        }
+    }
+
+    pub fn key_signature(&self) -> i8
+    {
+        let major_tonic = match self.mode
+        {
+            Mode::Major => self.tonic,
+            Mode::Minor => (self.tonic + 3) % 12,
+        };
+        let sharps = (major_tonic as i32 * 7).rem_euclid(12);
+        (if sharps > 6 { sharps - 12 } else { sharps }) as i8
+    }
+
+    pub fn relative(&self) -> Key
+    {
+        match self.mode
+        {
+            Mode::Major => Key { tonic: (self.tonic + 9) % 12, mode: Mode::Minor },
+            Mode::Minor => Key { tonic: (self.tonic + 3) % 12, mode: Mode::Major },
+        }
+    }
+
+    pub fn scale(&self) -> Scale
+    {
+        match self.mode
+        {
+            Mode::Major => Scale::new(self.tonic, ScaleKind::Major),
+            Mode::Minor => Scale::new(self.tonic, ScaleKind::NaturalMinor),
+        }
+    }
+
+    pub fn spelling(&self) -> Spelling
+    {
+        Spelling::from_key_signature(self.key_signature())
+    }
+
+    pub fn rank(pitch_class_weights: &[f32; 12]) -> Vec<KeyEstimate>
+    {
+        let mut estimates: Vec<KeyEstimate> = [(Mode::Major, &MAJOR_PROFILE), (Mode::Minor, &MINOR_PROFILE)]
+            .into_iter()
+            .flat_map(|(mode, profile)| (0..12u8).map(move |tonic|
+            {
+                let mut rotated = [0.0; 12];
+                for (i, weight) in rotated.iter_mut().enumerate()
+                {
+                    *weight = profile[(i + 12 - tonic as usize) % 12];
+                }
+                KeyEstimate { key: Key { tonic, mode }, correlation: correlation(pitch_class_weights, &rotated) }
+            }))
+            .collect();
+
+        estimates.sort_by(|a, b| b.correlation.total_cmp(&a.correlation));
+        estimates
+    }
+
+    pub fn estimate(pitch_class_weights: &[f32; 12]) -> KeyEstimate
+    {
+        Key::rank(pitch_class_weights)[0]
+    }
+}
+
+impl fmt::Display for Key
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result
+    {
+        let name = NoteName::from_midi(self.tonic as i32, OctaveConvention::default(), self.spelling());
+        write!(f, "{}", name.letter.as_char())?;
+        match name.accidental
+        {
+            1 => write!(f, "#")?,
+            -1 => write!(f, "b")?,
+            _ => {}
+        }
+        match self.mode
+        {
+            Mode::Major => write!(f, " major"),
+            Mode::Minor => write!(f, " minor"),
+        }
+    }
+}
@@ -0,0 +1,9 @@
+pub mod chord;
+pub mod interval;
+pub mod key;
+pub mod note_name;
+pub mod pitch;
+pub mod prelude;
+pub mod scale;
+
+#[cfg(test)] mod tests;
\ No newline at end of file
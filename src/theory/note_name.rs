@@ -0,0 +1,221 @@
+use std::{fmt, str::FromStr};
+
+use serde::{Serialize, Deserialize};
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, Default, Serialize, Deserialize)]
+#[repr(C)]
+pub enum Letter
+{
+    #[default]
+    C,
+    D,
+    E,
+    F,
+    G,
+    A,
+    B,
+}
+
+impl Letter
+{
+    pub const ALL: [Letter; 7] = [Letter::C, Letter::D, Letter::E, Letter::F, Letter::G, Letter::A, Letter::B];
+
+    pub fn semitone(self) -> i32
+    {
+        match self
+        {
+            Letter::C => 0,
+            Letter::D => 2,
+            Letter::E => 4,
+            Letter::F => 5,
+            Letter::G => 7,
+            Letter::A => 9,
+            Letter::B => 11,
+        }
+    }
+
+    pub fn from_char(c: char) -> Option<Letter>
+    {
+        match c.to_ascii_uppercase()
+        {
+            'C' => Some(Letter::C),
+            'D' => Some(Letter::D),
+            'E' => Some(Letter::E),
+            'F' => Some(Letter::F),
+            'G' => Some(Letter::G),
+            'A' => Some(Letter::A),
+            'B' => Some(Letter::B),
+            _ => None,
+        }
+    }
+
+    pub fn as_char(self) -> char
+    {
+        match self
+        {
+            Letter::C => 'C',
+            Letter::D => 'D',
+            Letter::E => 'E',
+            Letter::F => 'F',
+            Letter::G => 'G',
+            Letter::A => 'A',
+            Letter::B => 'B',
+        }
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[repr(C)]
+pub struct OctaveConvention
+{
+    pub middle_c_octave: i32,
+}
+
+impl OctaveConvention
+{
+    pub const SCIENTIFIC: OctaveConvention = OctaveConvention { middle_c_octave: 4 };
+    pub const YAMAHA: OctaveConvention = OctaveConvention { middle_c_octave: 3 };
+    pub const MIDI_OCTAVE: OctaveConvention = OctaveConvention { middle_c_octave: 5 };
+}
+
+impl Default for OctaveConvention
+{
+    fn default() -> Self
+    {
+        OctaveConvention::SCIENTIFIC
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, Default, Serialize, Deserialize)]
+#[repr(C)]
+pub enum Spelling
+{
+    #[default]
+    Sharps,
+    Flats,
+}
+
+impl Spelling
+{
+    pub fn from_key_signature(sharps_or_flats: i8) -> Spelling
+    {
+        if sharps_or_flats < 0 { Spelling::Flats } else { Spelling::Sharps }
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, Default, Serialize, Deserialize)]
+#[repr(C)]
+pub struct NoteName
+{
+    pub letter: Letter,
+    pub accidental: i8,
+    pub octave: i32,
+}
+
+impl NoteName
+{
+    pub fn new(letter: Letter, accidental: i8, octave: i32) -> NoteName
+    {
+        NoteName { letter, accidental, octave }
+    }
+
+    pub fn pitch_class(&self) -> u8
+    {
+        (self.letter.semitone() + self.accidental as i32).rem_euclid(12) as u8
+    }
+
+    pub fn to_midi(&self, convention: OctaveConvention) -> i32
+    {
+        60 + (self.octave - convention.middle_c_octave) * 12 + self.letter.semitone() + self.accidental as i32
+    }
+
+    pub fn from_midi(note: i32, convention: OctaveConvention, spelling: Spelling) -> NoteName
+    {
+        let pitch_class = note.rem_euclid(12);
+        let octave = (note - 60).div_euclid(12) + convention.middle_c_octave;
+
+        let (letter, accidental) = match (pitch_class, spelling)
+        {
+            (0, _) => (Letter::C, 0),
+            (1, Spelling::Sharps) => (Letter::C, 1),
+            (1, Spelling::Flats) => (Letter::D, -1),
+            (2, _) => (Letter::D, 0),
+            (3, Spelling::Sharps) => (Letter::D, 1),
+            (3, Spelling::Flats) => (Letter::E, -1),
+            (4, _) => (Letter::E, 0),
+            (5, _) => (Letter::F, 0),
+            (6, Spelling::Sharps) => (Letter::F, 1),
+            (6, Spelling::Flats) => (Letter::G, -1),
+            (7, _) => (Letter::G, 0),
+            (8, Spelling::Sharps) => (Letter::G, 1),
+            (8, Spelling::Flats) => (Letter::A, -1),
+            (9, _) => (Letter::A, 0),
+            (10, Spelling::Sharps) => (Letter::A, 1),
+            (10, Spelling::Flats) => (Letter::B, -1),
+            _ => (Letter::B, 0),
+        };
+
+        NoteName { letter, accidental, octave }
+    }
+
+    pub fn parse_pitch_class(s: &str) -> Result<(Letter, i8, &str), ParseNoteNameError>
+    {
+        let letter = s.chars().next().and_then(Letter::from_char).ok_or(ParseNoteNameError)?;
+
+        let mut accidental = 0i8;
+        let mut rest = &s[1..];
+        loop
+        {
+            let mut chars = rest.chars();
+            match chars.next()
+            {
+                Some('#' | '♯') => accidental += 1,
+                Some('b' | '♭') => accidental -= 1,
+                Some('x') => accidental += 2,
+                _ => break,
+            }
+            rest = chars.as_str();
+        }
+
+        Ok((letter, accidental, rest))
+    }
+}
+
+impl fmt::Display for NoteName
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result
+    {
+        write!(f, "{}", self.letter.as_char())?;
+        let accidental = if self.accidental > 0 { "#" } else { "b" };
+        for _ in 0..self.accidental.unsigned_abs()
+        {
+            write!(f, "{}", accidental)?;
+        }
+        write!(f, "{}", self.octave)
+    }
+}
+
+impl FromStr for NoteName
+{
+    type Err = ParseNoteNameError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err>
+    {
+        let (letter, accidental, rest) = NoteName::parse_pitch_class(s.trim())?;
+        let octave = rest.parse::<i32>().map_err(|_| ParseNoteNameError)?;
+        Ok(NoteName { letter, accidental, octave })
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct ParseNoteNameError;
+
+impl fmt::Display for ParseNoteNameError
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result
+    {
+        write!(f, "invalid note name")
+    }
+}
+
+impl std::error::Error for ParseNoteNameError {}
@@ -0,0 +1,118 @@
+use std::{fmt, str::FromStr};
+
+use serde::{Serialize, Deserialize};
+
+use crate::midi::util::{ftom, mtof};
+
+use super::note_name::{NoteName, OctaveConvention, ParseNoteNameError, Spelling};
+
+#[derive(Debug, Copy, Clone, PartialEq, Default, Serialize, Deserialize)]
+#[repr(C)]
+pub struct Pitch
+{
+    pub note: i32,
+    pub cents: f32,
+}
+
+impl Pitch
+{
+    pub fn new(note: i32) -> Pitch
+    {
+        Pitch { note, cents: 0.0 }
+    }
+
+    pub fn with_cents(note: i32, cents: f32) -> Pitch
+    {
+        Pitch { note, cents }
+    }
+
+    pub fn from_midi_value(m: f32) -> Pitch
+    {
+        let note = m.round();
+        Pitch { note: note as i32, cents: (m - note) * 100.0 }
+    }
+
+    pub fn from_frequency(f: f32) -> Pitch
+    {
+        Pitch::from_midi_value(ftom(f))
+    }
+
+    pub fn midi_value(&self) -> f32
+    {
+        self.note as f32 + self.cents / 100.0
+    }
+
+    pub fn frequency(&self) -> f32
+    {
+        mtof(self.midi_value())
+    }
+
+    pub fn detune(self, cents: f32) -> Pitch
+    {
+        Pitch::from_midi_value(self.midi_value() + cents / 100.0)
+    }
+
+    pub fn transpose(self, semitones: i32) -> Pitch
+    {
+        Pitch { note: self.note + semitones, cents: self.cents }
+    }
+
+    pub fn pitch_class(&self) -> u8
+    {
+        self.note.rem_euclid(12) as u8
+    }
+
+    pub fn octave(&self, convention: OctaveConvention) -> i32
+    {
+        (self.note - 60).div_euclid(12) + convention.middle_c_octave
+    }
+
+    pub fn name(&self, convention: OctaveConvention, spelling: Spelling) -> NoteName
+    {
+        NoteName::from_midi(self.note, convention, spelling)
+    }
+
+    pub fn parse_with(s: &str, convention: OctaveConvention) -> Result<Pitch, ParseNoteNameError>
+    {
+        let s = s.trim();
+        let (name, cents) = match s.rfind(['+', '-']).filter(|&i| s.ends_with('c') && i > 0 && s[..i].ends_with(|c: char| c.is_ascii_digit()))
+        {
+            Some(i) => (&s[..i], s[i..s.len() - 1].parse::<f32>().map_err(|_| ParseNoteNameError)?),
+            None => (s, 0.0),
+        };
+
+        let name = name.parse::<NoteName>()?;
+        Ok(Pitch::with_cents(name.to_midi(convention), cents))
+    }
+
+    pub fn to_string_with(&self, convention: OctaveConvention, spelling: Spelling) -> String
+    {
+        let name = self.name(convention, spelling);
+        if self.cents == 0.0
+        {
+            format!("{}", name)
+        }
+        else
+        {
+            format!("{}{:+}c", name, self.cents.round())
+        }
+    }
+}
+
+impl fmt::Display for Pitch
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result
+    {
+        write!(f, "{}", self.to_string_with(OctaveConvention::default(), Spelling::default()))
+    }
+}
+
+impl FromStr for Pitch
+{
+    type Err = ParseNoteNameError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err>
+    {
+        Pitch::parse_with(s, OctaveConvention::default())
+    }
+}
@@ -0,0 +1,9 @@
+pub use super::
+{
+    chord::{Chord, ChordQuality},
+    interval::Interval,
+    key::{Key, KeyEstimate, Mode},
+    note_name::{Letter, NoteName, OctaveConvention, ParseNoteNameError, Spelling},
+    pitch::Pitch,
+    scale::{Scale, ScaleKind},
+};
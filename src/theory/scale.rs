@@ -0,0 +1,140 @@
+use serde::{Serialize, Deserialize};
+
+use super::chord::{Chord, ChordQuality};
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, Default, Serialize, Deserialize)]
+#[repr(C)]
+pub enum ScaleKind
+{
+    #[default]
+    Major,
+    NaturalMinor,
+    HarmonicMinor,
+    MelodicMinor,
+    Dorian,
+    Phrygian,
+    Lydian,
+    Mixolydian,
+    Locrian,
+    MajorPentatonic,
+    MinorPentatonic,
+    Blues,
+    WholeTone,
+    Chromatic,
+}
+
+impl ScaleKind
+{
+    pub fn intervals(self) -> &'static [u8]
+    {
+        match self
+        {
+            ScaleKind::Major => &[0, 2, 4, 5, 7, 9, 11],
+            ScaleKind::NaturalMinor => &[0, 2, 3, 5, 7, 8, 10],
+            ScaleKind::HarmonicMinor => &[0, 2, 3, 5, 7, 8, 11],
+            ScaleKind::MelodicMinor => &[0, 2, 3, 5, 7, 9, 11],
+            ScaleKind::Dorian => &[0, 2, 3, 5, 7, 9, 10],
+            ScaleKind::Phrygian => &[0, 1, 3, 5, 7, 8, 10],
+            ScaleKind::Lydian => &[0, 2, 4, 6, 7, 9, 11],
+            ScaleKind::Mixolydian => &[0, 2, 4, 5, 7, 9, 10],
+            ScaleKind::Locrian => &[0, 1, 3, 5, 6, 8, 10],
+            ScaleKind::MajorPentatonic => &[0, 2, 4, 7, 9],
+            ScaleKind::MinorPentatonic => &[0, 3, 5, 7, 10],
+            ScaleKind::Blues => &[0, 3, 5, 6, 7, 10],
+            ScaleKind::WholeTone => &[0, 2, 4, 6, 8, 10],
+            ScaleKind::Chromatic => &[0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11],
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Default, Serialize, Deserialize)]
+#[repr(C)]
+pub struct Scale
+{
+    pub root: u8,
+    pub intervals: Vec<u8>,
+}
+
+impl Scale
+{
+    pub fn new(root: u8, kind: ScaleKind) -> Scale
+    {
+        Scale { root: root % 12, intervals: kind.intervals().to_vec() }
+    }
+
+    pub fn custom(root: u8, intervals: &[u8]) -> Scale
+    {
+        let mut intervals: Vec<u8> = intervals.iter().map(|i| i % 12).collect();
+        intervals.push(0);
+        intervals.sort();
+        intervals.dedup();
+
+        Scale { root: root % 12, intervals }
+    }
+
+    pub fn len(&self) -> usize
+    {
+        self.intervals.len()
+    }
+
+    pub fn is_empty(&self) -> bool
+    {
+        self.intervals.is_empty()
+    }
+
+    pub fn pitch_classes(&self) -> impl Iterator<Item = u8> + '_
+    {
+        self.intervals.iter().map(move |i| (self.root + i) % 12)
+    }
+
+    pub fn contains(&self, pitch_class: u8) -> bool
+    {
+        self.degree_of(pitch_class).is_some()
+    }
+
+    pub fn degree_of(&self, pitch_class: u8) -> Option<usize>
+    {
+        let interval = (pitch_class as i32 - self.root as i32).rem_euclid(12) as u8;
+        self.intervals.iter().position(|&i| i == interval)
+    }
+
+    pub fn note(&self, root_note: i32, degree: i32) -> i32
+    {
+        let len = self.intervals.len() as i32;
+        if len == 0
+        {
+            return root_note;
+        }
+        root_note + 12 * degree.div_euclid(len) + self.intervals[degree.rem_euclid(len) as usize] as i32
+    }
+
+    pub fn snap(&self, note: i32) -> i32
+    {
+        if self.intervals.is_empty()
+        {
+            return note;
+        }
+
+        (0..12)
+            .flat_map(|distance| [note - distance, note + distance])
+            .find(|n| self.contains(n.rem_euclid(12) as u8))
+            .unwrap_or(note)
+    }
+
+    pub fn triad(&self, degree: usize) -> Option<Chord>
+    {
+        self.chord(degree, &[0, 2, 4])
+    }
+
+    pub fn seventh(&self, degree: usize) -> Option<Chord>
+    {
+        self.chord(degree, &[0, 2, 4, 6])
+    }
+
+    fn chord(&self, degree: usize, steps: &[i32]) -> Option<Chord>
+    {
+        let root = self.note(self.root as i32, degree as i32);
+        let intervals: Vec<u8> = steps.iter().map(|&s| (self.note(self.root as i32, degree as i32 + s) - root) as u8).collect();
+        ChordQuality::from_intervals(&intervals).map(|quality| Chord::new(root.rem_euclid(12) as u8, quality))
+    }
+}
@@ -0,0 +1,3 @@
+#[cfg(test)] mod test_chord;
+#[cfg(test)] mod test_pitch;
+#[cfg(test)] mod test_scale;
\ No newline at end of file
@@ -0,0 +1,24 @@
+use crate::theory::prelude::*;
+
+#[test]
+fn test_chord_tones()
+{
+    let chord = Chord::new(0, ChordQuality::Major);
+    assert_eq!(chord.tones(60), vec![60, 64, 67]);
+
+    let chord = Chord::new(9, ChordQuality::Minor7);
+    assert_eq!(chord.tones(60), vec![69, 72, 76, 79]);
+    assert_eq!(chord.pitch_classes().collect::<Vec<_>>(), vec![9, 0, 4, 7]);
+    assert!(chord.contains(4));
+    assert!(!chord.contains(5));
+}
+
+#[test]
+fn test_chord_names()
+{
+    assert_eq!(Chord::new(1, ChordQuality::Minor7).to_string(), "C#m7");
+    assert_eq!(Chord::new(10, ChordQuality::Major7).to_string_with(Spelling::Flats), "Bbmaj7");
+    assert_eq!(Chord::new(11, ChordQuality::Diminished).to_string(), "Bdim");
+    assert_eq!(ChordQuality::from_intervals(&[0, 4, 7, 10]), Some(ChordQuality::Dominant7));
+    assert_eq!(ChordQuality::from_intervals(&[0, 1, 2]), None);
+}
@@ -0,0 +1,71 @@
+use crate::midi::{print_note_name::note_name, util::{ftom, mtof}};
+use crate::theory::prelude::*;
+
+#[test]
+fn test_parse_and_display_round_trip()
+{
+    for s in ["C4", "C#4", "Bb3", "A0", "G#9", "C-1", "Ebb2", "Fx5"]
+    {
+        let name: NoteName = s.parse().unwrap();
+        assert_eq!(name.to_string(), s.replace('x', "##"));
+    }
+
+    assert_eq!("C4".parse::<Pitch>().unwrap().note, 60);
+    assert_eq!("A4".parse::<Pitch>().unwrap().note, 69);
+    assert_eq!("Bb3".parse::<Pitch>().unwrap().note, 58);
+    assert_eq!("C-1".parse::<Pitch>().unwrap().note, 0);
+    assert_eq!(Pitch::new(61).to_string(), "C#4");
+    assert_eq!(Pitch::new(58).to_string_with(OctaveConvention::SCIENTIFIC, Spelling::Flats), "Bb3");
+
+    assert!("H4".parse::<Pitch>().is_err());
+    assert!("C".parse::<Pitch>().is_err());
+    assert!("".parse::<NoteName>().is_err());
+}
+
+#[test]
+fn test_octave_conventions()
+{
+    assert_eq!(Pitch::parse_with("C3", OctaveConvention::YAMAHA).unwrap().note, 60);
+    assert_eq!(Pitch::parse_with("C5", OctaveConvention::MIDI_OCTAVE).unwrap().note, 60);
+    assert_eq!(Pitch::new(60).octave(OctaveConvention::YAMAHA), 3);
+    assert_eq!(Pitch::new(59).octave(OctaveConvention::SCIENTIFIC), 3);
+    assert_eq!(note_name(60), "C5");
+    assert_eq!(note_name(13), "C#1");
+}
+
+#[test]
+fn test_frequency_conversions()
+{
+    assert!((ftom(440.0) - 69.0).abs() < 1e-4);
+    assert!((ftom(mtof(60.0)) - 60.0).abs() < 1e-4);
+    assert!((Pitch::new(69).frequency() - 440.0).abs() < 1e-3);
+
+    let pitch = Pitch::from_frequency(445.0);
+    assert_eq!(pitch.note, 69);
+    assert!((pitch.cents - 19.56).abs() < 0.05);
+}
+
+#[test]
+fn test_cents_detuning()
+{
+    let pitch = Pitch::new(60).detune(30.0);
+    assert_eq!(pitch.note, 60);
+    assert!((pitch.cents - 30.0).abs() < 1e-3);
+
+    let pitch = Pitch::new(60).detune(70.0);
+    assert_eq!(pitch.note, 61);
+    assert!((pitch.cents + 30.0).abs() < 1e-3);
+
+    assert_eq!(Pitch::with_cents(60, 12.0).to_string(), "C4+12c");
+    assert_eq!("C4+12c".parse::<Pitch>().unwrap(), Pitch::with_cents(60, 12.0));
+    assert_eq!("C-1-5c".parse::<Pitch>().unwrap(), Pitch::with_cents(0, -5.0));
+}
+
+#[test]
+fn test_intervals()
+{
+    assert_eq!(Interval::between(60, 67), Interval::PERFECT_FIFTH);
+    assert_eq!(Interval::PERFECT_FIFTH.invert(), Interval::PERFECT_FOURTH);
+    assert_eq!((Interval::OCTAVE + Interval::MAJOR_THIRD).simple(), Interval::MAJOR_THIRD);
+    assert!((Interval::OCTAVE.ratio() - 2.0).abs() < 1e-6);
+}
@@ -0,0 +1,53 @@
+use crate::theory::prelude::*;
+
+#[test]
+fn test_scale_membership()
+{
+    let c_major = Scale::new(0, ScaleKind::Major);
+    assert_eq!(c_major.pitch_classes().collect::<Vec<_>>(), vec![0, 2, 4, 5, 7, 9, 11]);
+    assert!(c_major.contains(4));
+    assert!(!c_major.contains(6));
+
+    let a_minor = Scale::new(9, ScaleKind::NaturalMinor);
+    assert_eq!(a_minor.pitch_classes().collect::<Vec<_>>(), vec![9, 11, 0, 2, 4, 5, 7]);
+    assert_eq!(a_minor.degree_of(0), Some(2));
+
+    let pentatonic = Scale::new(7, ScaleKind::MinorPentatonic);
+    assert_eq!(pentatonic.len(), 5);
+    assert!(pentatonic.contains(10));
+}
+
+#[test]
+fn test_custom_scales()
+{
+    let scale = Scale::custom(2, &[7, 3, 15]);
+    assert_eq!(scale.intervals, vec![0, 3, 7]);
+    assert_eq!(scale.note(62, 3), 74);
+    assert_eq!(scale.note(62, -1), 57);
+}
+
+#[test]
+fn test_snapping()
+{
+    let c_major = Scale::new(0, ScaleKind::Major);
+    assert_eq!(c_major.snap(60), 60);
+    assert_eq!(c_major.snap(61), 60);
+    assert_eq!(c_major.snap(66), 65);
+    assert_eq!(c_major.snap(-1), -1);
+
+    let whole_tone = Scale::new(1, ScaleKind::WholeTone);
+    assert_eq!(whole_tone.snap(60), 59);
+
+    assert_eq!(Scale::custom(0, &[]).snap(67), 72);
+}
+
+#[test]
+fn test_diatonic_chords()
+{
+    let c_major = Scale::new(0, ScaleKind::Major);
+    assert_eq!(c_major.triad(0), Some(Chord::new(0, ChordQuality::Major)));
+    assert_eq!(c_major.triad(1), Some(Chord::new(2, ChordQuality::Minor)));
+    assert_eq!(c_major.triad(6), Some(Chord::new(11, ChordQuality::Diminished)));
+    assert_eq!(c_major.seventh(4), Some(Chord::new(7, ChordQuality::Dominant7)));
+    assert_eq!(c_major.seventh(6), Some(Chord::new(11, ChordQuality::HalfDiminished7)));
+}